@@ -0,0 +1,2 @@
+-- Rollback 015: Drop habit_checkins (habit_completions rollup rows are kept)
+DROP TABLE IF EXISTS habit_checkins;
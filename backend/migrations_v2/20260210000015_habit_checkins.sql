@@ -0,0 +1,49 @@
+-- ============================================================================
+-- 015: Habit Check-ins (event log)
+-- ============================================================================
+-- Individual timestamped check-ins. A habit like "drink water 8x" records one
+-- row per glass, so we keep *when* each unit happened.
+--
+-- habit_completions remains the per-day rollup: its value is the SUM of the
+-- day's check-ins and the row is removed when the last check-in is undone.
+-- Streaks, heatmaps and stats keep reading the rollup; the application keeps
+-- both tables in sync inside one transaction.
+-- ============================================================================
+
+CREATE TABLE habit_checkins (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    habit_id            UUID NOT NULL REFERENCES habits(id) ON DELETE CASCADE,
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    -- Day this check-in rolls up into (same bucket as habit_completions)
+    local_date_bucket   DATE NOT NULL,
+
+    -- Exact instant of the check-in (client-supplied or server NOW())
+    checked_in_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Units recorded by this check-in
+    value               INTEGER NOT NULL DEFAULT 1,
+
+    note                TEXT,
+
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_checkin_value CHECK (value >= 1),
+
+    CONSTRAINT chk_checkin_note_length CHECK (
+        note IS NULL OR char_length(note) <= 5000
+    )
+);
+
+-- Rollup + undo: the day's check-ins for a habit, newest first
+CREATE INDEX idx_checkins_habit_date
+    ON habit_checkins (habit_id, local_date_bucket, checked_in_at DESC);
+
+-- Time-of-day analytics across a user's history
+CREATE INDEX idx_checkins_user_time
+    ON habit_checkins (user_id, checked_in_at DESC);
+
+-- Backfill: every existing rollup row becomes a single check-in
+INSERT INTO habit_checkins (habit_id, user_id, local_date_bucket, checked_in_at, value, note, created_at)
+SELECT habit_id, user_id, local_date_bucket, created_at, value, note, created_at
+FROM habit_completions;
@@ -0,0 +1,3 @@
+-- Rollback 016: Drop timer sessions
+DROP TABLE IF EXISTS habit_timer_sessions;
+DROP TYPE IF EXISTS timer_status;
@@ -0,0 +1,70 @@
+-- ============================================================================
+-- 016: Habit Timer Sessions
+-- ============================================================================
+-- Server-side start/pause/resume/stop timers for duration habits
+-- ("practice guitar 30 min"). Living on the server lets a timer started on
+-- one device be stopped on another and survive app restarts.
+--
+-- Elapsed time = accumulated_secs (closed segments) + the open segment since
+-- resumed_at while running. On stop, whole elapsed minutes are written as a
+-- check-in (migration 015) so they roll up into the day's completion value.
+-- ============================================================================
+
+CREATE TYPE timer_status AS ENUM ('running', 'paused', 'stopped');
+
+CREATE TABLE habit_timer_sessions (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    habit_id            UUID NOT NULL REFERENCES habits(id) ON DELETE CASCADE,
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    status              timer_status NOT NULL DEFAULT 'running',
+
+    -- Day the elapsed minutes are credited to (bucket at start time)
+    local_date_bucket   DATE NOT NULL,
+
+    started_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Start of the currently open segment; NULL while paused/stopped
+    resumed_at          TIMESTAMPTZ,
+    -- Seconds from segments already closed by pause/stop
+    accumulated_secs    BIGINT NOT NULL DEFAULT 0,
+    stopped_at          TIMESTAMPTZ,
+
+    -- Check-in written on stop (NULL if under one minute elapsed)
+    checkin_id          UUID REFERENCES habit_checkins(id) ON DELETE SET NULL,
+
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_timer_accumulated_non_negative CHECK (accumulated_secs >= 0),
+
+    -- An open segment exists exactly while running
+    CONSTRAINT chk_timer_running_segment CHECK (
+        (status = 'running' AND resumed_at IS NOT NULL)
+        OR
+        (status <> 'running' AND resumed_at IS NULL)
+    ),
+
+    CONSTRAINT chk_timer_stopped_consistency CHECK (
+        (status = 'stopped' AND stopped_at IS NOT NULL)
+        OR
+        (status <> 'stopped' AND stopped_at IS NULL)
+    )
+);
+
+-- At most one active (running or paused) timer per habit
+CREATE UNIQUE INDEX idx_timer_sessions_active_habit
+    ON habit_timer_sessions (habit_id)
+    WHERE status <> 'stopped';
+
+-- "Which timers does this user have open?" (app start, other devices)
+CREATE INDEX idx_timer_sessions_user_active
+    ON habit_timer_sessions (user_id)
+    WHERE status <> 'stopped';
+
+CREATE TRIGGER trg_timer_sessions_updated_at
+    BEFORE UPDATE ON habit_timer_sessions
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TRIGGER trg_timer_sessions_protect_created
+    BEFORE UPDATE ON habit_timer_sessions
+    FOR EACH ROW EXECUTE FUNCTION protect_created_at();
@@ -0,0 +1,9 @@
+-- Rollback 017: remove completion windows
+DROP FUNCTION IF EXISTS to_user_local(TIMESTAMPTZ, TEXT);
+ALTER TABLE habit_completions DROP COLUMN IF EXISTS is_late;
+ALTER TABLE habit_checkins DROP COLUMN IF EXISTS is_late;
+ALTER TABLE habits DROP CONSTRAINT IF EXISTS chk_habit_window;
+ALTER TABLE habits
+    DROP COLUMN IF EXISTS streak_on_time_only,
+    DROP COLUMN IF EXISTS window_end,
+    DROP COLUMN IF EXISTS window_start;
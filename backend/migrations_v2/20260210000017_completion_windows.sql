@@ -0,0 +1,43 @@
+-- ============================================================================
+-- 017: Completion Time Windows
+-- ============================================================================
+-- Optional local-time window per habit ("before 9am", "22:00-06:00").
+-- A window whose start is after its end wraps past midnight.
+--
+-- Check-ins outside the window are stored with is_late = true; the daily
+-- rollup is late only when every check-in of the day was late. Habits can
+-- opt into streaks that count on-time days only.
+-- ============================================================================
+
+ALTER TABLE habits
+    ADD COLUMN IF NOT EXISTS window_start TIME,
+    ADD COLUMN IF NOT EXISTS window_end TIME,
+    ADD COLUMN IF NOT EXISTS streak_on_time_only BOOLEAN NOT NULL DEFAULT false;
+
+-- Both bounds or neither; an empty window is not allowed
+ALTER TABLE habits
+    ADD CONSTRAINT chk_habit_window CHECK (
+        (window_start IS NULL AND window_end IS NULL)
+        OR
+        (window_start IS NOT NULL AND window_end IS NOT NULL AND window_start <> window_end)
+    );
+
+ALTER TABLE habit_checkins
+    ADD COLUMN IF NOT EXISTS is_late BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE habit_completions
+    ADD COLUMN IF NOT EXISTS is_late BOOLEAN NOT NULL DEFAULT false;
+
+-- ============================================================================
+-- Utility: convert an instant to the user's wall-clock time.
+-- Falls back to UTC for unknown timezone names instead of erroring, so a bad
+-- users.timezone value can't abort a write transaction.
+-- ============================================================================
+CREATE OR REPLACE FUNCTION to_user_local(ts TIMESTAMPTZ, tz TEXT)
+RETURNS TIMESTAMP AS $$
+BEGIN
+    RETURN ts AT TIME ZONE tz;
+EXCEPTION WHEN invalid_parameter_value THEN
+    RETURN ts AT TIME ZONE 'UTC';
+END;
+$$ LANGUAGE plpgsql STABLE;
@@ -0,0 +1,5 @@
+-- Rollback 018: drop note search columns
+DROP INDEX IF EXISTS idx_mood_logs_note_tsv;
+ALTER TABLE mood_logs DROP COLUMN IF EXISTS note_tsv;
+DROP INDEX IF EXISTS idx_completions_note_tsv;
+ALTER TABLE habit_completions DROP COLUMN IF EXISTS note_tsv;
@@ -0,0 +1,21 @@
+-- ============================================================================
+-- 018: Full-text search over journal notes
+-- ============================================================================
+-- Generated tsvector columns + GIN indexes on the two free-text note fields:
+-- habit_completions.note and mood_logs.note (the daily log). The search
+-- endpoint matches with websearch_to_tsquery and highlights via ts_headline.
+-- ============================================================================
+
+ALTER TABLE habit_completions
+    ADD COLUMN IF NOT EXISTS note_tsv tsvector
+    GENERATED ALWAYS AS (to_tsvector('english', COALESCE(note, ''))) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_completions_note_tsv
+    ON habit_completions USING GIN (note_tsv);
+
+ALTER TABLE mood_logs
+    ADD COLUMN IF NOT EXISTS note_tsv tsvector
+    GENERATED ALWAYS AS (to_tsvector('english', COALESCE(note, ''))) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_mood_logs_note_tsv
+    ON mood_logs USING GIN (note_tsv);
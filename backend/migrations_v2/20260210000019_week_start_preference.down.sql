@@ -0,0 +1,3 @@
+-- Rollback 019: drop week start preference
+ALTER TABLE users DROP COLUMN IF EXISTS week_start;
+DROP TYPE IF EXISTS week_start_day;
@@ -0,0 +1,13 @@
+-- ============================================================================
+-- 019: Week start preference
+-- ============================================================================
+-- Users choose whether their weeks (weekly review, week navigation) begin on
+-- Monday (ISO 8601, the default) or Sunday. A Sunday-start week is labelled
+-- with the ISO week of the Monday that follows it, so "2026-W06" always names
+-- the same seven days apart from the one-day shift.
+-- ============================================================================
+
+CREATE TYPE week_start_day AS ENUM ('monday', 'sunday');
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS week_start week_start_day NOT NULL DEFAULT 'monday';
@@ -0,0 +1,2 @@
+-- Rollback 020: drop cached period reviews
+DROP TABLE IF EXISTS period_reviews;
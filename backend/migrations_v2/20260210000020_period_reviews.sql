@@ -0,0 +1,27 @@
+-- ============================================================================
+-- 020: Cached period reviews
+-- ============================================================================
+-- Monthly and yearly reviews scan a full period of completions and mood logs.
+-- Once a period has closed its numbers only change if the user backfills, so
+-- the rendered review is stored here and served as-is. Writes that touch a
+-- date inside a cached period (completion rollups, mood logs) delete the
+-- affected rows so the next request regenerates them.
+-- ============================================================================
+
+CREATE TABLE period_reviews (
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    period_kind     TEXT NOT NULL,
+    period_start    DATE NOT NULL,
+    period_end      DATE NOT NULL,
+    payload         JSONB NOT NULL,
+    generated_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, period_kind, period_start),
+
+    CONSTRAINT chk_period_kind CHECK (period_kind IN ('month', 'year')),
+    CONSTRAINT chk_period_order CHECK (period_end >= period_start)
+);
+
+-- Invalidation: "every cached period containing this date"
+CREATE INDEX idx_period_reviews_user_range
+    ON period_reviews (user_id, period_start, period_end);
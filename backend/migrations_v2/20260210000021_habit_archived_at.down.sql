@@ -0,0 +1,4 @@
+-- Rollback 021: drop habit archive timestamp
+DROP TRIGGER IF EXISTS trg_habits_archived_at ON habits;
+DROP FUNCTION IF EXISTS set_habit_archived_at();
+ALTER TABLE habits DROP COLUMN IF EXISTS archived_at;
@@ -0,0 +1,32 @@
+-- ============================================================================
+-- 021: Habit archive timestamp
+-- ============================================================================
+-- Historical stats need to know whether a habit was active on a past date,
+-- not just whether it is archived now. archived_at is maintained by trigger
+-- whenever is_archived flips, so every write path keeps it in sync.
+--
+-- Existing archived habits are backfilled with updated_at, the closest
+-- available approximation of when they were archived.
+-- ============================================================================
+
+ALTER TABLE habits
+    ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+
+UPDATE habits SET archived_at = updated_at
+WHERE is_archived = true AND archived_at IS NULL;
+
+CREATE OR REPLACE FUNCTION set_habit_archived_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.is_archived AND NOT OLD.is_archived THEN
+        NEW.archived_at := NOW();
+    ELSIF NOT NEW.is_archived THEN
+        NEW.archived_at := NULL;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_habits_archived_at
+    BEFORE UPDATE OF is_archived ON habits
+    FOR EACH ROW EXECUTE FUNCTION set_habit_archived_at();
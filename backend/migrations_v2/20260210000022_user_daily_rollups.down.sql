@@ -0,0 +1,2 @@
+-- Rollback 022: drop per-user daily rollups
+DROP TABLE IF EXISTS user_daily_rollups;
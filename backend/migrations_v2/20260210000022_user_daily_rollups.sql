@@ -0,0 +1,35 @@
+-- ============================================================================
+-- 022: Per-user daily rollups
+-- ============================================================================
+-- One row per user per local day with the lifecycle- and schedule-aware
+-- counts behind daily stats, heatmaps and reviews, so analytics over a year
+-- reads 365 small rows instead of every completion.
+--
+-- Maintenance:
+--   - completion writes refresh the affected day (and the rest of its week,
+--     since weekly-target misses depend on earlier days) in the same tx
+--   - habit edits that change history delete the affected rows
+--   - readers compute and store any missing days on demand
+--   - a background job rebuilds recent days to heal drift
+-- ============================================================================
+
+CREATE TABLE user_daily_rollups (
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    local_date_bucket   DATE NOT NULL,
+
+    -- Habits active and due that day / how many of those were done
+    due_count           INTEGER NOT NULL DEFAULT 0,
+    completed_count     INTEGER NOT NULL DEFAULT 0,
+    -- All completion rows that day (scheduled or not) and their summed value
+    completion_count    INTEGER NOT NULL DEFAULT 0,
+    total_value         BIGINT NOT NULL DEFAULT 0,
+
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, local_date_bucket),
+
+    CONSTRAINT chk_rollup_counts CHECK (
+        due_count >= 0 AND completed_count >= 0 AND completed_count <= due_count
+        AND completion_count >= 0 AND total_value >= 0
+    )
+);
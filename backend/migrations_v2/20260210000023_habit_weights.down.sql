@@ -0,0 +1,11 @@
+-- Rollback 023: drop habit weights and weighted rollup sums
+ALTER TABLE user_daily_rollups
+    DROP CONSTRAINT IF EXISTS chk_rollup_weights,
+    DROP COLUMN IF EXISTS due_weight,
+    DROP COLUMN IF EXISTS completed_weight;
+
+ALTER TABLE habits
+    DROP CONSTRAINT IF EXISTS chk_habit_priority,
+    DROP CONSTRAINT IF EXISTS chk_habit_difficulty,
+    DROP COLUMN IF EXISTS priority,
+    DROP COLUMN IF EXISTS difficulty;
@@ -0,0 +1,25 @@
+-- ============================================================================
+-- 023: Habit priority and difficulty weights
+-- ============================================================================
+-- Optional 1–3 weights per habit (2 = normal). A habit's weight in the daily
+-- score is priority × difficulty, so with the defaults every habit weighs the
+-- same and the score equals the plain completion rate.
+--
+-- Rollups gain the weighted due/completed sums behind the score. Existing
+-- rows are dropped; readers refill them on demand with the new columns.
+-- ============================================================================
+
+ALTER TABLE habits
+    ADD COLUMN IF NOT EXISTS priority SMALLINT NOT NULL DEFAULT 2,
+    ADD COLUMN IF NOT EXISTS difficulty SMALLINT NOT NULL DEFAULT 2,
+    ADD CONSTRAINT chk_habit_priority CHECK (priority BETWEEN 1 AND 3),
+    ADD CONSTRAINT chk_habit_difficulty CHECK (difficulty BETWEEN 1 AND 3);
+
+DELETE FROM user_daily_rollups;
+
+ALTER TABLE user_daily_rollups
+    ADD COLUMN due_weight INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN completed_weight INTEGER NOT NULL DEFAULT 0,
+    ADD CONSTRAINT chk_rollup_weights CHECK (
+        due_weight >= 0 AND completed_weight >= 0 AND completed_weight <= due_weight
+    );
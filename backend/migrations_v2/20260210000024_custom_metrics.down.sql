@@ -0,0 +1,3 @@
+-- Rollback 024: drop custom metrics and their values
+DROP TABLE IF EXISTS custom_metric_values;
+DROP TABLE IF EXISTS custom_metrics;
@@ -0,0 +1,64 @@
+-- ============================================================================
+-- 024: User-defined daily metrics
+-- ============================================================================
+-- Mood logs cover three fixed 1–5 scales. Custom metrics let a user track
+-- anything else per day (sleep hours, weight, caffeine cups, pain level):
+--
+--   scale   — integer score within [min_value, max_value] (both required)
+--   number  — any number, optionally bounded by min_value/max_value
+--   boolean — 0 or 1; no unit or range
+--
+-- Values are one row per metric per local day, like mood_logs. Deleting a
+-- metric deletes its values; archiving keeps them but hides the metric.
+-- ============================================================================
+
+CREATE TABLE custom_metrics (
+    id              UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name            VARCHAR(50) NOT NULL,
+    kind            TEXT NOT NULL,
+    unit            VARCHAR(20),
+    min_value       DOUBLE PRECISION,
+    max_value       DOUBLE PRECISION,
+    sort_order      INTEGER NOT NULL DEFAULT 0,
+    is_archived     BOOLEAN NOT NULL DEFAULT false,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT uq_custom_metric_user_name UNIQUE (user_id, name),
+    CONSTRAINT chk_custom_metric_kind CHECK (kind IN ('scale', 'number', 'boolean')),
+    CONSTRAINT chk_custom_metric_range CHECK (
+        min_value IS NULL OR max_value IS NULL OR min_value < max_value
+    ),
+    CONSTRAINT chk_custom_metric_scale CHECK (
+        kind <> 'scale' OR (min_value IS NOT NULL AND max_value IS NOT NULL)
+    ),
+    CONSTRAINT chk_custom_metric_boolean CHECK (
+        kind <> 'boolean' OR (unit IS NULL AND min_value IS NULL AND max_value IS NULL)
+    )
+);
+
+CREATE INDEX idx_custom_metrics_user ON custom_metrics (user_id, sort_order);
+
+CREATE TABLE custom_metric_values (
+    metric_id           UUID NOT NULL REFERENCES custom_metrics(id) ON DELETE CASCADE,
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    local_date_bucket   DATE NOT NULL,
+    value               DOUBLE PRECISION NOT NULL,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (metric_id, local_date_bucket)
+);
+
+-- Correlations and trends read all of a user's values for a date range
+CREATE INDEX idx_custom_metric_values_user_date
+    ON custom_metric_values (user_id, local_date_bucket);
+
+CREATE TRIGGER trg_custom_metrics_updated_at
+    BEFORE UPDATE ON custom_metrics
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TRIGGER trg_custom_metric_values_updated_at
+    BEFORE UPDATE ON custom_metric_values
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
@@ -0,0 +1,4 @@
+-- Rollback 025: drop journal entries and the insights opt-in
+DROP TABLE IF EXISTS journal_entry_habits;
+DROP TABLE IF EXISTS journal_entries;
+ALTER TABLE users DROP COLUMN IF EXISTS journal_in_insights;
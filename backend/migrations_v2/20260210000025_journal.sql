@@ -0,0 +1,47 @@
+-- ============================================================================
+-- 025: Journal entries
+-- ============================================================================
+-- Free-form reflections beyond the single mood-log note: any number per day,
+-- tagged, optionally answering one of the server-side prompts (prompt_id is
+-- the stable id from services::journal, not a foreign key) and linked to the
+-- habits they are about.
+--
+-- Insights only read entries when the user opts in via
+-- users.journal_in_insights.
+-- ============================================================================
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS journal_in_insights BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE journal_entries (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    local_date_bucket   DATE NOT NULL,
+    prompt_id           TEXT,
+    body                TEXT NOT NULL,
+    tags                TEXT[] NOT NULL DEFAULT '{}',
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_journal_body_length CHECK (char_length(body) BETWEEN 1 AND 10000),
+    CONSTRAINT chk_journal_tag_count CHECK (cardinality(tags) <= 10)
+);
+
+CREATE INDEX idx_journal_entries_user_date
+    ON journal_entries (user_id, local_date_bucket DESC, created_at DESC);
+
+-- Tag filter: tags @> ARRAY['sleep']
+CREATE INDEX idx_journal_entries_tags ON journal_entries USING GIN (tags);
+
+CREATE TABLE journal_entry_habits (
+    entry_id    UUID NOT NULL REFERENCES journal_entries(id) ON DELETE CASCADE,
+    habit_id    UUID NOT NULL REFERENCES habits(id) ON DELETE CASCADE,
+
+    PRIMARY KEY (entry_id, habit_id)
+);
+
+CREATE INDEX idx_journal_entry_habits_habit ON journal_entry_habits (habit_id);
+
+CREATE TRIGGER trg_journal_entries_updated_at
+    BEFORE UPDATE ON journal_entries
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
@@ -0,0 +1,2 @@
+-- Rollback 026: drop AI insight usage tracking
+DROP TABLE IF EXISTS ai_insight_usage;
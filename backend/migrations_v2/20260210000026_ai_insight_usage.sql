@@ -0,0 +1,25 @@
+-- ============================================================================
+-- 026: AI insight usage
+-- ============================================================================
+-- One row per model call made for a user's insight, for quota enforcement
+-- (ai_insights_per_week: Free 0, Plus 1 per ISO week, Pro unlimited but at
+-- most one call per hour). A row is reserved before the call and removed
+-- again if the call fails and the free fallback is served instead, so only
+-- calls that produced an insight count.
+-- ============================================================================
+
+CREATE TABLE ai_insight_usage (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    -- Monday of the ISO week the call counts against
+    week_start_date     DATE NOT NULL,
+    used_at             TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_usage_week_is_monday CHECK (EXTRACT(ISODOW FROM week_start_date) = 1)
+);
+
+CREATE INDEX idx_ai_insight_usage_user_week
+    ON ai_insight_usage (user_id, week_start_date);
+CREATE INDEX idx_ai_insight_usage_user_time
+    ON ai_insight_usage (user_id, used_at DESC);
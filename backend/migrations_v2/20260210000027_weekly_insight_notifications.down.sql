@@ -0,0 +1,2 @@
+-- Rollback 027: drop the weekly insight notification preference
+ALTER TABLE users DROP COLUMN IF EXISTS insight_notifications;
@@ -0,0 +1,11 @@
+-- ============================================================================
+-- 027: Weekly insight notifications
+-- ============================================================================
+-- The Monday insight job generates each eligible user's weekly insight in
+-- their timezone, pushes an insight_ready WebSocket event and enqueues an
+-- insight_ready notification job. Users can turn the notification off; the
+-- insight is still generated and the WebSocket event still sent.
+-- ============================================================================
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS insight_notifications BOOLEAN NOT NULL DEFAULT true;
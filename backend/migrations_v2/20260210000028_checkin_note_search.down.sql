@@ -0,0 +1,3 @@
+-- Rollback 028: drop check-in note search column
+DROP INDEX IF EXISTS idx_checkins_note_tsv;
+ALTER TABLE habit_checkins DROP COLUMN IF EXISTS note_tsv;
@@ -0,0 +1,14 @@
+-- ============================================================================
+-- 028: Full-text search over check-in notes
+-- ============================================================================
+-- Every check-in can carry a note, but the day's habit_completions row only
+-- keeps the first one. Search reads habit_checkins directly, so it gets the
+-- same generated tsvector column and GIN index as the notes in 018.
+-- ============================================================================
+
+ALTER TABLE habit_checkins
+    ADD COLUMN IF NOT EXISTS note_tsv tsvector
+    GENERATED ALWAYS AS (to_tsvector('english', COALESCE(note, ''))) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_checkins_note_tsv
+    ON habit_checkins USING GIN (note_tsv);
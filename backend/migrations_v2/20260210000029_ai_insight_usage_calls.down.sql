@@ -0,0 +1,3 @@
+-- Rollback 029: drop model call count from AI insight usage
+ALTER TABLE ai_insight_usage DROP CONSTRAINT IF EXISTS chk_usage_model_calls;
+ALTER TABLE ai_insight_usage DROP COLUMN IF EXISTS model_calls;
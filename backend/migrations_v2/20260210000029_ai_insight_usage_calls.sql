@@ -0,0 +1,12 @@
+-- ============================================================================
+-- 029: Model calls per AI insight usage row
+-- ============================================================================
+-- A reply that can't be parsed gets one repair request, and both calls are
+-- billed. Only a call that never completed is refunded now; a reply that
+-- failed parsing or validation keeps its usage row, and model_calls records
+-- how many calls (repair included) the reservation ended up costing.
+-- ============================================================================
+
+ALTER TABLE ai_insight_usage
+    ADD COLUMN IF NOT EXISTS model_calls INTEGER NOT NULL DEFAULT 1,
+    ADD CONSTRAINT chk_usage_model_calls CHECK (model_calls >= 1);
@@ -0,0 +1,9 @@
+-- Rollback 030: fold provider sources back into 'claude' and drop them from
+-- insight_source (Postgres can't drop enum values, so the type is rebuilt)
+ALTER TABLE insights ALTER COLUMN source DROP DEFAULT;
+ALTER TABLE insights ALTER COLUMN source TYPE TEXT;
+UPDATE insights SET source = 'claude' WHERE source IN ('anthropic', 'openai', 'fake');
+DROP TYPE insight_source;
+CREATE TYPE insight_source AS ENUM ('claude', 'fallback');
+ALTER TABLE insights ALTER COLUMN source TYPE insight_source USING source::insight_source;
+ALTER TABLE insights ALTER COLUMN source SET DEFAULT 'claude';
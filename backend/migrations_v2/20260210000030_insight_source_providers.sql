@@ -0,0 +1,12 @@
+-- ============================================================================
+-- 030: Insight source per model provider
+-- ============================================================================
+-- Insights can come from any configured provider (LLM_PROVIDER), not only
+-- Anthropic's, so the source records the provider that wrote them:
+-- 'anthropic', 'openai' (any OpenAI-compatible endpoint) or 'fake'.
+-- 'claude' stays for model insights stored before this migration.
+-- ============================================================================
+
+ALTER TYPE insight_source ADD VALUE IF NOT EXISTS 'anthropic';
+ALTER TYPE insight_source ADD VALUE IF NOT EXISTS 'openai';
+ALTER TYPE insight_source ADD VALUE IF NOT EXISTS 'fake';
@@ -16,6 +16,14 @@ pub struct Config {
 
     pub claude_api_key: String,
     pub claude_model: String,
+    pub claude_base_url: String,
+
+    // Insight model provider: "anthropic", "openai" (any OpenAI-compatible
+    // endpoint, e.g. a local server) or "fake" (canned output, no network)
+    pub llm_provider: String,
+    pub llm_base_url: String,
+    pub llm_model: String,
+    pub llm_api_key: String,
 
     pub sentry_dsn: Option<String>,
 
@@ -55,6 +63,14 @@ impl Config {
             claude_api_key: env::var("CLAUDE_API_KEY").unwrap_or_else(|_| String::new()),
             claude_model: env::var("CLAUDE_MODEL")
                 .unwrap_or_else(|_| "claude-sonnet-4-20250514".into()),
+            claude_base_url: env::var("CLAUDE_BASE_URL")
+                .unwrap_or_else(|_| "https://api.anthropic.com".into()),
+
+            llm_provider: env::var("LLM_PROVIDER").unwrap_or_else(|_| "anthropic".into()),
+            llm_base_url: env::var("LLM_BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:11434".into()),
+            llm_model: env::var("LLM_MODEL").unwrap_or_else(|_| "llama3.1".into()),
+            llm_api_key: env::var("LLM_API_KEY").unwrap_or_else(|_| String::new()),
 
             sentry_dsn: env::var("SENTRY_DSN").ok().filter(|s| !s.is_empty()),
 
@@ -76,4 +92,31 @@ impl Config {
     pub fn listen_addr(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
+
+    /// Defaults for tests that build an `AppState`: no secrets, fake provider.
+    #[cfg(test)]
+    pub fn for_tests(database_url: &str) -> Self {
+        Self {
+            database_url: database_url.into(),
+            host: "127.0.0.1".into(),
+            port: 0,
+            frontend_url: "http://localhost:3000".into(),
+            jwt_secret: "test-secret".into(),
+            jwt_access_ttl_secs: 900,
+            jwt_refresh_ttl_secs: 604800,
+            stripe_secret_key: String::new(),
+            stripe_webhook_secret: String::new(),
+            claude_api_key: String::new(),
+            claude_model: String::new(),
+            claude_base_url: String::new(),
+            llm_provider: "fake".into(),
+            llm_base_url: String::new(),
+            llm_model: String::new(),
+            llm_api_key: String::new(),
+            sentry_dsn: None,
+            try_me_enabled: false,
+            demo_ttl_secs: 7200,
+            demo_max_insight_calls: 2,
+        }
+    }
 }
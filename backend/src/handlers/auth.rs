@@ -9,7 +9,10 @@ use crate::auth::{
     password::{hash_password, verify_password},
 };
 use crate::error::{AppError, AppResult};
-use crate::models::user::{SubscriptionStatus, SubscriptionTier, UserProfile};
+use crate::models::user::{
+    SubscriptionStatus, SubscriptionTier, UpdatePreferencesRequest, UserProfile, WeekStart,
+};
+use crate::services::{reviews, rollups};
 use crate::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -327,6 +330,49 @@ pub async fn me(
     Ok(Json(user.into()))
 }
 
+/// PUT /api/me/preferences — partial update of display preferences
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(body): Json<UpdatePreferencesRequest>,
+) -> AppResult<Json<UserProfile>> {
+    let mut tx = state.db.begin().await?;
+    let previous_week_start = sqlx::query_scalar::<_, WeekStart>(
+        "SELECT week_start FROM users WHERE id = $1 FOR UPDATE",
+    )
+    .bind(auth_user.id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("User not found".into()))?;
+
+    let user = sqlx::query_as::<_, crate::models::user::User>(
+        r#"
+        UPDATE users SET
+            week_start = COALESCE($2, week_start),
+            journal_in_insights = COALESCE($3, journal_in_insights),
+            insight_notifications = COALESCE($4, insight_notifications),
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(auth_user.id)
+    .bind(body.week_start)
+    .bind(body.journal_in_insights)
+    .bind(body.insight_notifications)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // Cached reviews and rollups were cut into weeks by the old week start
+    if user.week_start != previous_week_start {
+        reviews::invalidate_cached_from(&mut tx, auth_user.id, None).await?;
+        rollups::invalidate_from(&mut tx, auth_user.id, None).await?;
+    }
+    tx.commit().await?;
+
+    Ok(Json(user.into()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,227 @@
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::{AppError, AppResult};
+use crate::handlers::habits::fetch_owned_habit;
+use crate::models::habit::Habit;
+use crate::models::user::WeekStart;
+use crate::services::calendar::{self, DayState};
+use crate::services::history::{self, HistoryHeaders, HistoryWindow};
+use crate::services::mood;
+use crate::services::reviews;
+use crate::services::schedule::week_start_for;
+use crate::services::stats::{DoneDays, HabitDays};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarQuery {
+    /// "YYYY-MM". Default: the current month
+    pub month: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitCalendar {
+    pub habit_id: Uuid,
+    pub month: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: Vec<HabitCalendarDay>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitCalendarDay {
+    pub date: NaiveDate,
+    pub state: DayState,
+    /// Summed completion value (0 when not done)
+    pub count: i32,
+    pub target: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AggregateCalendar {
+    pub month: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Habits active at some point in the month, in display order
+    pub habits: Vec<CalendarHabit>,
+    pub days: Vec<AggregateCalendarDay>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarHabit {
+    pub id: Uuid,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AggregateCalendarDay {
+    pub date: NaiveDate,
+    pub done: i64,
+    pub missed: i64,
+    pub due: i64,
+    /// One state per entry in `habits`
+    pub states: Vec<DayState>,
+}
+
+/// GET /api/habits/:id/calendar?month=YYYY-MM — every date of the month with its state
+pub async fn get_habit_calendar(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+    Query(query): Query<CalendarQuery>,
+) -> AppResult<(HistoryHeaders, Json<HabitCalendar>)> {
+    let habit = fetch_owned_habit(&state, habit_id, auth_user.id).await?;
+    let (month, window, month_end, today) =
+        month_window(&state, auth_user.id, query.month.as_deref()).await?;
+    let (first, done) = load_month(&state, auth_user.id, &window).await?;
+
+    let empty = DoneDays::new();
+    let habit_done = done.get(&habit.id).unwrap_or(&empty);
+    let days = if window.start > month_end {
+        Vec::new()
+    } else {
+        let span = HabitDays::new(&habit, habit_done);
+        calendar::month_states(&span, window.start, month_end, today, first)
+            .into_iter()
+            .map(|(date, state)| HabitCalendarDay {
+                date,
+                state,
+                count: habit_done.get(&date).copied().unwrap_or(0),
+                target: habit.target_per_day,
+            })
+            .collect()
+    };
+
+    Ok((
+        window.headers(),
+        Json(HabitCalendar {
+            habit_id,
+            month,
+            start: window.start,
+            end: month_end,
+            days,
+        }),
+    ))
+}
+
+/// GET /api/calendar?month=YYYY-MM — all habits' states per date, with totals
+pub async fn get_calendar(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<CalendarQuery>,
+) -> AppResult<(HistoryHeaders, Json<AggregateCalendar>)> {
+    let (month, window, month_end, today) =
+        month_window(&state, auth_user.id, query.month.as_deref()).await?;
+    let (first, done) = load_month(&state, auth_user.id, &window).await?;
+
+    // Archived habits still show for the part of the month they were active
+    let habits: Vec<Habit> = sqlx::query_as::<_, Habit>(
+        "SELECT * FROM habits WHERE user_id = $1 ORDER BY sort_order, created_at",
+    )
+    .bind(auth_user.id)
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .filter(|h| {
+        h.created_at.date_naive() <= month_end
+            && h.archived_at
+                .map_or(true, |a| a.date_naive() > window.start)
+    })
+    .collect();
+
+    let empty = DoneDays::new();
+    let spans: Vec<HabitDays> = habits
+        .iter()
+        .map(|h| HabitDays::new(h, done.get(&h.id).unwrap_or(&empty)))
+        .collect();
+
+    let days = window
+        .start
+        .iter_days()
+        .take_while(|d| *d <= month_end)
+        .map(|date| {
+            let states: Vec<DayState> = spans
+                .iter()
+                .map(|h| calendar::day_state(h, date, today, first))
+                .collect();
+            let count = |s: DayState| states.iter().filter(|x| **x == s).count() as i64;
+            AggregateCalendarDay {
+                date,
+                done: count(DayState::Done),
+                missed: count(DayState::Missed),
+                due: count(DayState::Due),
+                states,
+            }
+        })
+        .collect();
+
+    Ok((
+        window.headers(),
+        Json(AggregateCalendar {
+            month,
+            start: window.start,
+            end: month_end,
+            habits: habits
+                .iter()
+                .map(|h| CalendarHabit {
+                    id: h.id,
+                    name: h.name.clone(),
+                    color: h.color.clone(),
+                })
+                .collect(),
+            days,
+        }),
+    ))
+}
+
+/// The requested month's history window (clamped to the tier's heatmap
+/// history and to today), the month's last day, and today. Days after
+/// today are still listed, as due or not scheduled.
+async fn month_window(
+    state: &AppState,
+    user_id: Uuid,
+    month: Option<&str>,
+) -> AppResult<(String, HistoryWindow, NaiveDate, NaiveDate)> {
+    let today = mood::local_today(&mut *state.db.acquire().await?, user_id).await?;
+    let (start, end) = match month {
+        Some(label) => reviews::parse_month(label)
+            .ok_or_else(|| AppError::Validation("month must look like 2026-02".into()))?,
+        None => {
+            let start = today.with_day(1).unwrap_or(today);
+            (start, reviews::month_end(start))
+        }
+    };
+
+    let limits = history::entitlements(&mut *state.db.acquire().await?, user_id).await?;
+    let limit_days = limits.heatmap_months as i64 * history::DAYS_PER_MONTH;
+    let window = HistoryWindow::clamp(start, end, today, limit_days);
+    Ok((start.format("%Y-%m").to_string(), window, end, today))
+}
+
+/// The user's week start and completions for the window. Weekly targets
+/// need the earlier days of the first week too.
+async fn load_month(
+    state: &AppState,
+    user_id: Uuid,
+    window: &HistoryWindow,
+) -> AppResult<(WeekStart, std::collections::HashMap<Uuid, DoneDays>)> {
+    let first = sqlx::query_scalar::<_, WeekStart>("SELECT week_start FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(&state.db)
+        .await?;
+    let done = reviews::load_done(
+        &mut *state.db.acquire().await?,
+        user_id,
+        week_start_for(window.start, first),
+        window.end,
+    )
+    .await?;
+    Ok((first, done))
+}
@@ -0,0 +1,229 @@
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::{AppError, AppResult};
+use crate::handlers::completions::update_streak;
+use crate::handlers::habits::fetch_owned_habit;
+use crate::models::checkin::{
+    Checkin, CheckinQuery, CheckinResult, CreateCheckinRequest, HourBucket, TimeOfDayQuery,
+};
+use crate::services::checkins;
+use crate::services::mood;
+use crate::AppState;
+
+/// POST /api/habits/:id/checkins — append one timestamped check-in
+pub async fn create_checkin(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+    Json(body): Json<CreateCheckinRequest>,
+) -> AppResult<Json<CheckinResult>> {
+    let habit = fetch_owned_habit(&state, habit_id, auth_user.id).await?;
+
+    let now = Utc::now();
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let completed_date = body.completed_date.unwrap_or(today);
+
+    // G-23: Validate ±1 day from the user's local today
+    if (completed_date - today).num_days().abs() > 1 {
+        return Err(AppError::Validation(
+            "completed_date must be within ±1 day of today".into(),
+        ));
+    }
+
+    let checked_in_at = body.checked_in_at.unwrap_or(now);
+    if checked_in_at > now {
+        return Err(AppError::Validation(
+            "checked_in_at cannot be in the future".into(),
+        ));
+    }
+
+    let value = body.value.unwrap_or(1);
+    if !(1..=100).contains(&value) {
+        return Err(AppError::Validation(
+            "value must be between 1 and 100".into(),
+        ));
+    }
+
+    if body.note.as_ref().is_some_and(|n| n.chars().count() > 5000) {
+        return Err(AppError::Validation(
+            "Note must be under 5000 characters".into(),
+        ));
+    }
+
+    let mut tx = state.db.begin().await?;
+    checkins::lock_habit(&mut tx, habit_id).await?;
+    let is_late = checkins::is_late(&mut tx, &habit, completed_date, checked_in_at).await?;
+    let checkin = checkins::record_checkin(
+        &mut tx,
+        checkins::NewCheckin {
+            habit_id,
+            user_id: auth_user.id,
+            date: completed_date,
+            checked_in_at,
+            value,
+            note: body.note.as_deref(),
+            is_late,
+        },
+    )
+    .await?;
+    let (completion, checkins_today) =
+        checkins::rollup_day(&mut tx, habit_id, auth_user.id, completed_date).await?;
+    tx.commit().await?;
+
+    update_streak(&state, habit_id).await?;
+    broadcast_change(&state, auth_user.id, habit_id, Some(checkin.id));
+
+    let is_complete = completion
+        .as_ref()
+        .is_some_and(|c| c.value >= habit.target_per_day);
+
+    Ok(Json(CheckinResult {
+        checkin: Some(checkin),
+        completion,
+        checkins_today,
+        target_per_day: habit.target_per_day,
+        is_complete,
+    }))
+}
+
+/// GET /api/habits/:id/checkins?date= — the day's check-ins, newest first
+pub async fn list_checkins(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+    Query(query): Query<CheckinQuery>,
+) -> AppResult<Json<Vec<Checkin>>> {
+    fetch_owned_habit(&state, habit_id, auth_user.id).await?;
+
+    let date = match query.date {
+        Some(date) => date,
+        None => mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?,
+    };
+
+    let rows = sqlx::query_as::<_, Checkin>(
+        r#"
+        SELECT * FROM habit_checkins
+        WHERE habit_id = $1 AND user_id = $2 AND local_date_bucket = $3
+        ORDER BY checked_in_at DESC, created_at DESC
+        "#,
+    )
+    .bind(habit_id)
+    .bind(auth_user.id)
+    .bind(date)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(rows))
+}
+
+/// DELETE /api/habits/:id/checkins/latest?date= — undo the most recent check-in
+pub async fn undo_checkin(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+    Query(query): Query<CheckinQuery>,
+) -> AppResult<Json<CheckinResult>> {
+    let habit = fetch_owned_habit(&state, habit_id, auth_user.id).await?;
+
+    let date = match query.date {
+        Some(date) => date,
+        None => mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?,
+    };
+
+    let mut tx = state.db.begin().await?;
+    checkins::lock_habit(&mut tx, habit_id).await?;
+    let removed = checkins::undo_last_checkin(&mut tx, habit_id, date).await?;
+    // Idempotent: undoing a day without check-ins leaves the rollup untouched
+    let (completion, checkins_today) = if removed.is_some() {
+        checkins::rollup_day(&mut tx, habit_id, auth_user.id, date).await?
+    } else {
+        let completion = sqlx::query_as::<_, crate::models::completion::Completion>(
+            "SELECT * FROM habit_completions WHERE habit_id = $1 AND local_date_bucket = $2",
+        )
+        .bind(habit_id)
+        .bind(date)
+        .fetch_optional(&mut *tx)
+        .await?;
+        (completion, 0)
+    };
+    tx.commit().await?;
+
+    if let Some(removed) = &removed {
+        update_streak(&state, habit_id).await?;
+        broadcast_change(&state, auth_user.id, habit_id, Some(removed.id));
+    }
+
+    let is_complete = completion
+        .as_ref()
+        .is_some_and(|c| c.value >= habit.target_per_day);
+
+    Ok(Json(CheckinResult {
+        checkin: removed,
+        completion,
+        checkins_today,
+        target_per_day: habit.target_per_day,
+        is_complete,
+    }))
+}
+
+/// GET /api/habits/:id/time-of-day — check-ins per local hour (0-23)
+pub async fn get_time_of_day(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+    Query(query): Query<TimeOfDayQuery>,
+) -> AppResult<Json<Vec<HourBucket>>> {
+    fetch_owned_habit(&state, habit_id, auth_user.id).await?;
+
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+    let start = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?
+        - chrono::Duration::days(days);
+
+    // Hours are bucketed in the user's IANA timezone; every hour is returned
+    let rows = sqlx::query_as::<_, HourBucket>(
+        r#"
+        WITH hours AS (SELECT generate_series(0, 23) AS hour),
+        local AS (
+            SELECT
+                EXTRACT(HOUR FROM to_user_local(c.checked_in_at, u.timezone))::int AS hour,
+                c.value
+            FROM habit_checkins c
+            JOIN users u ON u.id = c.user_id
+            WHERE c.habit_id = $1 AND c.user_id = $2 AND c.local_date_bucket >= $3
+        )
+        SELECT
+            h.hour,
+            COUNT(l.hour) AS checkins,
+            COALESCE(SUM(l.value), 0)::bigint AS value
+        FROM hours h
+        LEFT JOIN local l ON l.hour = h.hour
+        GROUP BY h.hour
+        ORDER BY h.hour ASC
+        "#,
+    )
+    .bind(habit_id)
+    .bind(auth_user.id)
+    .bind(start)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(rows))
+}
+
+fn broadcast_change(state: &AppState, user_id: Uuid, habit_id: Uuid, checkin_id: Option<Uuid>) {
+    if let Some(tx) = state.ws_tx.as_ref() {
+        let msg = serde_json::json!({
+            "type": "completion_changed",
+            "user_id": user_id,
+            "habit_id": habit_id,
+            "checkin_id": checkin_id,
+        });
+        let _ = tx.send(msg.to_string());
+    }
+}
@@ -11,6 +11,17 @@ use crate::error::{AppError, AppResult};
 use crate::models::completion::{
     Completion, CompletionQuery, CreateCompletionRequest, DailyStats, StreakInfo,
 };
+use crate::dto::{HabitStatsResponse, WeeklyReviewQuery};
+use crate::handlers::habits::fetch_owned_habit;
+use crate::models::user::WeekStart;
+use crate::services::checkins;
+use crate::services::history::{self, HistoryHeaders, HistoryWindow};
+use crate::services::mood;
+use crate::services::recovery;
+use crate::services::reviews;
+use crate::services::rollups;
+use crate::services::schedule::{self, effective_start, Schedule};
+use crate::services::stats;
 use crate::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -31,16 +42,78 @@ pub struct HeatmapEntry {
     pub target: i32,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ScoreTrendQuery {
+    /// Days to chart, ending today. Default: 30
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ScoreTrend {
+    pub today: Option<f64>,
+    /// Weighted score over the last 7 / 30 days
+    pub score_7d: Option<f64>,
+    pub score_30d: Option<f64>,
+    pub points: Vec<ScorePoint>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ScorePoint {
+    pub date: chrono::NaiveDate,
+    pub score: Option<f64>,
+    /// Trailing 7- and 30-day scores ending on this date
+    pub rolling_7d: Option<f64>,
+    pub rolling_30d: Option<f64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct AggregateHeatmapEntry {
+    pub date: chrono::NaiveDate,
+    /// Habits that were active and due that day
+    pub due: i64,
+    pub completed: i64,
+    /// None when nothing was due
+    pub rate: Option<f64>,
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct WeeklyReview {
+    /// ISO label ("2026-W06"); Sunday-start weeks begin the day before
+    pub week: String,
     pub week_start: chrono::NaiveDate,
     pub week_end: chrono::NaiveDate,
+    pub week_start_day: WeekStart,
+    pub previous_week: String,
+    /// None while the next week hasn't started yet
+    pub next_week: Option<String>,
     pub total_completions: i64,
     pub total_possible: i64,
     pub completion_rate: f64,
+    /// Weighted score over the week, 0–100 (None when nothing was due)
+    pub score: Option<f64>,
     pub best_day: Option<String>,
     pub worst_day: Option<String>,
+    /// Share of windowed-habit completions done on time (None without windows)
+    pub on_time_rate: Option<f64>,
     pub habits: Vec<WeeklyHabitReview>,
+    /// All habits' recovery over the RECOVERY_DAYS ending with this week
+    pub recovery: recovery::Recovery,
+    /// "You recovered after 4 of 5 misses"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_summary: Option<String>,
+    pub comparison: WeekComparison,
+}
+
+/// The week before the reviewed one, for "vs last week" deltas
+#[derive(Debug, serde::Serialize)]
+pub struct WeekComparison {
+    pub week_start: chrono::NaiveDate,
+    pub total_completions: i64,
+    pub total_possible: i64,
+    pub completion_rate: f64,
+    /// Reviewed week's rate minus this week's rate
+    pub rate_change: f64,
+    pub score: Option<f64>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -50,6 +123,10 @@ pub struct WeeklyHabitReview {
     pub completed: i64,
     pub possible: i64,
     pub rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_time_rate: Option<f64>,
+    /// Recovery from misses over the RECOVERY_DAYS ending with this week
+    pub recovery: recovery::Recovery,
 }
 
 pub async fn create_completion(
@@ -58,7 +135,7 @@ pub async fn create_completion(
     Json(body): Json<CreateCompletionRequest>,
 ) -> AppResult<Json<Completion>> {
     // Verify habit ownership
-    let _habit = sqlx::query_as::<_, crate::models::habit::Habit>(
+    let habit = sqlx::query_as::<_, crate::models::habit::Habit>(
         "SELECT * FROM habits WHERE id = $1 AND user_id = $2",
     )
     .bind(body.habit_id)
@@ -67,10 +144,9 @@ pub async fn create_completion(
     .await?
     .ok_or(AppError::NotFound("Habit not found".into()))?;
 
-    let completed_date = body.completed_date.unwrap_or_else(|| Utc::now().date_naive());
-
-    // G-23: Validate ±1 day from server-now
-    let today = Utc::now().date_naive();
+    // G-23: Validate ±1 day from the user's local today
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let completed_date = body.completed_date.unwrap_or(today);
     let diff = (completed_date - today).num_days().abs();
     if diff > 1 {
         return Err(AppError::Validation(
@@ -79,26 +155,48 @@ pub async fn create_completion(
     }
 
     let value = body.value.unwrap_or(1);
+    if value < 1 {
+        return Err(AppError::Validation("value must be at least 1".into()));
+    }
 
-    // G-12: Idempotent — ON CONFLICT returns existing row
-    let completion = sqlx::query_as::<_, Completion>(
-        r#"
-        INSERT INTO habit_completions (id, habit_id, user_id, local_date_bucket, value, note)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        ON CONFLICT (habit_id, local_date_bucket) DO UPDATE
-            SET value = habit_completions.value  -- no-op update to trigger RETURNING
-        RETURNING *
-        "#,
+    // G-12: Idempotent — an existing rollup for the day is returned as-is.
+    // Checked under the habit lock so two concurrent creates can't both miss it.
+    let mut tx = state.db.begin().await?;
+    checkins::lock_habit(&mut tx, body.habit_id).await?;
+    let existing = sqlx::query_as::<_, Completion>(
+        "SELECT * FROM habit_completions WHERE habit_id = $1 AND local_date_bucket = $2",
     )
-    .bind(Uuid::new_v4())
     .bind(body.habit_id)
-    .bind(auth_user.id)
     .bind(completed_date)
-    .bind(value)
-    .bind(&body.note)
-    .fetch_one(&state.db)
+    .fetch_optional(&mut *tx)
     .await?;
 
+    let completion = match existing {
+        Some(existing) => existing,
+        None => {
+            let now = Utc::now();
+            let is_late = checkins::is_late(&mut tx, &habit, completed_date, now).await?;
+            checkins::record_checkin(
+                &mut tx,
+                checkins::NewCheckin {
+                    habit_id: body.habit_id,
+                    user_id: auth_user.id,
+                    date: completed_date,
+                    checked_in_at: now,
+                    value,
+                    note: body.note.as_deref(),
+                    is_late,
+                },
+            )
+            .await?;
+            checkins::rollup_day(&mut tx, body.habit_id, auth_user.id, completed_date)
+                .await?
+                .0
+                .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Rollup missing after check-in")))?
+        }
+    };
+    tx.commit().await?;
+
     // Update streak
     update_streak(&state, body.habit_id).await?;
 
@@ -120,11 +218,18 @@ pub async fn list_completions(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Query(query): Query<CompletionQuery>,
-) -> AppResult<Json<Vec<Completion>>> {
-    let start = query
-        .start_date
-        .unwrap_or_else(|| Utc::now().date_naive() - chrono::Duration::days(30));
-    let end = query.end_date.unwrap_or_else(|| Utc::now().date_naive());
+) -> AppResult<(HistoryHeaders, Json<Vec<Completion>>)> {
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    let window = history::analytics_window(
+        &mut conn,
+        auth_user.id,
+        query.start_date.unwrap_or(today - chrono::Duration::days(30)),
+        query.end_date.unwrap_or(today),
+        today,
+    )
+    .await?;
+    let (start, end) = (window.start, window.end);
 
     let completions = if let Some(habit_id) = query.habit_id {
         sqlx::query_as::<_, Completion>(
@@ -155,7 +260,7 @@ pub async fn list_completions(
         .await?
     };
 
-    Ok(Json(completions))
+    Ok((window.headers(), Json(completions)))
 }
 
 pub async fn delete_completion(
@@ -173,10 +278,13 @@ pub async fn delete_completion(
     .await?;
 
     if let Some(completion) = completion {
-        sqlx::query("DELETE FROM habit_completions WHERE id = $1")
-            .bind(completion_id)
-            .execute(&state.db)
+        let mut tx = state.db.begin().await?;
+        checkins::lock_habit(&mut tx, completion.habit_id).await?;
+        // With no check-ins left the rollup deletes the completion row
+        checkins::clear_day(&mut tx, completion.habit_id, completion.completed_date).await?;
+        checkins::rollup_day(&mut tx, completion.habit_id, auth_user.id, completion.completed_date)
             .await?;
+        tx.commit().await?;
 
         update_streak(&state, completion.habit_id).await?;
 
@@ -200,7 +308,7 @@ pub async fn toggle_completion(
     Json(body): Json<ToggleRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
     // Verify ownership
-    let _habit = sqlx::query_as::<_, crate::models::habit::Habit>(
+    let habit = sqlx::query_as::<_, crate::models::habit::Habit>(
         "SELECT * FROM habits WHERE id = $1 AND user_id = $2",
     )
     .bind(body.habit_id)
@@ -209,42 +317,57 @@ pub async fn toggle_completion(
     .await?
     .ok_or(AppError::NotFound("Habit not found".into()))?;
 
-    let completed_date = body.completed_date.unwrap_or_else(|| Utc::now().date_naive());
+    let completed_date = match body.completed_date {
+        Some(date) => date,
+        None => mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?,
+    };
 
-    // Check if completion exists
+    // Check if completion exists, under the habit lock so two toggles
+    // can't both see the same state
+    let mut tx = state.db.begin().await?;
+    checkins::lock_habit(&mut tx, body.habit_id).await?;
     let existing = sqlx::query_as::<_, Completion>(
         "SELECT * FROM habit_completions WHERE habit_id = $1 AND user_id = $2 AND local_date_bucket = $3",
     )
     .bind(body.habit_id)
     .bind(auth_user.id)
     .bind(completed_date)
-    .fetch_optional(&state.db)
+    .fetch_optional(&mut *tx)
     .await?;
 
     let result = if let Some(existing) = existing {
-        // Delete
-        sqlx::query("DELETE FROM habit_completions WHERE id = $1")
-            .bind(existing.id)
-            .execute(&state.db)
-            .await?;
+        // Delete the day's check-ins and its rollup
+        checkins::clear_day(&mut tx, body.habit_id, completed_date).await?;
+        checkins::rollup_day(&mut tx, body.habit_id, auth_user.id, completed_date).await?;
         serde_json::json!({ "action": "deleted", "completion_id": existing.id })
     } else {
-        // Create
-        let completion = sqlx::query_as::<_, Completion>(
-            r#"
-            INSERT INTO habit_completions (id, habit_id, user_id, local_date_bucket, value)
-            VALUES ($1, $2, $3, $4, 1)
-            RETURNING *
-            "#,
+        // Create a single check-in and roll it up; outside the window it's late
+        let now = Utc::now();
+        let is_late = checkins::is_late(&mut tx, &habit, completed_date, now).await?;
+        checkins::record_checkin(
+            &mut tx,
+            checkins::NewCheckin {
+                habit_id: body.habit_id,
+                user_id: auth_user.id,
+                date: completed_date,
+                checked_in_at: now,
+                value: 1,
+                note: None,
+                is_late,
+            },
         )
-        .bind(Uuid::new_v4())
-        .bind(body.habit_id)
-        .bind(auth_user.id)
-        .bind(completed_date)
-        .fetch_one(&state.db)
         .await?;
-        serde_json::json!({ "action": "created", "completion_id": completion.id })
+        let (completion, _) =
+            checkins::rollup_day(&mut tx, body.habit_id, auth_user.id, completed_date).await?;
+        let completion = completion
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Rollup missing after check-in")))?;
+        serde_json::json!({
+            "action": "created",
+            "completion_id": completion.id,
+            "is_late": completion.is_late,
+        })
     };
+    tx.commit().await?;
 
     update_streak(&state, body.habit_id).await?;
 
@@ -286,7 +409,7 @@ pub async fn get_heatmap(
     Extension(auth_user): Extension<AuthUser>,
     Path(habit_id): Path<Uuid>,
     Query(query): Query<HeatmapQuery>,
-) -> AppResult<Json<Vec<HeatmapEntry>>> {
+) -> AppResult<(HistoryHeaders, Json<Vec<HeatmapEntry>>)> {
     let habit = sqlx::query_as::<_, crate::models::habit::Habit>(
         "SELECT * FROM habits WHERE id = $1 AND user_id = $2",
     )
@@ -296,9 +419,9 @@ pub async fn get_heatmap(
     .await?
     .ok_or(AppError::NotFound("Habit not found".into()))?;
 
-    let months = query.months.unwrap_or(3).min(12);
-    let start_date = Utc::now().date_naive() - chrono::Duration::days(months as i64 * 30);
-    let end_date = Utc::now().date_naive();
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    let window = history::heatmap_window(&mut conn, auth_user.id, query.months.unwrap_or(3), today).await?;
 
     let rows = sqlx::query_as::<_, (chrono::NaiveDate, i64)>(
         r#"
@@ -311,9 +434,9 @@ pub async fn get_heatmap(
     )
     .bind(habit_id)
     .bind(auth_user.id)
-    .bind(start_date)
-    .bind(end_date)
-    .fetch_all(&state.db)
+    .bind(window.start)
+    .bind(window.end)
+    .fetch_all(&mut *conn)
     .await?;
 
     let entries: Vec<HeatmapEntry> = rows
@@ -325,27 +448,110 @@ pub async fn get_heatmap(
         })
         .collect();
 
-    Ok(Json(entries))
+    Ok((window.headers(), Json(entries)))
 }
 
+/// GET /api/stats/score?days= — weighted daily score with rolling 7/30-day trends
+pub async fn get_score_trend(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<ScoreTrendQuery>,
+) -> AppResult<(HistoryHeaders, Json<ScoreTrend>)> {
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+    let mut conn = state.db.acquire().await?;
+    let window = history::analytics_window(
+        &mut conn,
+        auth_user.id,
+        today - chrono::Duration::days(days - 1),
+        today,
+        today,
+    )
+    .await?;
+
+    // Rolling windows look back before the first point, as far as the tier allows
+    let earliest = today - chrono::Duration::days(window.limit_days - 1);
+    let lookback = (window.start - chrono::Duration::days(29)).max(earliest);
+    let rows = rollups::read_range(&mut conn, auth_user.id, lookback, today).await?;
+
+    let weights: Vec<(i64, i64)> = rows
+        .iter()
+        .map(|r| (r.completed_weight as i64, r.due_weight as i64))
+        .collect();
+    let rolling_7d = stats::rolling_scores(&weights, 7);
+    let rolling_30d = stats::rolling_scores(&weights, 30);
+
+    let points: Vec<ScorePoint> = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.date >= window.start)
+        .map(|(i, r)| ScorePoint {
+            date: r.date,
+            score: r.score(),
+            rolling_7d: rolling_7d[i],
+            rolling_30d: rolling_30d[i],
+        })
+        .collect();
+
+    Ok((
+        window.headers(),
+        Json(ScoreTrend {
+            today: rows.last().and_then(|r| r.score()),
+            score_7d: rolling_7d.last().copied().flatten(),
+            score_30d: rolling_30d.last().copied().flatten(),
+            points,
+        }),
+    ))
+}
+
+/// Days of history the weekly review judges recovery over
+const RECOVERY_DAYS: i64 = 28;
+
 /// G-10: Weekly review summary
+/// GET /api/stats/weekly-review?week=YYYY-WNN — defaults to the last complete week
 pub async fn get_weekly_review(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-) -> AppResult<Json<WeeklyReview>> {
-    let today = Utc::now().date_naive();
-    // ISO week: Monday to Sunday
-    let days_since_monday = today.weekday().num_days_from_monday();
-    let week_start = today - chrono::Duration::days(days_since_monday as i64 + 7); // last Monday
-    let week_end = week_start + chrono::Duration::days(6); // last Sunday
+    Query(query): Query<WeeklyReviewQuery>,
+) -> AppResult<(HistoryHeaders, Json<WeeklyReview>)> {
+    let week_start_day = sqlx::query_scalar::<_, WeekStart>(
+        "SELECT week_start FROM users WHERE id = $1",
+    )
+    .bind(auth_user.id)
+    .fetch_one(&state.db)
+    .await?;
+
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let week_start = match query.week.as_deref() {
+        Some(label) => reviews::parse_week(label, week_start_day).ok_or_else(|| {
+            AppError::Validation("week must be an ISO week like 2026-W06".into())
+        })?,
+        None => reviews::last_complete_week(today, week_start_day),
+    };
+    if week_start > today {
+        return Err(AppError::Validation("week is in the future".into()));
+    }
+    let week_end = week_start + chrono::Duration::days(6);
+    let prev_start = week_start - chrono::Duration::days(7);
+    let prev_end = week_start - chrono::Duration::days(1);
+    let next_start = week_end + chrono::Duration::days(1);
+
+    // The week, its comparison week and the recovery lookback are each
+    // judged only over days the tier's history allows, and not past today
+    let mut conn = state.db.acquire().await?;
+    let window = history::analytics_window(&mut conn, auth_user.id, week_start, week_end, today).await?;
+    let clamp = |start, end| HistoryWindow::clamp(start, end, today, window.limit_days);
+    let prev = clamp(prev_start, prev_end);
+    let recovery_window = clamp(week_end - chrono::Duration::days(RECOVERY_DAYS - 1), week_end);
 
     let habits = sqlx::query_as::<_, crate::models::habit::Habit>(
-        "SELECT * FROM habits WHERE user_id = $1 AND is_archived = false",
+        "SELECT * FROM habits WHERE user_id = $1 AND is_archived = false ORDER BY sort_order, created_at",
     )
     .bind(auth_user.id)
-    .fetch_all(&state.db)
+    .fetch_all(&mut *conn)
     .await?;
 
+    // One pass covers the comparison week and the recovery lookback
     let completions = sqlx::query_as::<_, Completion>(
         r#"
         SELECT * FROM habit_completions
@@ -353,90 +559,112 @@ pub async fn get_weekly_review(
         "#,
     )
     .bind(auth_user.id)
-    .bind(week_start)
-    .bind(week_end)
-    .fetch_all(&state.db)
+    .bind(recovery_window.start.min(prev.start))
+    .bind(window.end)
+    .fetch_all(&mut *conn)
     .await?;
 
-    let mut habit_reviews = Vec::new();
-    let mut total_completions: i64 = 0;
-    let mut total_possible: i64 = 0;
-
-    for habit in &habits {
-        let completed = completions
+    let done = reviews::done_by_habit(
+        completions
             .iter()
-            .filter(|c| c.habit_id == habit.id)
-            .count() as i64;
-        // For daily habits, possible = 7. For weekly_days, possible = scheduled days count.
-        let possible: i64 = match habit.frequency {
-            crate::models::habit::HabitFrequency::Daily => 7,
-            crate::models::habit::HabitFrequency::WeeklyDays => {
-                habit.frequency_config
-                    .get("days")
-                    .and_then(|d| d.as_array())
-                    .map(|a| a.len() as i64)
-                    .unwrap_or(7)
-            }
-            crate::models::habit::HabitFrequency::WeeklyTarget => {
-                habit.frequency_config
-                    .get("times_per_week")
-                    .and_then(|t| t.as_i64())
-                    .unwrap_or(1)
-            }
+            .map(|c| (c.habit_id, c.completed_date, c.value)),
+    );
+    let empty = stats::DoneDays::new();
+    let inputs: Vec<reviews::HabitInput> = habits
+        .iter()
+        .map(|h| reviews::HabitInput::new(h, done.get(&h.id).unwrap_or(&empty)))
+        .collect();
+
+    let (habit_recovery, total_recovery) = recovery::for_habits(
+        &habits,
+        &done,
+        recovery_window.start,
+        recovery_window.end,
+        week_start_day,
+    );
+
+    let current = reviews::period_totals(&inputs, window.start, window.end, week_start_day);
+
+    let mut habit_reviews = Vec::with_capacity(current.habits.len());
+    let mut windowed_completions: i64 = 0;
+    let mut windowed_on_time: i64 = 0;
+
+    for totals in &current.habits {
+        let Some(habit) = habits.iter().find(|h| h.id == totals.habit_id) else {
+            continue;
         };
-        let rate = if possible > 0 {
-            completed as f64 / possible as f64
+        // On-time tracking only applies to habits with a completion window
+        let in_week: Vec<&Completion> = completions
+            .iter()
+            .filter(|c| c.habit_id == habit.id && c.completed_date >= week_start)
+            .collect();
+        let on_time_rate = if habit.window_start.is_some() && !in_week.is_empty() {
+            let on_time = in_week.iter().filter(|c| !c.is_late).count() as i64;
+            windowed_completions += in_week.len() as i64;
+            windowed_on_time += on_time;
+            Some(on_time as f64 / in_week.len() as f64)
         } else {
-            0.0
+            None
         };
-        total_completions += completed;
-        total_possible += possible;
         habit_reviews.push(WeeklyHabitReview {
             id: habit.id,
             name: habit.name.clone(),
-            completed,
-            possible,
-            rate,
+            completed: totals.completed,
+            possible: totals.possible.round() as i64,
+            rate: totals.rate,
+            on_time_rate,
+            recovery: habit_recovery.get(&habit.id).copied().unwrap_or_default(),
         });
     }
 
-    // Best/worst day by completion count
-    let day_names = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
-    let mut day_counts = [0i64; 7];
-    for c in &completions {
-        let dow = c.completed_date.weekday().num_days_from_monday() as usize;
-        if dow < 7 {
-            day_counts[dow] += 1;
-        }
+    // Best/worst day by share of due habits done, from the daily rollups;
+    // ties go to the earlier day and days with nothing due are skipped
+    let days = rollups::read_range(&mut conn, auth_user.id, prev.start.min(window.start), window.end).await?;
+    let (prev_days, week_days): (Vec<_>, Vec<_>) = days.iter().partition(|r| r.date < week_start);
+    let week_totals = rollups::totals(week_days.iter().copied());
+    let prev_totals = rollups::totals(prev_days.iter().copied());
+    let mut day_rates = [None; 7];
+    for r in &week_days {
+        day_rates[(r.date - week_start).num_days() as usize] = r.rate();
     }
+    let (best, worst) = stats::best_and_worst(&day_rates);
+    let day_name = |offset: usize| {
+        let date = week_start + chrono::Duration::days(offset as i64);
+        stats::WEEKDAY_NAMES[date.weekday().num_days_from_monday() as usize].to_string()
+    };
 
-    let best_day = day_counts
-        .iter()
-        .enumerate()
-        .max_by_key(|(_, &count)| count)
-        .map(|(i, _)| day_names[i].to_string());
-    let worst_day = day_counts
-        .iter()
-        .enumerate()
-        .min_by_key(|(_, &count)| count)
-        .map(|(i, _)| day_names[i].to_string());
-
-    let completion_rate = if total_possible > 0 {
-        total_completions as f64 / total_possible as f64
+    let on_time_rate = if windowed_completions > 0 {
+        Some(windowed_on_time as f64 / windowed_completions as f64)
     } else {
-        0.0
+        None
     };
 
-    Ok(Json(WeeklyReview {
+    Ok((window.headers(), Json(WeeklyReview {
+        week: reviews::week_label(week_start, week_start_day),
         week_start,
         week_end,
-        total_completions,
-        total_possible,
-        completion_rate,
-        best_day,
-        worst_day,
+        week_start_day,
+        previous_week: reviews::week_label(prev_start, week_start_day),
+        next_week: (next_start <= today).then(|| reviews::week_label(next_start, week_start_day)),
+        total_completions: week_totals.completed,
+        total_possible: week_totals.due,
+        completion_rate: week_totals.rate,
+        score: rollups::total_score(week_days),
+        best_day: best.map(day_name),
+        worst_day: worst.map(day_name),
+        on_time_rate,
         habits: habit_reviews,
-    }))
+        recovery: total_recovery,
+        recovery_summary: recovery::describe(&total_recovery),
+        comparison: WeekComparison {
+            week_start: prev_start,
+            total_completions: prev_totals.completed,
+            total_possible: prev_totals.due,
+            completion_rate: prev_totals.rate,
+            rate_change: week_totals.rate - prev_totals.rate,
+            score: rollups::total_score(prev_days),
+        },
+    })))
 }
 
 pub async fn get_streak(
@@ -454,19 +682,12 @@ pub async fn get_streak(
     .await?
     .ok_or(AppError::NotFound("Habit not found".into()))?;
 
-    let thirty_days_ago = Utc::now().date_naive() - chrono::Duration::days(30);
-    let days_with_completions = sqlx::query_scalar::<_, i64>(
-        r#"
-        SELECT COUNT(DISTINCT local_date_bucket) FROM habit_completions
-        WHERE habit_id = $1 AND local_date_bucket >= $2
-        "#,
-    )
-    .bind(habit_id)
-    .bind(thirty_days_ago)
-    .fetch_one(&state.db)
-    .await?;
-
-    let completion_rate = days_with_completions as f64 / 30.0;
+    // Schedule-aware, and only counting days since the habit existed
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let schedule = Schedule::from_habit(&habit);
+    let start = effective_start(&habit, today - chrono::Duration::days(29));
+    let done = load_done_days(&state, habit_id, start, today).await?;
+    let completion_rate = stats::period_rate(&schedule, start, today, &done).rate;
 
     Ok(Json(StreakInfo {
         habit_id,
@@ -477,63 +698,191 @@ pub async fn get_streak(
     }))
 }
 
-pub async fn get_daily_stats(
+/// Periods reported by the stats endpoint, trimmed to the tier's analytics window
+const STATS_PERIODS: [i64; 4] = [7, 30, 90, 365];
+
+/// GET /api/habits/:id/stats — schedule-aware rates and patterns for one habit
+pub async fn get_habit_stats(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-    Query(query): Query<CompletionQuery>,
-) -> AppResult<Json<Vec<DailyStats>>> {
-    let start = query
-        .start_date
-        .unwrap_or_else(|| Utc::now().date_naive() - chrono::Duration::days(30));
-    let end = query.end_date.unwrap_or_else(|| Utc::now().date_naive());
+    Path(habit_id): Path<Uuid>,
+) -> AppResult<Json<HabitStatsResponse>> {
+    let habit = fetch_owned_habit(&state, habit_id, auth_user.id).await?;
+
+    let analytics_days = history::entitlements(&mut *state.db.acquire().await?, auth_user.id)
+        .await?
+        .analytics_days;
+    let first = sqlx::query_scalar::<_, WeekStart>("SELECT week_start FROM users WHERE id = $1")
+        .bind(auth_user.id)
+        .fetch_one(&state.db)
+        .await?;
 
-    let stats = sqlx::query_as::<_, DailyStats>(
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let schedule = Schedule::from_habit(&habit);
+    let window_start = effective_start(
+        &habit,
+        today - chrono::Duration::days(analytics_days as i64 - 1),
+    );
+    let done = load_done_days(&state, habit_id, window_start, today).await?;
+
+    let rates: Vec<stats::PeriodRate> = STATS_PERIODS
+        .iter()
+        .filter(|days| **days <= analytics_days as i64)
+        .map(|days| {
+            let start = effective_start(&habit, today - chrono::Duration::days(days - 1));
+            let mut rate = stats::period_rate(&schedule, start, today, &done);
+            rate.days = *days;
+            rate
+        })
+        .collect();
+
+    let completion_rate_30d = rates.iter().find(|r| r.days == 30).map(|r| r.rate);
+
+    let week_start = schedule::week_start_for(today, first);
+    let completions_this_week =
+        stats::counted_completions(&schedule, week_start, today, &done) as i32;
+
+    let (best, worst) = stats::best_and_worst(&stats::weekday_rates(
+        &schedule,
+        window_start,
+        today,
+        &done,
+    ));
+
+    // Today isn't over, so it can't settle a miss
+    let recovery = recovery::recovery(
+        &stats::HabitDays::new(&habit, &done),
+        window_start,
+        today - chrono::Duration::days(1),
+        first,
+    );
+
+    let average_value = stats::average_value(window_start, today, &done);
+    let value_vs_target = average_value
+        .filter(|_| habit.target_per_day > 0)
+        .map(|avg| avg / habit.target_per_day as f64);
+
+    Ok(Json(HabitStatsResponse {
+        habit_id,
+        current_streak: habit.current_streak,
+        longest_streak: habit.longest_streak,
+        total_completions: habit.total_completions,
+        completion_rate_30d,
+        completions_this_week,
+        target_this_week: schedule.weekly_target(),
+        rates,
+        analytics_days,
+        best_weekday: best.map(|i| stats::WEEKDAY_NAMES[i].to_string()),
+        worst_weekday: worst.map(|i| stats::WEEKDAY_NAMES[i].to_string()),
+        average_value,
+        value_vs_target,
+        longest_streak_in_window: stats::longest_streak_range(&schedule, &done),
+        recovery,
+    }))
+}
+
+/// Completed days for a habit in `[start, end]` with each day's value
+async fn load_done_days(
+    state: &AppState,
+    habit_id: Uuid,
+    start: chrono::NaiveDate,
+    end: chrono::NaiveDate,
+) -> AppResult<stats::DoneDays> {
+    let rows = sqlx::query_as::<_, (chrono::NaiveDate, i32)>(
         r#"
-        WITH dates AS (
-            SELECT generate_series($2::date, $3::date, '1 day'::interval)::date AS date
-        ),
-        active_habits AS (
-            SELECT COUNT(*) AS total FROM habits
-            WHERE user_id = $1 AND is_archived = false
-        ),
-        daily_completions AS (
-            SELECT local_date_bucket, COUNT(DISTINCT habit_id) AS completed
-            FROM habit_completions
-            WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
-            GROUP BY local_date_bucket
-        )
-        SELECT
-            d.date,
-            ah.total AS total_habits,
-            COALESCE(dc.completed, 0) AS completed_habits,
-            CASE WHEN ah.total > 0
-                THEN COALESCE(dc.completed, 0)::float / ah.total::float
-                ELSE 0.0
-            END AS completion_rate
-        FROM dates d
-        CROSS JOIN active_habits ah
-        LEFT JOIN daily_completions dc ON dc.local_date_bucket = d.date
-        ORDER BY d.date ASC
+        SELECT local_date_bucket, value FROM habit_completions
+        WHERE habit_id = $1 AND local_date_bucket BETWEEN $2 AND $3
         "#,
     )
-    .bind(auth_user.id)
+    .bind(habit_id)
     .bind(start)
     .bind(end)
     .fetch_all(&state.db)
     .await?;
 
-    Ok(Json(stats))
+    Ok(rows.into_iter().collect())
 }
 
-async fn update_streak(state: &AppState, habit_id: Uuid) -> AppResult<()> {
-    // Calculate current streak by finding consecutive days with completions ending today
-    let today = Utc::now().date_naive();
+/// GET /api/stats/daily — per-date rate over the habits that were active and due
+pub async fn get_daily_stats(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<CompletionQuery>,
+) -> AppResult<(HistoryHeaders, Json<Vec<DailyStats>>)> {
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let start = query.start_date.unwrap_or(today - chrono::Duration::days(30));
+    let end = query.end_date.unwrap_or(today);
+    if start > end {
+        return Err(AppError::Validation(
+            "start_date must be on or before end_date".into(),
+        ));
+    }
+
+    // No stats for days that haven't happened yet
+    let end = end.min(today);
+
+    let mut conn = state.db.acquire().await?;
+    let window = history::analytics_window(&mut conn, auth_user.id, start, end, today).await?;
+    if window.is_empty() {
+        return Ok((window.headers(), Json(Vec::new())));
+    }
+
+    let stats = rollups::read_range(&mut conn, auth_user.id, window.start, window.end)
+        .await?
+        .into_iter()
+        .map(|r| DailyStats {
+            date: r.date,
+            total_habits: r.due_count as i64,
+            completed_habits: r.completed_count as i64,
+            completion_rate: r.rate().unwrap_or(0.0),
+            score: r.score(),
+        })
+        .collect();
+
+    Ok((window.headers(), Json(stats)))
+}
+
+/// GET /api/stats/heatmap?months= — all habits combined, one cell per day
+pub async fn get_aggregate_heatmap(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<HeatmapQuery>,
+) -> AppResult<(HistoryHeaders, Json<Vec<AggregateHeatmapEntry>>)> {
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    let window = history::heatmap_window(&mut conn, auth_user.id, query.months.unwrap_or(3), today).await?;
+
+    let entries = rollups::read_range(&mut conn, auth_user.id, window.start, window.end)
+        .await?
+        .into_iter()
+        .map(|r| AggregateHeatmapEntry {
+            date: r.date,
+            due: r.due_count as i64,
+            completed: r.completed_count as i64,
+            rate: r.rate(),
+        })
+        .collect();
+
+    Ok((window.headers(), Json(entries)))
+}
+
+pub async fn update_streak(state: &AppState, habit_id: Uuid) -> AppResult<()> {
+    // Calculate current streak by finding consecutive days with completions
+    // ending today, in the owner's timezone
+    let today = sqlx::query_scalar::<_, chrono::NaiveDate>(
+        "SELECT to_user_local(NOW(), u.timezone)::date FROM habits h JOIN users u ON u.id = h.user_id WHERE h.id = $1",
+    )
+    .bind(habit_id)
+    .fetch_one(&state.db)
+    .await?;
 
+    // Habits with streak_on_time_only skip days where every check-in was late
     let dates = sqlx::query_scalar::<_, chrono::NaiveDate>(
         r#"
-        SELECT DISTINCT local_date_bucket FROM habit_completions
-        WHERE habit_id = $1
-        ORDER BY local_date_bucket DESC
+        SELECT DISTINCT c.local_date_bucket FROM habit_completions c
+        JOIN habits h ON h.id = c.habit_id
+        WHERE c.habit_id = $1 AND (NOT h.streak_on_time_only OR NOT c.is_late)
+        ORDER BY c.local_date_bucket DESC
         "#,
     )
     .bind(habit_id)
@@ -1,13 +1,17 @@
+//! Legacy `/api/daily-logs` routes, kept for older clients. They read and
+//! write `mood_logs` like `/api/mood`.
+
 use axum::{
     extract::{Query, State},
     Extension, Json,
 };
-use chrono::Utc;
-use uuid::Uuid;
 
 use crate::auth::middleware::AuthUser;
 use crate::error::{AppError, AppResult};
 use crate::models::daily_log::{DailyLog, DailyLogQuery, UpsertDailyLogRequest};
+use crate::models::mood_log::MoodValues;
+use crate::services::history::{self, HistoryHeaders};
+use crate::services::mood;
 use crate::AppState;
 
 pub async fn upsert_daily_log(
@@ -15,93 +19,60 @@ pub async fn upsert_daily_log(
     Extension(auth_user): Extension<AuthUser>,
     Json(body): Json<UpsertDailyLogRequest>,
 ) -> AppResult<Json<DailyLog>> {
-    let log_date = body.log_date.unwrap_or_else(|| Utc::now().date_naive());
-
-    // Validate ranges
-    if let Some(mood) = body.mood {
-        if !(1..=5).contains(&mood) {
-            return Err(AppError::Validation("Mood must be between 1 and 5".into()));
-        }
-    }
-    if let Some(energy) = body.energy {
-        if !(1..=5).contains(&energy) {
-            return Err(AppError::Validation("Energy must be between 1 and 5".into()));
-        }
-    }
-    if let Some(stress) = body.stress {
-        if !(1..=5).contains(&stress) {
-            return Err(AppError::Validation("Stress must be between 1 and 5".into()));
-        }
+    let update = MoodValues {
+        mood: scale("Mood", body.mood)?,
+        energy: scale("Energy", body.energy)?,
+        stress: scale("Stress", body.stress)?,
+        note: body.note,
+    };
+    if update.note.as_ref().is_some_and(|n| n.chars().count() > 5000) {
+        return Err(AppError::Validation("Note must be under 5000 characters".into()));
     }
 
-    let log = sqlx::query_as::<_, DailyLog>(
-        r#"
-        INSERT INTO daily_logs (id, user_id, log_date, mood, energy, stress, note)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
-        ON CONFLICT (user_id, log_date) DO UPDATE SET
-            mood = COALESCE($4, daily_logs.mood),
-            energy = COALESCE($5, daily_logs.energy),
-            stress = COALESCE($6, daily_logs.stress),
-            note = COALESCE($7, daily_logs.note),
-            updated_at = NOW()
-        RETURNING *
-        "#,
-    )
-    .bind(Uuid::new_v4())
-    .bind(auth_user.id)
-    .bind(log_date)
-    .bind(body.mood)
-    .bind(body.energy)
-    .bind(body.stress)
-    .bind(&body.note)
-    .fetch_one(&state.db)
-    .await?;
+    let mut tx = state.db.begin().await?;
+    let log_date = match body.log_date {
+        Some(date) => date,
+        None => mood::local_today(&mut tx, auth_user.id).await?,
+    };
+    let existing = mood::find(&mut tx, auth_user.id, log_date).await?;
+    let is_first = existing.is_none();
+    let values = MoodValues::merge(existing.as_ref(), update, &[]).map_err(AppError::Validation)?;
+    let log = mood::save(&mut tx, auth_user.id, log_date, &values).await?;
+    tx.commit().await?;
 
-    // Demo funnel event: first mood log (deduplicated)
-    if auth_user.is_demo {
-        let already = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM demo_events WHERE demo_user_id = $1 AND event_name = 'demo_first_mood_log'",
-        )
-        .bind(auth_user.id)
-        .fetch_one(&state.db)
-        .await
-        .unwrap_or(1);
-        if already == 0 {
-            let _ = crate::handlers::demo::track_demo_event(
-                &state.db,
-                auth_user.id,
-                "demo_first_mood_log",
-                None,
-            )
-            .await;
-        }
+    if auth_user.is_demo && is_first {
+        crate::handlers::mood::track_first_mood_log(&state, &auth_user).await;
     }
 
-    Ok(Json(log))
+    Ok(Json(log.into()))
 }
 
 pub async fn list_daily_logs(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Query(query): Query<DailyLogQuery>,
-) -> AppResult<Json<Vec<DailyLog>>> {
-    let start = query
-        .start_date
-        .unwrap_or_else(|| Utc::now().date_naive() - chrono::Duration::days(30));
-    let end = query.end_date.unwrap_or_else(|| Utc::now().date_naive());
-
-    let logs = sqlx::query_as::<_, DailyLog>(
-        r#"
-        SELECT * FROM daily_logs
-        WHERE user_id = $1 AND log_date BETWEEN $2 AND $3
-        ORDER BY log_date DESC
-        "#,
+) -> AppResult<(HistoryHeaders, Json<Vec<DailyLog>>)> {
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    let window = history::analytics_window(
+        &mut conn,
+        auth_user.id,
+        query.start_date.unwrap_or(today - chrono::Duration::days(30)),
+        query.end_date.unwrap_or(today),
+        today,
     )
-    .bind(auth_user.id)
-    .bind(start)
-    .bind(end)
-    .fetch_all(&state.db)
     .await?;
 
-    Ok(Json(logs))
+    let logs = mood::list(&mut conn, auth_user.id, window.start, window.end).await?;
+    Ok((window.headers(), Json(logs.into_iter().map(DailyLog::from).collect())))
+}
+
+/// A 1-5 score from the legacy i32 fields.
+fn scale(name: &str, value: Option<i32>) -> AppResult<Option<i16>> {
+    match value {
+        Some(v) if !(1..=5).contains(&v) => {
+            Err(AppError::Validation(format!("{} must be between 1 and 5", name)))
+        }
+        v => Ok(v.map(|v| v as i16)),
+    }
 }
@@ -177,10 +177,28 @@ pub async fn reset_demo(
         .bind(auth_user.id)
         .execute(&mut *tx)
         .await?;
+    // Cascades to journal_entry_habits
+    sqlx::query("DELETE FROM journal_entries WHERE user_id = $1")
+        .bind(auth_user.id)
+        .execute(&mut *tx)
+        .await?;
+    // Cascades to custom_metric_values
+    sqlx::query("DELETE FROM custom_metrics WHERE user_id = $1")
+        .bind(auth_user.id)
+        .execute(&mut *tx)
+        .await?;
     sqlx::query("DELETE FROM insights WHERE user_id = $1")
         .bind(auth_user.id)
         .execute(&mut *tx)
         .await?;
+    sqlx::query("DELETE FROM user_daily_rollups WHERE user_id = $1")
+        .bind(auth_user.id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM period_reviews WHERE user_id = $1")
+        .bind(auth_user.id)
+        .execute(&mut *tx)
+        .await?;
 
     // Reset insight counter and extend expiry
     sqlx::query(
@@ -418,6 +436,19 @@ async fn seed_demo_data(db: &sqlx::PgPool, user_id: Uuid) -> AppResult<()> {
         }
     }
 
+    // Mirror each seeded rollup row as a single check-in
+    sqlx::query(
+        r#"
+        INSERT INTO habit_checkins (habit_id, user_id, local_date_bucket, checked_in_at, value)
+        SELECT habit_id, user_id, local_date_bucket, local_date_bucket + TIME '08:00', value
+        FROM habit_completions
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .execute(db)
+    .await?;
+
     // Compute accurate streaks from seeded completion dates
     for (habit_id, days) in &completion_sets {
         let mut dates: Vec<NaiveDate> = days
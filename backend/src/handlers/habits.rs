@@ -2,28 +2,39 @@ use axum::{
     extract::{Path, State},
     Extension, Json,
 };
-use chrono::{Datelike, Utc};
 use uuid::Uuid;
 
 use crate::auth::middleware::AuthUser;
 use crate::error::{AppError, AppResult};
-use crate::models::habit::{CreateHabitRequest, Habit, HabitWithStatus, UpdateHabitRequest};
+use crate::handlers::completions::update_streak;
+use crate::models::habit::{
+    validate_weights, validate_window, CreateHabitRequest, Habit, HabitWithStatus, TodayView,
+    UpdateHabitRequest,
+};
+use crate::services::{mood, reviews, risk, rollups};
+use crate::services::schedule::Schedule;
+use crate::services::stats::{self, DoneDays};
 use crate::AppState;
 
 pub async fn list_habits(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
 ) -> AppResult<Json<Vec<HabitWithStatus>>> {
-    // Get user timezone for date calculations
-    let _user_tz_str = sqlx::query_scalar::<_, String>(
-        "SELECT timezone FROM users WHERE id = $1",
-    )
-    .bind(auth_user.id)
-    .fetch_one(&state.db)
-    .await
-    .unwrap_or_else(|_| "UTC".to_string());
+    Ok(Json(load_today(&state, auth_user.id).await?.habits))
+}
+
+/// GET /api/habits/today — today's habits plus the overall weighted score
+pub async fn today_view(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> AppResult<Json<TodayView>> {
+    Ok(Json(load_today(&state, auth_user.id).await?))
+}
 
-    let today = Utc::now().date_naive();
+/// Active habits annotated with today's status, and today's weighted score
+/// over the habits due today.
+async fn load_today(state: &AppState, user_id: Uuid) -> AppResult<TodayView> {
+    let today = mood::local_today(&mut *state.db.acquire().await?, user_id).await?;
 
     let habits = sqlx::query_as::<_, Habit>(
         r#"
@@ -32,10 +43,18 @@ pub async fn list_habits(
         ORDER BY sort_order ASC, created_at ASC
         "#,
     )
-    .bind(auth_user.id)
+    .bind(user_id)
     .fetch_all(&state.db)
     .await?;
 
+    let history = risk::load_history(&mut *state.db.acquire().await?, user_id, today).await?;
+    let empty = DoneDays::new();
+    let due_weight: i64 = habits
+        .iter()
+        .filter(|h| compute_is_due_today(h, today))
+        .map(|h| h.weight())
+        .sum();
+
     let mut result = Vec::with_capacity(habits.len());
     for habit in habits {
         let completed_today = sqlx::query_scalar::<_, i64>(
@@ -51,15 +70,33 @@ pub async fn list_habits(
 
         let is_complete = completed_today >= habit.target_per_day;
         let is_due_today = compute_is_due_today(&habit, today);
+        let risk = (is_due_today && !is_complete).then(|| {
+            risk::predict(&habit, today, history.get(&habit.id).unwrap_or(&empty))
+        });
+        let score_share = is_due_today.then(|| {
+            (habit.weight() as f64 * 1000.0 / due_weight as f64).round() / 10.0
+        });
         result.push(HabitWithStatus {
             habit,
             completed_today,
             is_complete,
             is_due_today,
+            score_share,
+            risk,
         });
     }
 
-    Ok(Json(result))
+    let completed_weight = result
+        .iter()
+        .filter(|h| h.is_due_today && h.is_complete)
+        .map(|h| h.habit.weight())
+        .sum();
+
+    Ok(TodayView {
+        date: today,
+        score: stats::score(completed_weight, due_weight),
+        habits: result,
+    })
 }
 
 pub async fn get_habit(
@@ -87,6 +124,8 @@ pub async fn create_habit(
     if body.name.is_empty() {
         return Err(AppError::Validation("Habit name is required".into()));
     }
+    validate_window(body.window_start, body.window_end).map_err(AppError::Validation)?;
+    validate_weights(body.priority, body.difficulty).map_err(AppError::Validation)?;
 
     // Enforce free-tier limit: max 5 habits
     let habit_count =
@@ -128,8 +167,12 @@ pub async fn create_habit(
 
     let habit = sqlx::query_as::<_, Habit>(
         r#"
-        INSERT INTO habits (id, user_id, name, description, color, icon, frequency, frequency_config, target_per_day, reminder_time, sort_order)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        INSERT INTO habits (
+            id, user_id, name, description, color, icon, frequency, frequency_config,
+            target_per_day, reminder_time, sort_order, window_start, window_end, streak_on_time_only,
+            priority, difficulty
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
         RETURNING *
         "#,
     )
@@ -142,11 +185,21 @@ pub async fn create_habit(
     .bind(body.frequency.as_ref().unwrap_or(&crate::models::habit::HabitFrequency::Daily))
     .bind(body.frequency_config.as_ref().unwrap_or(&serde_json::json!({})))
     .bind(body.target_per_day.unwrap_or(1))
-    .bind(&body.reminder_time)
+    .bind(body.reminder_time)
     .bind(next_order)
+    .bind(body.window_start)
+    .bind(body.window_end)
+    .bind(body.streak_on_time_only.unwrap_or(false))
+    .bind(body.priority.unwrap_or(2))
+    .bind(body.difficulty.unwrap_or(2))
     .fetch_one(&state.db)
     .await?;
 
+    // Today's rollup was counted without this habit
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    rollups::invalidate_from(&mut conn, auth_user.id, Some(today)).await?;
+
     Ok(Json(habit))
 }
 
@@ -157,7 +210,7 @@ pub async fn update_habit(
     Json(body): Json<UpdateHabitRequest>,
 ) -> AppResult<Json<Habit>> {
     // Verify ownership
-    let _existing = sqlx::query_as::<_, Habit>(
+    let existing = sqlx::query_as::<_, Habit>(
         "SELECT * FROM habits WHERE id = $1 AND user_id = $2",
     )
     .bind(habit_id)
@@ -166,6 +219,18 @@ pub async fn update_habit(
     .await?
     .ok_or(AppError::NotFound("Habit not found".into()))?;
 
+    // Validate the window as it will be after the partial update
+    let clear_window = body.clear_window.unwrap_or(false);
+    if !clear_window {
+        validate_window(
+            body.window_start.or(existing.window_start),
+            body.window_end.or(existing.window_end),
+        )
+        .map_err(AppError::Validation)?;
+    }
+    validate_weights(body.priority, body.difficulty).map_err(AppError::Validation)?;
+
+    let mut tx = state.db.begin().await?;
     let habit = sqlx::query_as::<_, Habit>(
         r#"
         UPDATE habits SET
@@ -179,6 +244,11 @@ pub async fn update_habit(
             reminder_time = COALESCE($10, reminder_time),
             is_archived = COALESCE($11, is_archived),
             sort_order = COALESCE($12, sort_order),
+            window_start = CASE WHEN $13 THEN NULL ELSE COALESCE($14, window_start) END,
+            window_end = CASE WHEN $13 THEN NULL ELSE COALESCE($15, window_end) END,
+            streak_on_time_only = COALESCE($16, streak_on_time_only),
+            priority = COALESCE($17, priority),
+            difficulty = COALESCE($18, difficulty),
             updated_at = NOW()
         WHERE id = $1 AND user_id = $2
         RETURNING *
@@ -192,13 +262,46 @@ pub async fn update_habit(
     .bind(&body.icon)
     .bind(&body.frequency)
     .bind(&body.frequency_config)
-    .bind(&body.target_per_day)
-    .bind(&body.reminder_time)
-    .bind(&body.is_archived)
-    .bind(&body.sort_order)
-    .fetch_one(&state.db)
+    .bind(body.target_per_day)
+    .bind(body.reminder_time)
+    .bind(body.is_archived)
+    .bind(body.sort_order)
+    .bind(clear_window)
+    .bind(body.window_start)
+    .bind(body.window_end)
+    .bind(body.streak_on_time_only)
+    .bind(body.priority)
+    .bind(body.difficulty)
+    .fetch_one(&mut *tx)
     .await?;
 
+    // Schedule and weight changes rewrite every past day; archiving only
+    // affects today on
+    let schedule_changed = body.frequency.is_some()
+        || body.frequency_config.is_some()
+        || body.target_per_day.is_some_and(|t| t != existing.target_per_day)
+        || habit.weight() != existing.weight();
+    let archive_changed = body.is_archived.is_some_and(|a| a != existing.is_archived);
+    if schedule_changed || archive_changed {
+        let from = if schedule_changed {
+            None
+        } else {
+            Some(mood::local_today(&mut tx, auth_user.id).await?)
+        };
+        rollups::invalidate_from(&mut tx, auth_user.id, from).await?;
+        reviews::invalidate_cached_from(&mut tx, auth_user.id, from).await?;
+    }
+    tx.commit().await?;
+
+    // Which days count toward the streak depends on the on-time rule
+    let streak_rule_changed = habit.streak_on_time_only != existing.streak_on_time_only
+        || habit.window_start != existing.window_start
+        || habit.window_end != existing.window_end;
+    if streak_rule_changed {
+        update_streak(&state, habit_id).await?;
+        return Ok(Json(fetch_owned_habit(&state, habit_id, auth_user.id).await?));
+    }
+
     Ok(Json(habit))
 }
 
@@ -217,25 +320,24 @@ pub async fn delete_habit(
         return Err(AppError::NotFound("Habit not found".into()));
     }
 
+    let mut conn = state.db.acquire().await?;
+    rollups::invalidate_from(&mut conn, auth_user.id, None).await?;
+    reviews::invalidate_cached_from(&mut conn, auth_user.id, None).await?;
+
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
+/// Load a habit, 404 unless it belongs to `user_id`.
+pub async fn fetch_owned_habit(state: &AppState, habit_id: Uuid, user_id: Uuid) -> AppResult<Habit> {
+    sqlx::query_as::<_, Habit>("SELECT * FROM habits WHERE id = $1 AND user_id = $2")
+        .bind(habit_id)
+        .bind(user_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound("Habit not found".into()))
+}
+
 /// Compute whether a habit is due today based on its schedule type.
 fn compute_is_due_today(habit: &Habit, today: chrono::NaiveDate) -> bool {
-    match habit.frequency {
-        crate::models::habit::HabitFrequency::Daily => true,
-        crate::models::habit::HabitFrequency::WeeklyDays => {
-            // frequency_config: { "days": [1, 3, 5] } where 1=Mon, 7=Sun (ISO 8601)
-            if let Some(days) = habit.frequency_config.get("days").and_then(|d| d.as_array()) {
-                let today_iso = today.weekday().number_from_monday() as i64;
-                days.iter().any(|d| d.as_i64() == Some(today_iso))
-            } else {
-                true // malformed config → treat as daily
-            }
-        }
-        crate::models::habit::HabitFrequency::WeeklyTarget => {
-            // weekly_target habits are always "due" — user picks which days
-            true
-        }
-    }
+    Schedule::from_habit(habit).is_due(today)
 }
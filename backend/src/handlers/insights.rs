@@ -1,27 +1,395 @@
-use axum::{extract::State, Extension, Json};
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    Extension, Json,
+};
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::auth::middleware::AuthUser;
-use crate::error::AppResult;
+use crate::dto::InsightResponse;
+use crate::error::{AppError, AppResult};
+use crate::models::insight::{Insight, InsightContent, InsightHistoryQuery};
+use crate::models::user::{SubscriptionTier, UserEntitlements, WeekStart};
+use crate::services::ai_quota;
+use crate::services::correlations::{self, HabitCorrelation};
+use crate::services::fallback_insight::{self, FallbackInput};
+use crate::services::history::{self, HistoryHeaders};
+use crate::services::insight_output;
+use crate::services::insights;
+use crate::services::journal;
+use crate::services::mood;
+use crate::services::notifications;
+use crate::services::recovery::{self, Recovery};
+use crate::services::reviews;
+use crate::services::rollups;
 use crate::AppState;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct InsightResponse {
-    pub summary: String,
-    pub wins: Vec<String>,
-    pub improvements: Vec<String>,
-    pub mood_correlation: Option<String>,
-    pub streak_analysis: String,
-    pub tip_of_the_week: String,
-    pub source: String, // "claude" or "fallback"
+/// Mood/habit correlations need more days than the 30-day habit summary
+const CORRELATION_DAYS: i64 = 90;
+/// Weeks returned by the history endpoint by default, and at most
+const HISTORY_LIMIT: i64 = 12;
+const MAX_HISTORY_LIMIT: i64 = 52;
+/// Journal entries summarised for opted-in users
+const JOURNAL_DAYS: i64 = 7;
+const JOURNAL_MAX_ENTRIES: usize = 20;
+const JOURNAL_EXCERPT_CHARS: usize = 280;
+/// Local hour on Monday from which the weekly insight job picks a user up
+pub const WEEKLY_INSIGHT_HOUR: i32 = 6;
+/// Users generated in parallel by the weekly job
+const WEEKLY_INSIGHT_CONCURRENCY: usize = 4;
+/// Tries per user per run; later hours of the Monday retry again
+const WEEKLY_INSIGHT_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Deserialize)]
+pub struct CorrelationQuery {
+    /// Days of history. Default: 90, clamped to the tier's analytics window
+    pub days: Option<i64>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct CorrelationResponse {
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub min_samples: usize,
+    pub habits: Vec<HabitCorrelation>,
+}
+
+/// GET /api/insights and POST /api/insights/generate — this ISO week's
+/// insight, generated and stored on the first request of the week
 pub async fn get_insights(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
 ) -> AppResult<Json<InsightResponse>> {
+    let mut conn = state.db.acquire().await?;
+    let week_start = insights::iso_week_start(mood::local_today(&mut conn, auth_user.id).await?);
+    let cached = insights::for_week(&mut conn, auth_user.id, week_start).await?;
+    drop(conn);
+    let insight = match cached {
+        Some(insight) => insight,
+        None => generate_and_store(&state, &auth_user, week_start, false).await?,
+    };
+    with_quota(&state, &auth_user, insight).await
+}
+
+/// POST /api/insights/regenerate — replace this week's stored insight.
+/// 429 while the AI quota is spent (tiers without AI regenerate the fallback)
+pub async fn regenerate_insights(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> AppResult<Json<InsightResponse>> {
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let insight =
+        generate_and_store(&state, &auth_user, insights::iso_week_start(today), true).await?;
+    with_quota(&state, &auth_user, insight).await
+}
+
+/// GET /api/insights/latest — the most recent stored insight; never generates
+pub async fn get_latest_insight(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> AppResult<Json<InsightResponse>> {
+    let insight = insights::latest(&mut *state.db.acquire().await?, auth_user.id)
+        .await?
+        .ok_or(AppError::NotFound("No insights generated yet".into()))?;
+    with_quota(&state, &auth_user, insight).await
+}
+
+/// GET /api/insights/history?limit= — stored insights, newest week first
+pub async fn get_insight_history(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<InsightHistoryQuery>,
+) -> AppResult<Json<Vec<InsightResponse>>> {
+    let limit = query
+        .limit
+        .unwrap_or(HISTORY_LIMIT)
+        .clamp(1, MAX_HISTORY_LIMIT);
+    let list = insights::history(&mut *state.db.acquire().await?, auth_user.id, limit).await?;
+    Ok(Json(list.into_iter().map(InsightResponse::from).collect()))
+}
+
+/// Generate and store the week's insight. A model call is only made when
+/// the AI quota has room (demo users have their own cap instead); otherwise
+/// the free fallback is stored, except on regeneration, which is refused
+/// rather than replacing an AI insight with the fallback. A regeneration
+/// whose model call fails keeps the stored AI insight.
+async fn generate_and_store(
+    state: &AppState,
+    auth_user: &AuthUser,
+    week_start: chrono::NaiveDate,
+    regenerate: bool,
+) -> AppResult<Insight> {
+    let content = generate_charged(state, auth_user, week_start, regenerate).await?;
+    Ok(insights::store(
+        &mut *state.db.acquire().await?,
+        auth_user.id,
+        week_start,
+        &content,
+    )
+    .await?)
+}
+
+/// The week's insight, not yet stored, with its model calls charged to the
+/// local week's AI quota.
+async fn generate_charged(
+    state: &AppState,
+    auth_user: &AuthUser,
+    week_start: chrono::NaiveDate,
+    regenerate: bool,
+) -> AppResult<InsightContent> {
+    let limit = history::entitlements(&mut *state.db.acquire().await?, auth_user.id)
+        .await?
+        .ai_insights_per_week;
+    let reservation = if auth_user.is_demo {
+        None
+    } else {
+        let mut tx = state.db.begin().await?;
+        let reserved =
+            ai_quota::reserve(&mut tx, auth_user.id, limit, week_start, chrono::Utc::now()).await?;
+        tx.commit().await?;
+        if reserved.is_none() && regenerate && limit != Some(0) {
+            return Err(AppError::RateLimited);
+        }
+        reserved
+    };
+    let use_ai = auth_user.is_demo || reservation.is_some();
+
+    let (content, model_calls) = generate_insight(state, auth_user, use_ai).await?;
+    let mut conn = state.db.acquire().await?;
+    // Unusable replies were still billed: only a call that never completed
+    // is given back
+    if let Some(usage_id) = reservation {
+        match model_calls {
+            0 => ai_quota::refund(&mut conn, usage_id).await?,
+            calls => ai_quota::record_calls(&mut conn, usage_id, calls).await?,
+        }
+    }
+    Ok(content)
+}
+
+/// The response for a stored insight, with the user's current AI quota.
+async fn with_quota(
+    state: &AppState,
+    auth_user: &AuthUser,
+    insight: Insight,
+) -> AppResult<Json<InsightResponse>> {
+    let mut conn = state.db.acquire().await?;
+    let limit = history::entitlements(&mut conn, auth_user.id)
+        .await?
+        .ai_insights_per_week;
+    let week_start = insights::iso_week_start(mood::local_today(&mut conn, auth_user.id).await?);
+    let quota = ai_quota::status(
+        &mut conn,
+        auth_user.id,
+        limit,
+        week_start,
+        chrono::Utc::now(),
+    )
+    .await?;
+    Ok(Json(InsightResponse {
+        quota: Some(quota),
+        ..insight.into()
+    }))
+}
+
+// ── Weekly Insight Worker ────────────────────────────────────────────────────
+
+/// Hourly: generate this week's insight for eligible users with active
+/// habits whose local time is Monday, from WEEKLY_INSIGHT_HOUR on, who don't
+/// have one yet. Users
+/// missed by one tick (or whose generation failed) are picked up by the next,
+/// as are users who only got the fallback while a model call is still
+/// available to them.
+pub fn spawn_weekly_insight_worker(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            match run_weekly_insights(&state).await {
+                Ok(count) => {
+                    if count > 0 {
+                        tracing::info!(generated = count, "Weekly insights: generated");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Weekly insight worker error");
+                }
+            }
+        }
+    });
+}
+
+/// Tiers that include AI insights get one generated every Monday
+fn gets_weekly_insight(tier: &SubscriptionTier) -> bool {
+    UserEntitlements::for_tier(tier).ai_insights_per_week != Some(0)
+}
+
+/// Wait before retry `attempt` (1-based): 2s, 8s, ...
+fn retry_delay(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_secs(2 * 4u64.pow(attempt - 1))
+}
+
+async fn run_weekly_insights(state: &AppState) -> Result<usize, sqlx::Error> {
+    // On a local Monday, the local date is the ISO week start
+    // A week holding only the fallback is still due; has_fallback says so
+    let due = sqlx::query_as::<_, (Uuid, SubscriptionTier, chrono::NaiveDate, bool)>(
+        r#"
+        SELECT u.id, u.subscription_tier, to_user_local(NOW(), u.timezone)::date AS week_start,
+               EXISTS (
+                   SELECT 1 FROM insights i
+                   WHERE i.user_id = u.id
+                     AND i.week_start_date = to_user_local(NOW(), u.timezone)::date
+                     AND i.source = 'fallback'
+               ) AS has_fallback
+        FROM users u
+        WHERE u.is_demo = false
+          AND EXISTS (SELECT 1 FROM habits h WHERE h.user_id = u.id AND h.is_archived = false)
+          AND EXTRACT(ISODOW FROM to_user_local(NOW(), u.timezone)) = 1
+          AND EXTRACT(HOUR FROM to_user_local(NOW(), u.timezone)) >= $1
+          AND NOT EXISTS (
+              SELECT 1 FROM insights i
+              WHERE i.user_id = u.id
+                AND i.week_start_date = to_user_local(NOW(), u.timezone)::date
+                AND i.source <> 'fallback'
+          )
+        "#,
+    )
+    .bind(WEEKLY_INSIGHT_HOUR)
+    .fetch_all(&state.db)
+    .await?;
+
+    let generated = std::sync::atomic::AtomicUsize::new(0);
+    futures_util::stream::iter(
+        due.into_iter()
+            .filter(|(_, tier, _, _)| gets_weekly_insight(tier)),
+    )
+    .for_each_concurrent(
+        WEEKLY_INSIGHT_CONCURRENCY,
+        |(user_id, _, week_start, has_fallback)| {
+            let generated = &generated;
+            async move {
+                if weekly_insight_for(state, user_id, week_start, has_fallback).await {
+                    generated.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                }
+            }
+        },
+    )
+    .await;
+    Ok(generated.into_inner())
+}
+
+/// Whether a model call could be charged to the week right now.
+async fn ai_available(
+    state: &AppState,
+    user_id: Uuid,
+    week_start: chrono::NaiveDate,
+) -> AppResult<bool> {
+    let mut conn = state.db.acquire().await?;
+    let limit = history::entitlements(&mut conn, user_id)
+        .await?
+        .ai_insights_per_week;
+    let quota = ai_quota::status(&mut conn, user_id, limit, week_start, chrono::Utc::now()).await?;
+    Ok(quota.available)
+}
+
+/// Generate and store one user's weekly insight, retrying errors, then
+/// announce it. A fallback while a model call is still available means the
+/// call failed without being charged, so it is retried too; if it is all
+/// that comes back it is stored and announced once, and later ticks try to
+/// replace it. Returns whether an insight was stored.
+async fn weekly_insight_for(
+    state: &AppState,
+    user_id: Uuid,
+    week_start: chrono::NaiveDate,
+    has_fallback: bool,
+) -> bool {
+    let auth_user = AuthUser {
+        id: user_id,
+        email: None,
+        is_demo: false,
+    };
+    if has_fallback
+        && !ai_available(state, user_id, week_start)
+            .await
+            .unwrap_or(false)
+    {
+        return false;
+    }
+    let mut attempt = 1;
+    let content = loop {
+        match generate_charged(state, &auth_user, week_start, false).await {
+            Ok(content) => {
+                let retry = content.source == "fallback"
+                    && attempt < WEEKLY_INSIGHT_ATTEMPTS
+                    && ai_available(state, user_id, week_start)
+                        .await
+                        .unwrap_or(false);
+                if !retry {
+                    break content;
+                }
+                tracing::warn!(user_id = %user_id, attempt, "Weekly insight fell back to the fallback, retrying");
+            }
+            Err(e) if attempt < WEEKLY_INSIGHT_ATTEMPTS => {
+                tracing::warn!(user_id = %user_id, attempt, error = %e, "Weekly insight failed, retrying");
+            }
+            Err(e) => {
+                tracing::error!(user_id = %user_id, error = %e, "Weekly insight failed");
+                return false;
+            }
+        }
+        // Tests retry at once
+        if !cfg!(test) {
+            tokio::time::sleep(retry_delay(attempt)).await;
+        }
+        attempt += 1;
+    };
+    // The stored fallback was already announced
+    if has_fallback && content.source == "fallback" {
+        return false;
+    }
+    let stored = match state.db.acquire().await {
+        Ok(mut conn) => insights::store(&mut conn, user_id, week_start, &content).await,
+        Err(e) => Err(e),
+    };
+    let insight = match stored {
+        Ok(insight) => insight,
+        Err(e) => {
+            tracing::error!(user_id = %user_id, error = %e, "Weekly insight failed to store");
+            return false;
+        }
+    };
+
+    if let Some(tx) = state.ws_tx.as_ref() {
+        let msg = serde_json::json!({
+            "type": "insight_ready",
+            "user_id": user_id,
+            "insight_id": insight.id,
+            "week_start_date": insight.week_start_date,
+            "source": insight.source,
+        });
+        let _ = tx.send(msg.to_string());
+    }
+    if let Err(e) =
+        notifications::enqueue_insight_ready(&state.db, user_id, insight.id, week_start).await
+    {
+        tracing::warn!(user_id = %user_id, error = %e, "Failed to enqueue insight_ready notification");
+    }
+    true
+}
+
+/// Build an insight from the last 30 days: from the model when `use_ai`,
+/// otherwise (or if the model fails) from the deterministic fallback. Also
+/// returns the number of model calls that completed.
+async fn generate_insight(
+    state: &AppState,
+    auth_user: &AuthUser,
+    use_ai: bool,
+) -> AppResult<(InsightContent, u32)> {
     // Gather user's habit data for the last 30 days
-    let today = chrono::Utc::now().date_naive();
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
     let thirty_days_ago = today - chrono::Duration::days(30);
 
     let habits = sqlx::query_as::<_, crate::models::habit::Habit>(
@@ -31,27 +399,66 @@ pub async fn get_insights(
     .fetch_all(&state.db)
     .await?;
 
-    let completions = sqlx::query_as::<_, crate::models::completion::Completion>(
+    // The period total comes from the daily rollups
+    let mut conn = state.db.acquire().await?;
+    let total_completions: i64 =
+        rollups::read_range(&mut conn, auth_user.id, thirty_days_ago, today)
+            .await?
+            .iter()
+            .map(|r| r.completion_count as i64)
+            .sum();
+
+    // Today isn't over, so recovery stops at yesterday
+    let week_start_day =
+        sqlx::query_scalar::<_, WeekStart>("SELECT week_start FROM users WHERE id = $1")
+            .bind(auth_user.id)
+            .fetch_one(&mut *conn)
+            .await?;
+    let yesterday = today - chrono::Duration::days(1);
+    let done = reviews::load_done(&mut conn, auth_user.id, thirty_days_ago, today).await?;
+    let (habit_recovery, total_recovery) =
+        recovery::for_habits(&habits, &done, thirty_days_ago, yesterday, week_start_day);
+
+    // Correlations look further back, but no further than the tier allows
+    let correlation_window = history::analytics_window(
+        &mut conn,
+        auth_user.id,
+        today - chrono::Duration::days(CORRELATION_DAYS - 1),
+        today,
+        today,
+    )
+    .await?;
+    let correlations = correlations::load(
+        &mut conn,
+        auth_user.id,
+        &habits,
+        correlation_window.start,
+        correlation_window.end,
+    )
+    .await?;
+    let correlation_days = (correlation_window.end - correlation_window.start).num_days() + 1;
+    drop(conn);
+
+    let mood_averages = sqlx::query_as::<_, (i64, Option<f64>, Option<f64>, Option<f64>)>(
         r#"
-        SELECT * FROM habit_completions
+        SELECT COUNT(*), AVG(mood)::float8, AVG(energy)::float8, AVG(stress)::float8
+        FROM mood_logs
         WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
-        ORDER BY local_date_bucket DESC
         "#,
     )
     .bind(auth_user.id)
     .bind(thirty_days_ago)
     .bind(today)
-    .fetch_all(&state.db)
+    .fetch_one(&state.db)
     .await?;
 
-    // Build context for Claude
+    let journal_section = journal_context(state, auth_user, &habits, today).await?;
+
+    // Build context for the model
     let habit_summary: Vec<String> = habits
         .iter()
         .map(|h| {
-            let completions_for_habit = completions
-                .iter()
-                .filter(|c| c.habit_id == h.id)
-                .count();
+            let completions_for_habit = done.get(&h.id).map_or(0, |d| d.len());
             format!(
                 "- {} (streak: {}, completions last 30d: {}, target/day: {})",
                 h.name, h.current_streak, completions_for_habit, h.target_per_day
@@ -67,7 +474,16 @@ Habits:
 
 Total completions in period: {}
 
-Provide a JSON response with this exact schema:
+Mood (1-5 scale; stress 5 = very high):
+{}
+
+Mood/habit correlations over the last {} days (only statistically significant ones):
+{}
+
+Recovery after misses ("never miss twice"; a double miss is two or more in a row):
+{}
+{}
+Reply with only a JSON object in this exact schema (no prose, no code fences; at most {} wins and {} improvements, each under {} characters):
 {{
   "summary": "2-3 sentence progress summary",
   "wins": ["specific win 1", "specific win 2"],
@@ -77,9 +493,33 @@ Provide a JSON response with this exact schema:
   "tip_of_the_week": "one specific tip"
 }}"#,
         habit_summary.join("\n"),
-        completions.len()
+        total_completions,
+        mood_context(mood_averages),
+        correlation_days,
+        correlation_context(&correlations),
+        recovery_context(&habits, &habit_recovery, &total_recovery),
+        journal_section,
+        insight_output::MAX_LIST_ITEMS,
+        insight_output::MAX_LIST_ITEMS,
+        insight_output::MAX_ITEM_CHARS,
     );
 
+    let fallback_input = FallbackInput {
+        habits: &habits,
+        done: &done,
+        correlations: &correlations,
+        recovery: &habit_recovery,
+        total_recovery: &total_recovery,
+        start: thirty_days_ago,
+        end: yesterday,
+        week_start: week_start_day,
+    };
+    let fallback = || fallback_insight::generate(&fallback_input, today);
+
+    if !use_ai {
+        return Ok((fallback(), 0));
+    }
+
     // Demo mode: enforce AI call cap (atomic check-and-increment)
     if auth_user.is_demo {
         let updated = sqlx::query_scalar::<_, i32>(
@@ -96,8 +536,7 @@ Provide a JSON response with this exact schema:
 
         if updated.is_none() {
             tracing::info!(user_id = %auth_user.id, "Demo insight cap reached, using fallback");
-            let insight = generate_fallback_insight(&habits, &completions);
-            return Ok(Json(insight));
+            return Ok((fallback(), 0));
         }
 
         // Track demo event
@@ -110,143 +549,321 @@ Provide a JSON response with this exact schema:
         .await;
     }
 
-    // Try Claude API, fall back to deterministic if unavailable
-    let insight = match call_claude(&state, &prompt).await {
-        Ok(mut insight) => {
-            insight.source = "claude".to_string();
-            insight
-        }
-        Err(e) => {
-            tracing::warn!(error = %e, "Claude API unavailable, using deterministic fallback");
-            generate_fallback_insight(&habits, &completions)
+    // Try the model, fall back to deterministic if unavailable. Failures
+    // are already logged with their kind by insight_output.
+    let generation = insight_output::generate(state.insight_provider.as_ref(), &prompt).await;
+    let insight = match generation.result {
+        Ok(insight) => insight,
+        Err(failure) => {
+            tracing::info!(
+                metric = "insight_generated",
+                source = "fallback",
+                reason = failure.kind()
+            );
+            fallback()
         }
     };
 
-    Ok(Json(insight))
-}
-
-async fn call_claude(state: &AppState, prompt: &str) -> Result<InsightResponse, anyhow::Error> {
-    // B-14: Add 30-second timeout to prevent indefinite hangs
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
-    
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
-        .header("x-api-key", &state.config.claude_api_key)
-        .header("anthropic-version", "2023-06-01")
-        .header("content-type", "application/json")
-        .json(&serde_json::json!({
-            "model": state.config.claude_model,
-            "max_tokens": 1024,
-            "messages": [{
-                "role": "user",
-                "content": prompt
-            }]
-        }))
-        .send()
-        .await?;
+    Ok((insight, generation.calls))
+}
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("Claude API error {}: {}", status, body);
-    }
+/// GET /api/insights/correlations?days= — per-habit mood, energy and stress effects
+pub async fn get_correlations(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<CorrelationQuery>,
+) -> AppResult<(HistoryHeaders, Json<CorrelationResponse>)> {
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let days = query.days.unwrap_or(CORRELATION_DAYS).clamp(1, 365);
+    let window = history::analytics_window(
+        &mut *state.db.acquire().await?,
+        auth_user.id,
+        today - chrono::Duration::days(days - 1),
+        today,
+        today,
+    )
+    .await?;
+    let (start_date, end_date) = (window.start, window.end);
+
+    let habits = sqlx::query_as::<_, crate::models::habit::Habit>(
+        "SELECT * FROM habits WHERE user_id = $1 AND is_archived = false ORDER BY sort_order, created_at",
+    )
+    .bind(auth_user.id)
+    .fetch_all(&state.db)
+    .await?;
 
-    let claude_response: serde_json::Value = response.json().await?;
-    let text = claude_response["content"][0]["text"]
-        .as_str()
-        .unwrap_or("{}");
+    let habits = correlations::load(
+        &mut *state.db.acquire().await?,
+        auth_user.id,
+        &habits,
+        start_date,
+        end_date,
+    )
+    .await?;
 
-    let mut insight: InsightResponse = serde_json::from_str(text)?;
-    insight.source = "claude".to_string();
-    Ok(insight)
+    Ok((
+        window.headers(),
+        Json(CorrelationResponse {
+            start_date,
+            end_date,
+            min_samples: correlations::MIN_SAMPLES,
+            habits,
+        }),
+    ))
 }
 
-fn generate_fallback_insight(
+/// Recent journal entries for the prompt, only if the user opted in.
+/// Empty otherwise, so nothing about the journal reaches the model.
+async fn journal_context(
+    state: &AppState,
+    auth_user: &AuthUser,
     habits: &[crate::models::habit::Habit],
-    completions: &[crate::models::completion::Completion],
-) -> InsightResponse {
-    if habits.is_empty() {
-        return InsightResponse {
-            summary: "You haven't created any habits yet. Start by adding a habit to track!".into(),
-            wins: vec![],
-            improvements: vec!["Create your first habit to get started".into()],
-            mood_correlation: None,
-            streak_analysis: "No data available yet.".into(),
-            tip_of_the_week: "Start small — one habit, done consistently, beats five habits done sporadically.".into(),
-            source: "fallback".into(),
-        };
+    today: chrono::NaiveDate,
+) -> AppResult<String> {
+    let mut conn = state.db.acquire().await?;
+    let opted_in =
+        sqlx::query_scalar::<_, bool>("SELECT journal_in_insights FROM users WHERE id = $1")
+            .bind(auth_user.id)
+            .fetch_one(&mut *conn)
+            .await?;
+    if !opted_in {
+        return Ok(String::new());
     }
 
-    // Find best and worst habits by completion rate
-    let mut habit_rates: Vec<(&crate::models::habit::Habit, f64)> = habits
-        .iter()
-        .map(|h| {
-            let count = completions.iter().filter(|c| c.habit_id == h.id).count() as f64;
-            let rate = count / 30.0;
-            (h, rate)
-        })
-        .collect();
-    habit_rates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    let start = today - chrono::Duration::days(JOURNAL_DAYS - 1);
+    let mut entries = journal::list(&mut conn, auth_user.id, start, today, None, None).await?;
+    if entries.is_empty() {
+        return Ok(String::new());
+    }
+    entries.truncate(JOURNAL_MAX_ENTRIES);
+    let name = |id: Uuid| habits.iter().find(|h| h.id == id).map(|h| h.name.clone());
+    Ok(format!(
+        "\nJournal entries from the last {} days (shared by the user; summarise themes, don't quote):\n{}\n",
+        JOURNAL_DAYS,
+        journal::summarize(&entries, name, JOURNAL_EXCERPT_CHARS).join("\n"),
+    ))
+}
 
-    let best = habit_rates.first();
-    let worst = habit_rates.last();
+fn mood_context(
+    (days, mood, energy, stress): (i64, Option<f64>, Option<f64>, Option<f64>),
+) -> String {
+    if days == 0 {
+        return "- No mood logs in this period".into();
+    }
+    let fmt = |v: Option<f64>| v.map_or("n/a".to_string(), |v| format!("{:.1}", v));
+    format!(
+        "- {} days logged; average mood {}, energy {}, stress {}",
+        days,
+        fmt(mood),
+        fmt(energy),
+        fmt(stress)
+    )
+}
 
-    let total_rate = if !habit_rates.is_empty() {
-        habit_rates.iter().map(|(_, r)| r).sum::<f64>() / habit_rates.len() as f64
-    } else {
-        0.0
+fn correlation_context(correlations: &[HabitCorrelation]) -> String {
+    let found = correlations::significant_effects(correlations);
+    if found.is_empty() {
+        return "- None found (not enough mood logs or no clear difference)".into();
+    }
+    found
+        .iter()
+        .map(|(habit, effect)| format!("- {}", correlations::describe(habit, effect)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn recovery_context(
+    habits: &[crate::models::habit::Habit],
+    habit_recovery: &HashMap<Uuid, Recovery>,
+    total: &Recovery,
+) -> String {
+    let Some(summary) = recovery::describe(total) else {
+        return "- No settled misses in this period".into();
     };
+    let mut lines = vec![format!("- Overall: {}", summary)];
+    for h in habits {
+        if let Some(r) = habit_recovery.get(&h.id).filter(|r| r.misses > 0) {
+            lines.push(format!(
+                "- {}: recovered {} of {}, double misses {}",
+                h.name, r.recovered, r.misses, r.double_misses
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm::{FakeProvider, InsightProvider};
 
-    let summary = if let (Some((best_h, best_r)), Some((worst_h, _))) = (best, worst) {
-        format!(
-            "Over the last 30 days, your overall completion rate is {:.0}%. {} is your strongest habit at {:.0}% completion. Consider focusing more on {}.",
-            total_rate * 100.0,
-            best_h.name,
-            best_r * 100.0,
-            worst_h.name,
+    #[test]
+    fn test_weekly_insight_eligibility_and_backoff() {
+        assert!(!gets_weekly_insight(&SubscriptionTier::Free));
+        assert!(gets_weekly_insight(&SubscriptionTier::Plus));
+        assert!(gets_weekly_insight(&SubscriptionTier::Pro));
+
+        assert_eq!(retry_delay(1).as_secs(), 2);
+        assert_eq!(retry_delay(2).as_secs(), 8);
+    }
+
+    /// A Plus user with one habit, and an `AppState` around `provider`.
+    async fn plus_user_state(
+        url: &str,
+        provider: std::sync::Arc<dyn InsightProvider>,
+    ) -> (AppState, AuthUser) {
+        let db = sqlx::PgPool::connect(url).await.unwrap();
+        let user_id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO users (email, password_hash, subscription_tier) VALUES ($1, 'x', 'plus') RETURNING id",
         )
-    } else {
-        "Keep tracking your habits consistently!".into()
-    };
+        .bind(format!("{}@insights.test", Uuid::new_v4()))
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO habits (user_id, name) VALUES ($1, 'Read')")
+            .bind(user_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        let state = AppState {
+            db,
+            config: std::sync::Arc::new(crate::config::Config::for_tests(url)),
+            ws_tx: None,
+            rate_limiter: crate::auth::rate_limit::RateLimitState::new(),
+            insight_provider: provider,
+        };
+        let auth_user = AuthUser {
+            id: user_id,
+            email: None,
+            is_demo: false,
+        };
+        (state, auth_user)
+    }
 
-    let mut wins = Vec::new();
-    if let Some((h, r)) = best {
-        if *r > 0.5 {
-            wins.push(format!("{} completed {:.0}% of the time — great consistency!", h.name, r * 100.0));
-        }
+    /// Delete a fixture user; their habits, insights and usage cascade.
+    async fn remove_user(state: &AppState, user_id: Uuid) {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&state.db)
+            .await
+            .unwrap();
     }
-    let max_streak = habits.iter().map(|h| h.current_streak).max().unwrap_or(0);
-    if max_streak > 0 {
-        let streak_habit = habits.iter().max_by_key(|h| h.current_streak).unwrap();
-        wins.push(format!("{}-day streak on {} — keep it going!", max_streak, streak_habit.name));
+
+    async fn model_calls(state: &AppState, user_id: Uuid) -> Vec<i32> {
+        sqlx::query_scalar("SELECT model_calls FROM ai_insight_usage WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&state.db)
+            .await
+            .unwrap()
     }
 
-    let mut improvements = Vec::new();
-    if let Some((h, r)) = worst {
-        if *r < 0.5 {
-            improvements.push(format!("Try setting a reminder for {} to improve your {:.0}% rate.", h.name, r * 100.0));
-        }
+    /// The insight path end to end with `FakeProvider`. Needs a migrated
+    /// database, so it only runs when DATABASE_URL is set (as in CI); each
+    /// user is removed once checked.
+    #[tokio::test]
+    async fn test_generate_and_store_with_fake_provider() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return;
+        };
+        let week_start = insights::iso_week_start(chrono::Utc::now().date_naive());
+
+        // A usable reply is stored under the provider's name and charged
+        let fake = std::sync::Arc::new(FakeProvider::default());
+        let (state, user) = plus_user_state(&url, fake.clone()).await;
+        let insight = generate_and_store(&state, &user, week_start, false)
+            .await
+            .unwrap();
+        assert_eq!(insight.source, "fake");
+        assert_eq!(insight.summary, "A steady week with your habits.");
+        assert_eq!(fake.calls(), 1);
+        assert_eq!(model_calls(&state, user.id).await, vec![1]);
+        // Plus has one call a week, so regenerating is refused
+        let again = generate_and_store(&state, &user, week_start, true).await;
+        assert!(matches!(again, Err(AppError::RateLimited)));
+        remove_user(&state, user.id).await;
+
+        // Unusable replies fall back but stay charged, repair included
+        let fake = std::sync::Arc::new(FakeProvider::replying("no json here"));
+        let (state, user) = plus_user_state(&url, fake.clone()).await;
+        let insight = generate_and_store(&state, &user, week_start, false)
+            .await
+            .unwrap();
+        assert_eq!(insight.source, "fallback");
+        assert_eq!(fake.calls(), 2);
+        assert_eq!(model_calls(&state, user.id).await, vec![2]);
+        remove_user(&state, user.id).await;
+
+        // A failed call is refunded
+        let fake = std::sync::Arc::new(FakeProvider::failing("timeout"));
+        let (state, user) = plus_user_state(&url, fake).await;
+        let insight = generate_and_store(&state, &user, week_start, false)
+            .await
+            .unwrap();
+        assert_eq!(insight.source, "fallback");
+        assert!(model_calls(&state, user.id).await.is_empty());
+        remove_user(&state, user.id).await;
+
+        // A regeneration that only gets the fallback keeps the AI insight
+        let reply = FakeProvider::default().complete("").await.unwrap();
+        let fake = std::sync::Arc::new(FakeProvider::sequence(vec![
+            Ok(reply),
+            Err("timeout".into()),
+        ]));
+        let (state, user) = plus_user_state(&url, fake.clone()).await;
+        generate_and_store(&state, &user, week_start, false)
+            .await
+            .unwrap();
+        // As if the quota had reset
+        sqlx::query("DELETE FROM ai_insight_usage WHERE user_id = $1")
+            .bind(user.id)
+            .execute(&state.db)
+            .await
+            .unwrap();
+        let kept = generate_and_store(&state, &user, week_start, true)
+            .await
+            .unwrap();
+        assert_eq!((kept.source.as_str(), fake.calls()), ("fake", 2));
+        let stored =
+            insights::for_week(&mut state.db.acquire().await.unwrap(), user.id, week_start)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(stored.source, "fake");
+        remove_user(&state, user.id).await;
     }
-    improvements.push("Consider pairing a difficult habit with one you enjoy.".into());
-    improvements.push("Track at the same time each day to build automaticity.".into());
 
-    let streak_analysis = if max_streak > 7 {
-        format!("Your longest active streak is {} days. Streaks above 7 days indicate strong habit formation.", max_streak)
-    } else if max_streak > 0 {
-        format!("Your longest active streak is {} days. Focus on not breaking the chain.", max_streak)
-    } else {
-        "No active streaks. Complete a habit today to start building momentum.".into()
-    };
+    /// Needs a migrated database, like the test above; the users are
+    /// removed at the end.
+    #[tokio::test]
+    async fn test_weekly_insight_retries_an_uncharged_fallback() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return;
+        };
+        let week_start = insights::iso_week_start(chrono::Utc::now().date_naive());
+        let reply = FakeProvider::default().complete("").await.unwrap();
+
+        // The first call fails and is refunded, so the retry gets the model
+        let fake = std::sync::Arc::new(FakeProvider::sequence(vec![
+            Err("timeout".into()),
+            Ok(reply),
+        ]));
+        let (state, user) = plus_user_state(&url, fake.clone()).await;
+        assert!(weekly_insight_for(&state, user.id, week_start, false).await);
+        let stored =
+            insights::for_week(&mut state.db.acquire().await.unwrap(), user.id, week_start)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(stored.source, "fake");
+        assert_eq!(fake.calls(), 2);
+        remove_user(&state, user.id).await;
 
-    InsightResponse {
-        summary,
-        wins,
-        improvements,
-        mood_correlation: None,
-        streak_analysis,
-        tip_of_the_week: "The best time to build a habit is right after an existing routine — this is called habit stacking.".into(),
-        source: "fallback".into(),
+        // A stored fallback with the quota spent is left alone
+        let fake = std::sync::Arc::new(FakeProvider::replying("no json here"));
+        let (state, user) = plus_user_state(&url, fake.clone()).await;
+        assert!(weekly_insight_for(&state, user.id, week_start, false).await);
+        assert!(!weekly_insight_for(&state, user.id, week_start, true).await);
+        assert_eq!(fake.calls(), 2);
+        remove_user(&state, user.id).await;
     }
 }
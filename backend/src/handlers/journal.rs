@@ -0,0 +1,205 @@
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::{AppError, AppResult};
+use crate::models::journal::{
+    normalize_tags, validate_body, CreateJournalEntryRequest, JournalEntry, JournalQuery,
+    UpdateJournalEntryRequest,
+};
+use crate::services::history::{self, HistoryHeaders};
+use crate::services::journal::{self, Prompt};
+use crate::services::mood;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct PromptQuery {
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromptsResponse {
+    pub date: NaiveDate,
+    pub prompts: Vec<&'static Prompt>,
+}
+
+/// GET /api/journal/prompts?date= — the day's reflection prompts
+pub async fn get_prompts(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<PromptQuery>,
+) -> AppResult<Json<PromptsResponse>> {
+    let date = match query.date {
+        Some(date) => date,
+        None => mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?,
+    };
+    Ok(Json(PromptsResponse {
+        date,
+        prompts: journal::prompts_for(date),
+    }))
+}
+
+/// GET /api/journal?start_date=&end_date=&tag=&habit_id= — default: the last 30 days
+pub async fn list_entries(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<JournalQuery>,
+) -> AppResult<(HistoryHeaders, Json<Vec<JournalEntry>>)> {
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    let end = query.end_date.unwrap_or(today);
+    let start = query.start_date.unwrap_or(end - Duration::days(29));
+    let window = history::analytics_window(&mut conn, auth_user.id, start, end, today).await?;
+    let tag = match query.tag.as_deref() {
+        Some(tag) => normalize_tags(&[tag.to_string()])
+            .map_err(AppError::Validation)?
+            .pop(),
+        None => None,
+    };
+
+    let entries = journal::list(
+        &mut conn,
+        auth_user.id,
+        window.start,
+        window.end,
+        tag.as_deref(),
+        query.habit_id,
+    )
+    .await?;
+    Ok((window.headers(), Json(entries)))
+}
+
+/// POST /api/journal
+pub async fn create_entry(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(body): Json<CreateJournalEntryRequest>,
+) -> AppResult<Json<JournalEntry>> {
+    validate_body(&body.body).map_err(AppError::Validation)?;
+    let tags = normalize_tags(&body.tags).map_err(AppError::Validation)?;
+    check_prompt(body.prompt_id.as_deref())?;
+
+    let mut tx = state.db.begin().await?;
+    if !journal::owns_habits(&mut tx, auth_user.id, &body.habit_ids).await? {
+        return Err(AppError::NotFound("Habit not found".into()));
+    }
+    let date = match body.date {
+        Some(date) => date,
+        None => mood::local_today(&mut tx, auth_user.id).await?,
+    };
+
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO journal_entries (id, user_id, local_date_bucket, prompt_id, body, tags)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(id)
+    .bind(auth_user.id)
+    .bind(date)
+    .bind(&body.prompt_id)
+    .bind(&body.body)
+    .bind(&tags)
+    .execute(&mut *tx)
+    .await?;
+    journal::set_habits(&mut tx, id, &body.habit_ids).await?;
+    let entry = journal::find(&mut tx, auth_user.id, id)
+        .await?
+        .ok_or_else(|| {
+            AppError::Internal(anyhow::anyhow!("journal entry vanished after insert"))
+        })?;
+    tx.commit().await?;
+
+    Ok(Json(entry))
+}
+
+/// PUT /api/journal/:id
+pub async fn update_entry(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(entry_id): Path<Uuid>,
+    Json(body): Json<UpdateJournalEntryRequest>,
+) -> AppResult<Json<JournalEntry>> {
+    if let Some(text) = body.body.as_deref() {
+        validate_body(text).map_err(AppError::Validation)?;
+    }
+    let tags = body
+        .tags
+        .as_deref()
+        .map(normalize_tags)
+        .transpose()
+        .map_err(AppError::Validation)?;
+    let clear_prompt = body.clear_prompt.unwrap_or(false);
+    if !clear_prompt {
+        check_prompt(body.prompt_id.as_deref())?;
+    }
+
+    let mut tx = state.db.begin().await?;
+    let result = sqlx::query(
+        r#"
+        UPDATE journal_entries SET
+            local_date_bucket = COALESCE($3, local_date_bucket),
+            body = COALESCE($4, body),
+            tags = COALESCE($5, tags),
+            prompt_id = CASE WHEN $6 THEN NULL ELSE COALESCE($7, prompt_id) END
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(entry_id)
+    .bind(auth_user.id)
+    .bind(body.date)
+    .bind(&body.body)
+    .bind(&tags)
+    .bind(clear_prompt)
+    .bind(&body.prompt_id)
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Journal entry not found".into()));
+    }
+
+    if let Some(habit_ids) = &body.habit_ids {
+        if !journal::owns_habits(&mut tx, auth_user.id, habit_ids).await? {
+            return Err(AppError::NotFound("Habit not found".into()));
+        }
+        journal::set_habits(&mut tx, entry_id, habit_ids).await?;
+    }
+    let entry = journal::find(&mut tx, auth_user.id, entry_id)
+        .await?
+        .ok_or(AppError::NotFound("Journal entry not found".into()))?;
+    tx.commit().await?;
+
+    Ok(Json(entry))
+}
+
+/// DELETE /api/journal/:id
+pub async fn delete_entry(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(entry_id): Path<Uuid>,
+) -> AppResult<Json<serde_json::Value>> {
+    let result = sqlx::query("DELETE FROM journal_entries WHERE id = $1 AND user_id = $2")
+        .bind(entry_id)
+        .bind(auth_user.id)
+        .execute(&state.db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Journal entry not found".into()));
+    }
+    Ok(Json(serde_json::json!({ "deleted": true, "id": entry_id })))
+}
+
+fn check_prompt(prompt_id: Option<&str>) -> AppResult<()> {
+    match prompt_id {
+        Some(id) if journal::prompt(id).is_none() => {
+            Err(AppError::Validation(format!("Unknown prompt: {}", id)))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,254 @@
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use chrono::Duration;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::{AppError, AppResult};
+use crate::models::custom_metric::{
+    validate_definition, CreateMetricRequest, CustomMetric, MetricValue, MetricValuesQuery,
+    SetMetricValuesRequest, UpdateMetricRequest, MAX_METRICS,
+};
+use crate::services::history::{self, HistoryHeaders};
+use crate::services::{metrics, mood};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct MetricListQuery {
+    pub include_archived: Option<bool>,
+}
+
+/// GET /api/metrics
+pub async fn list_metrics(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<MetricListQuery>,
+) -> AppResult<Json<Vec<CustomMetric>>> {
+    let include_archived = query.include_archived.unwrap_or(false);
+    let list = metrics::list(
+        &mut *state.db.acquire().await?,
+        auth_user.id,
+        include_archived,
+    )
+    .await?;
+    Ok(Json(list))
+}
+
+/// POST /api/metrics
+pub async fn create_metric(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(body): Json<CreateMetricRequest>,
+) -> AppResult<Json<CustomMetric>> {
+    let name = body.name.trim();
+    validate_definition(
+        name,
+        body.kind,
+        body.unit.as_deref(),
+        body.min_value,
+        body.max_value,
+    )
+    .map_err(AppError::Validation)?;
+
+    let mut tx = state.db.begin().await?;
+    // Concurrent creates queue here, so the count below stays accurate
+    sqlx::query("SELECT 1 FROM users WHERE id = $1 FOR UPDATE")
+        .bind(auth_user.id)
+        .execute(&mut *tx)
+        .await?;
+    let count =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM custom_metrics WHERE user_id = $1")
+            .bind(auth_user.id)
+            .fetch_one(&mut *tx)
+            .await?;
+    if count >= MAX_METRICS {
+        return Err(AppError::Validation(format!(
+            "You can define up to {} metrics",
+            MAX_METRICS
+        )));
+    }
+    if metrics::name_taken(&mut tx, auth_user.id, name, None).await? {
+        return Err(AppError::Conflict(format!(
+            "A metric named {} already exists",
+            name
+        )));
+    }
+
+    let metric = sqlx::query_as::<_, CustomMetric>(
+        r#"
+        INSERT INTO custom_metrics (id, user_id, name, kind, unit, min_value, max_value, sort_order)
+        VALUES ($1, $2, $3, $4, $5, $6, $7,
+                (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM custom_metrics WHERE user_id = $2))
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(auth_user.id)
+    .bind(name)
+    .bind(body.kind)
+    .bind(&body.unit)
+    .bind(body.min_value)
+    .bind(body.max_value)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(metric))
+}
+
+/// PUT /api/metrics/:id
+pub async fn update_metric(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(metric_id): Path<Uuid>,
+    Json(body): Json<UpdateMetricRequest>,
+) -> AppResult<Json<CustomMetric>> {
+    let mut tx = state.db.begin().await?;
+    let existing = metrics::find(&mut tx, auth_user.id, metric_id)
+        .await?
+        .ok_or(AppError::NotFound("Metric not found".into()))?;
+
+    // Validate the definition as it will be after the partial update
+    let clear_unit = body.clear_unit.unwrap_or(false);
+    let clear_range = body.clear_range.unwrap_or(false);
+    let name = body
+        .name
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(&existing.name);
+    let unit = if clear_unit {
+        None
+    } else {
+        body.unit.as_deref().or(existing.unit.as_deref())
+    };
+    let (min_value, max_value) = if clear_range {
+        (None, None)
+    } else {
+        (
+            body.min_value.or(existing.min_value),
+            body.max_value.or(existing.max_value),
+        )
+    };
+    validate_definition(name, existing.kind, unit, min_value, max_value)
+        .map_err(AppError::Validation)?;
+    if name != existing.name
+        && metrics::name_taken(&mut tx, auth_user.id, name, Some(metric_id)).await?
+    {
+        return Err(AppError::Conflict(format!(
+            "A metric named {} already exists",
+            name
+        )));
+    }
+    // A narrower range must still hold every stored value
+    if existing.range_narrows(min_value, max_value) {
+        let outside = metrics::count_out_of_range(&mut tx, metric_id, min_value, max_value).await?;
+        if outside > 0 {
+            return Err(AppError::Validation(format!(
+                "{} stored value(s) of {} fall outside the new range; change or remove them first",
+                outside, name
+            )));
+        }
+    }
+
+    let metric = sqlx::query_as::<_, CustomMetric>(
+        r#"
+        UPDATE custom_metrics SET
+            name = $3,
+            unit = $4,
+            min_value = $5,
+            max_value = $6,
+            sort_order = COALESCE($7, sort_order),
+            is_archived = COALESCE($8, is_archived)
+        WHERE id = $1 AND user_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(metric_id)
+    .bind(auth_user.id)
+    .bind(name)
+    .bind(unit)
+    .bind(min_value)
+    .bind(max_value)
+    .bind(body.sort_order)
+    .bind(body.is_archived)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(metric))
+}
+
+/// DELETE /api/metrics/:id — removes the metric and all its values
+pub async fn delete_metric(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(metric_id): Path<Uuid>,
+) -> AppResult<Json<serde_json::Value>> {
+    let result = sqlx::query("DELETE FROM custom_metrics WHERE id = $1 AND user_id = $2")
+        .bind(metric_id)
+        .bind(auth_user.id)
+        .execute(&state.db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Metric not found".into()));
+    }
+    Ok(Json(
+        serde_json::json!({ "deleted": true, "id": metric_id }),
+    ))
+}
+
+/// PUT /api/metrics/values — set a day's values; `value: null` removes one
+pub async fn set_values(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(body): Json<SetMetricValuesRequest>,
+) -> AppResult<Json<Vec<MetricValue>>> {
+    let mut tx = state.db.begin().await?;
+    let date = match body.date {
+        Some(date) => date,
+        None => mood::local_today(&mut tx, auth_user.id).await?,
+    };
+    let defined = metrics::list(&mut tx, auth_user.id, true).await?;
+
+    let mut saved = Vec::new();
+    for input in &body.values {
+        let metric = defined
+            .iter()
+            .find(|m| m.id == input.metric_id)
+            .ok_or(AppError::NotFound("Metric not found".into()))?;
+        match input.value {
+            Some(value) => {
+                let value = metric.check_value(value).map_err(AppError::Validation)?;
+                saved
+                    .push(metrics::set_value(&mut tx, auth_user.id, metric.id, date, value).await?);
+            }
+            None => metrics::delete_value(&mut tx, auth_user.id, metric.id, date).await?,
+        }
+    }
+    tx.commit().await?;
+
+    Ok(Json(saved))
+}
+
+/// GET /api/metrics/values?start_date=&end_date= — default: the last 30 days
+pub async fn list_values(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<MetricValuesQuery>,
+) -> AppResult<(HistoryHeaders, Json<Vec<MetricValue>>)> {
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    let window = history::analytics_window(
+        &mut conn,
+        auth_user.id,
+        query.start_date.unwrap_or(today - Duration::days(30)),
+        query.end_date.unwrap_or(today),
+        today,
+    )
+    .await?;
+    let rows = metrics::values(&mut conn, auth_user.id, window.start, window.end).await?;
+    Ok((window.headers(), Json(rows)))
+}
@@ -1,8 +1,16 @@
 pub mod auth;
 pub mod habits;
 pub mod completions;
+pub mod calendar;
+pub mod checkins;
+pub mod timers;
 pub mod daily_logs;
+pub mod mood;
+pub mod metrics;
+pub mod journal;
 pub mod insights;
+pub mod reviews;
+pub mod search;
 pub mod billing;
 pub mod health;
 pub mod ws;
@@ -0,0 +1,189 @@
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::auth::middleware::AuthUser;
+use crate::dto::{MoodLogResponse, MoodQuery, MoodRequest};
+use crate::error::{AppError, AppResult};
+use crate::models::mood_log::{MoodLog, MoodValues};
+use crate::models::user::WeekStart;
+use crate::services::correlations::MoodMetric;
+use crate::services::history::{self, HistoryHeaders};
+use crate::services::mood_trend::{self, LoggingStreaks, SeriesLabel, SeriesTrend};
+use crate::services::schedule::week_start_for;
+use crate::services::{metrics, mood};
+use crate::AppState;
+
+/// Default span for the trend when `days` is omitted
+const TREND_DAYS: i64 = 30;
+
+#[derive(Debug, Deserialize)]
+pub struct TrendQuery {
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MoodTrend {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub days_logged: usize,
+    pub logging: LoggingStreaks,
+    /// mood, energy, stress, then active custom metrics
+    pub series: Vec<SeriesTrend>,
+}
+
+/// POST /api/mood — partial upsert of the day's log. Omitted fields keep
+/// their value; fields listed in `clear` are removed.
+pub async fn upsert(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(body): Json<MoodRequest>,
+) -> AppResult<Json<MoodLogResponse>> {
+    body.validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+    body.validate_at_least_one().map_err(AppError::Validation)?;
+
+    let mut tx = state.db.begin().await?;
+    let date = match body.date {
+        Some(date) => date,
+        None => mood::local_today(&mut tx, auth_user.id).await?,
+    };
+    let existing = mood::find(&mut tx, auth_user.id, date).await?;
+    let is_first = existing.is_none();
+    let values = MoodValues::merge(existing.as_ref(), body.values(), &body.clear)
+        .map_err(AppError::Validation)?;
+    let log = mood::save(&mut tx, auth_user.id, date, &values).await?;
+    tx.commit().await?;
+
+    if auth_user.is_demo && is_first {
+        track_first_mood_log(&state, &auth_user).await;
+    }
+
+    Ok(Json(log.into()))
+}
+
+/// GET /api/mood?range=7d — logs for the last 7/14/30/90 days, newest first
+pub async fn list(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<MoodQuery>,
+) -> AppResult<(HistoryHeaders, Json<Vec<MoodLogResponse>>)> {
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    let start = today - Duration::days(query.range_days() - 1);
+    let window = history::analytics_window(&mut conn, auth_user.id, start, today, today).await?;
+    let logs = mood::list(&mut conn, auth_user.id, window.start, window.end).await?;
+    Ok((
+        window.headers(),
+        Json(logs.into_iter().map(MoodLogResponse::from).collect()),
+    ))
+}
+
+/// GET /api/mood/trend?days=30 — rolling averages, weekday profile,
+/// volatility, logging streaks and this week against the baseline
+pub async fn trend(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<TrendQuery>,
+) -> AppResult<(HistoryHeaders, Json<MoodTrend>)> {
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    let days = query.days.unwrap_or(TREND_DAYS).clamp(1, 365);
+    let window = history::analytics_window(
+        &mut conn,
+        auth_user.id,
+        today - Duration::days(days - 1),
+        today,
+        today,
+    )
+    .await?;
+    let first = sqlx::query_scalar::<_, WeekStart>("SELECT week_start FROM users WHERE id = $1")
+        .bind(auth_user.id)
+        .fetch_one(&mut *conn)
+        .await?;
+    let week_start = week_start_for(today, first);
+
+    let logs = mood::list(&mut conn, auth_user.id, window.start, window.end).await?;
+    let custom = metrics::load_series(&mut conn, auth_user.id, window.start, window.end).await?;
+
+    let mut series: Vec<SeriesTrend> = MoodMetric::ALL
+        .iter()
+        .map(|metric| {
+            let values: BTreeMap<NaiveDate, f64> = logs
+                .iter()
+                .filter_map(|l| score(l, *metric).map(|v| (l.local_date_bucket, v as f64)))
+                .collect();
+            let label = SeriesLabel::named(metric.as_str());
+            mood_trend::series_trend(label, &values, window.start, window.end, week_start)
+        })
+        .collect();
+    series.extend(custom.into_iter().map(|s| {
+        let label = SeriesLabel {
+            metric: s.metric.name,
+            metric_id: Some(s.metric.id),
+            kind: Some(s.metric.kind),
+            unit: s.metric.unit,
+        };
+        mood_trend::series_trend(label, &s.values, window.start, window.end, week_start)
+    }));
+
+    let logged: BTreeSet<NaiveDate> = logs.iter().map(|l| l.local_date_bucket).collect();
+    Ok((
+        window.headers(),
+        Json(MoodTrend {
+            start_date: window.start,
+            end_date: window.end,
+            days_logged: logged.len(),
+            logging: mood_trend::logging_streaks(&logged, today),
+            series,
+        }),
+    ))
+}
+
+fn score(log: &MoodLog, metric: MoodMetric) -> Option<i16> {
+    match metric {
+        MoodMetric::Mood => log.mood,
+        MoodMetric::Energy => log.energy,
+        MoodMetric::Stress => log.stress,
+    }
+}
+
+/// DELETE /api/mood/:date — idempotent; `deleted` is false if there was no log
+pub async fn delete(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(date): Path<NaiveDate>,
+) -> AppResult<Json<serde_json::Value>> {
+    let mut tx = state.db.begin().await?;
+    let deleted = mood::delete(&mut tx, auth_user.id, date).await?;
+    tx.commit().await?;
+    Ok(Json(
+        serde_json::json!({ "deleted": deleted, "date": date }),
+    ))
+}
+
+/// Demo funnel event: first mood log (deduplicated)
+pub(crate) async fn track_first_mood_log(state: &AppState, auth_user: &AuthUser) {
+    let already = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM demo_events WHERE demo_user_id = $1 AND event_name = 'demo_first_mood_log'",
+    )
+    .bind(auth_user.id)
+    .fetch_one(&state.db)
+    .await
+    .unwrap_or(1);
+    if already == 0 {
+        let _ = crate::handlers::demo::track_demo_event(
+            &state.db,
+            auth_user.id,
+            "demo_first_mood_log",
+            None,
+        )
+        .await;
+    }
+}
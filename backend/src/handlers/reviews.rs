@@ -0,0 +1,377 @@
+use axum::{
+    extract::{Query, State},
+    Extension, Json,
+};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::{AppError, AppResult};
+use crate::models::habit::Habit;
+use crate::services::history::{self, HistoryHeaders, HistoryWindow};
+use crate::services::mood;
+use crate::services::reviews::{self, HabitInput};
+use crate::services::rollups;
+use crate::services::stats::{self, DoneDays, StreakRange};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct MonthlyReviewQuery {
+    /// "YYYY-MM". Default: last complete month.
+    pub month: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct YearlyReviewQuery {
+    /// "YYYY". Default: last complete year.
+    pub year: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeriodReview {
+    /// "month" or "year"
+    pub kind: String,
+    /// "2026-01" for months, "2026" for years
+    pub period: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// False while the period is still running (such reviews aren't cached)
+    pub is_final: bool,
+    pub total_completions: i64,
+    pub total_possible: i64,
+    pub completion_rate: f64,
+    pub most_consistent_weekday: Option<String>,
+    pub habits: Vec<PeriodHabitReview>,
+    pub mood: MoodSummary,
+    /// Month-by-month breakdown (yearly reviews only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub months: Vec<MonthTrend>,
+    pub comparison: PeriodComparison,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeriodHabitReview {
+    pub id: Uuid,
+    pub name: String,
+    pub color: String,
+    pub completed: i64,
+    pub possible: i64,
+    pub rate: f64,
+    /// Longest streak that fell inside the period
+    pub longest_streak: Option<StreakRange>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MoodSummary {
+    pub days_logged: i64,
+    pub avg_mood: Option<f64>,
+    pub avg_energy: Option<f64>,
+    pub avg_stress: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MonthTrend {
+    pub month: String,
+    pub total_completions: i64,
+    pub completion_rate: f64,
+    /// Change from the previous month; None for the first month
+    pub rate_change: Option<f64>,
+    pub avg_mood: Option<f64>,
+}
+
+/// The preceding month or year, for period-over-period deltas
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeriodComparison {
+    pub period: String,
+    pub total_completions: i64,
+    pub completion_rate: f64,
+    pub rate_change: f64,
+    pub avg_mood: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PeriodKind {
+    Month,
+    Year,
+}
+
+impl PeriodKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            PeriodKind::Month => "month",
+            PeriodKind::Year => "year",
+        }
+    }
+
+    fn label(self, start: NaiveDate) -> String {
+        match self {
+            PeriodKind::Month => start.format("%Y-%m").to_string(),
+            PeriodKind::Year => start.format("%Y").to_string(),
+        }
+    }
+
+    /// The period immediately before the one starting at `start`
+    fn previous(self, start: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let end = start.pred_opt().unwrap_or(start);
+        match self {
+            PeriodKind::Month => (end.with_day0(0).unwrap_or(end), end),
+            PeriodKind::Year => (end.with_ordinal(1).unwrap_or(end), end),
+        }
+    }
+}
+
+/// GET /api/stats/monthly-review?month=YYYY-MM
+pub async fn get_monthly_review(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<MonthlyReviewQuery>,
+) -> AppResult<(HistoryHeaders, Json<PeriodReview>)> {
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let (start, end) = match query.month.as_deref() {
+        Some(label) => reviews::parse_month(label)
+            .ok_or_else(|| AppError::Validation("month must look like 2026-01".into()))?,
+        None => PeriodKind::Month.previous(today.with_day(1).unwrap_or(today)),
+    };
+    review_period(&state, auth_user.id, PeriodKind::Month, start, end, today).await
+}
+
+/// GET /api/stats/yearly-review?year=YYYY — "Year in Review"
+pub async fn get_yearly_review(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<YearlyReviewQuery>,
+) -> AppResult<(HistoryHeaders, Json<PeriodReview>)> {
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+    let (start, end) = match query.year.as_deref() {
+        Some(label) => reviews::parse_year(label)
+            .ok_or_else(|| AppError::Validation("year must look like 2026".into()))?,
+        None => PeriodKind::Year.previous(today.with_ordinal(1).unwrap_or(today)),
+    };
+    review_period(&state, auth_user.id, PeriodKind::Year, start, end, today).await
+}
+
+async fn review_period(
+    state: &AppState,
+    user_id: Uuid,
+    kind: PeriodKind,
+    start: NaiveDate,
+    end: NaiveDate,
+    today: NaiveDate,
+) -> AppResult<(HistoryHeaders, Json<PeriodReview>)> {
+    if start > today {
+        return Err(AppError::Validation(format!(
+            "{} is in the future",
+            kind.as_str()
+        )));
+    }
+    let is_final = end < today;
+
+    // Both periods are judged only over days the tier's history allows;
+    // a running period only up to today
+    let mut conn = state.db.acquire().await?;
+    let window = history::analytics_window(&mut conn, user_id, start, end, today).await?;
+    let (prev_start, prev_end) = kind.previous(start);
+    let prev = HistoryWindow::clamp(prev_start, prev_end, today, window.limit_days);
+    let (from, through) = (window.start, window.end);
+    let (prev_from, prev_through) = (prev.start, prev.end);
+
+    // Only complete, untruncated reviews are cached, so a tier change
+    // never serves a recap cut to another tier's history
+    let cacheable = is_final && window.truncated_days == 0 && prev.truncated_days == 0;
+    if cacheable {
+        if let Some(payload) =
+            reviews::cached_review(&mut conn, user_id, kind.as_str(), start).await?
+        {
+            // A payload from an older shape falls through to regeneration
+            if let Ok(review) = serde_json::from_value::<PeriodReview>(payload) {
+                return Ok((window.headers(), Json(review)));
+            }
+        }
+    }
+
+    // Archived habits still belong in a recap if they were done in the period
+    let habits = sqlx::query_as::<_, Habit>(
+        r#"
+        SELECT * FROM habits h
+        WHERE h.user_id = $1
+          AND (h.is_archived = false OR EXISTS (
+              SELECT 1 FROM habit_completions c
+              WHERE c.habit_id = h.id AND c.local_date_bucket BETWEEN $2 AND $3
+          ))
+        ORDER BY h.sort_order, h.created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(through)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let rows = sqlx::query_as::<_, (Uuid, NaiveDate, i32)>(
+        r#"
+        SELECT habit_id, local_date_bucket, value FROM habit_completions
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(prev_from.min(from))
+    .bind(through)
+    .fetch_all(&mut *conn)
+    .await?;
+    let done = reviews::done_by_habit(rows);
+    let empty = DoneDays::new();
+    let inputs: Vec<HabitInput> = habits
+        .iter()
+        .map(|h| HabitInput::new(h, done.get(&h.id).unwrap_or(&empty)))
+        .collect();
+
+    let week_start = sqlx::query_scalar::<_, crate::models::user::WeekStart>(
+        "SELECT week_start FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let totals = reviews::period_totals(&inputs, from, through, week_start);
+
+    // Period totals come from the daily rollups
+    let days = rollups::read_range(&mut conn, user_id, prev_from.min(from), through).await?;
+    let in_range = |start: NaiveDate, end: NaiveDate| {
+        rollups::totals(
+            days.iter()
+                .filter(move |r| r.date >= start && r.date <= end),
+        )
+    };
+    let (current, previous) = (in_range(from, through), in_range(prev_from, prev_through));
+
+    let habit_reviews = totals
+        .habits
+        .iter()
+        .filter_map(|t| {
+            let input = inputs.iter().find(|i| i.habit.id == t.habit_id)?;
+            Some(PeriodHabitReview {
+                id: input.habit.id,
+                name: input.habit.name.clone(),
+                color: input.habit.color.clone(),
+                completed: t.completed,
+                possible: t.possible.round() as i64,
+                rate: t.rate,
+                longest_streak: reviews::longest_streak_in(input, from, through),
+            })
+        })
+        .collect();
+
+    let (best_weekday, _) = stats::best_and_worst(&reviews::weekday_rates(&inputs, from, through));
+
+    let mood = mood_summary(&mut conn, user_id, from, through).await?;
+    let prev_mood = mood_summary(&mut conn, user_id, prev_from, prev_through).await?;
+
+    let months = if kind == PeriodKind::Year {
+        let mut trends: Vec<MonthTrend> = Vec::new();
+        for month in reviews::months_in(from, through) {
+            let month = month.max(from);
+            let month_end = reviews::month_end(month).min(through);
+            let t = in_range(month, month_end);
+            let m = mood_summary(&mut conn, user_id, month, month_end).await?;
+            trends.push(MonthTrend {
+                month: PeriodKind::Month.label(month),
+                total_completions: t.completed,
+                completion_rate: t.rate,
+                rate_change: trends.last().map(|prev| t.rate - prev.completion_rate),
+                avg_mood: m.avg_mood,
+            });
+        }
+        trends
+    } else {
+        Vec::new()
+    };
+
+    let review = PeriodReview {
+        kind: kind.as_str().to_string(),
+        period: kind.label(start),
+        start,
+        end,
+        is_final,
+        total_completions: current.completed,
+        total_possible: current.due,
+        completion_rate: current.rate,
+        most_consistent_weekday: best_weekday.map(|i| stats::WEEKDAY_NAMES[i].to_string()),
+        habits: habit_reviews,
+        mood,
+        months,
+        comparison: PeriodComparison {
+            period: kind.label(prev_start),
+            total_completions: previous.completed,
+            completion_rate: previous.rate,
+            rate_change: current.rate - previous.rate,
+            avg_mood: prev_mood.avg_mood,
+        },
+        generated_at: Utc::now(),
+    };
+
+    if cacheable {
+        let payload = serde_json::to_value(&review)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to cache review: {}", e)))?;
+        reviews::store_review(&mut conn, user_id, kind.as_str(), start, end, &payload).await?;
+    }
+
+    Ok((window.headers(), Json(review)))
+}
+
+async fn mood_summary(
+    conn: &mut sqlx::PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> AppResult<MoodSummary> {
+    let summary = sqlx::query_as::<_, MoodSummary>(
+        r#"
+        SELECT
+            COUNT(*) AS days_logged,
+            AVG(mood)::float8 AS avg_mood,
+            AVG(energy)::float8 AS avg_energy,
+            AVG(stress)::float8 AS avg_stress
+        FROM mood_logs
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_one(conn)
+    .await?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_previous_period() {
+        assert_eq!(
+            PeriodKind::Month.previous(d(2026, 3, 1)),
+            (d(2026, 2, 1), d(2026, 2, 28))
+        );
+        assert_eq!(
+            PeriodKind::Month.previous(d(2026, 1, 1)),
+            (d(2025, 12, 1), d(2025, 12, 31))
+        );
+        assert_eq!(
+            PeriodKind::Year.previous(d(2026, 1, 1)),
+            (d(2025, 1, 1), d(2025, 12, 31))
+        );
+    }
+
+    #[test]
+    fn test_period_labels() {
+        assert_eq!(PeriodKind::Month.label(d(2026, 3, 1)), "2026-03");
+        assert_eq!(PeriodKind::Year.label(d(2026, 1, 1)), "2026");
+    }
+}
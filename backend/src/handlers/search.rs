@@ -0,0 +1,279 @@
+use axum::{
+    extract::{Query, State},
+    Extension, Json,
+};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection};
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::{AppError, AppResult};
+use crate::services::history::{self, HistoryHeaders, HistoryWindow};
+use crate::services::mood;
+use crate::AppState;
+
+const DEFAULT_PER_PAGE: i64 = 20;
+const MAX_PER_PAGE: i64 = 50;
+const MAX_QUERY_CHARS: usize = 200;
+/// ts_headline wraps matches in these; `highlight` turns them into <mark>
+/// after escaping the note, so user text never reaches the client as HTML
+const MATCH_START: char = '\u{2}';
+const MATCH_END: char = '\u{3}';
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+    /// Restrict to one habit's check-in notes (daily logs are excluded)
+    pub habit_id: Option<Uuid>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct SearchHit {
+    /// "checkin" or "daily_log"
+    pub kind: String,
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub habit_id: Option<Uuid>,
+    pub habit_name: Option<String>,
+    pub habit_color: Option<String>,
+    /// HTML-escaped matching fragments with terms wrapped in <mark>…</mark>
+    pub snippet: String,
+    pub rank: f32,
+    #[serde(skip)]
+    pub total: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    pub query: String,
+    pub results: Vec<SearchHit>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+}
+
+/// GET /api/search?q= — full-text search across check-in and daily log notes
+pub async fn search_notes(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<SearchQuery>,
+) -> AppResult<(HistoryHeaders, Json<SearchResponse>)> {
+    let q = query.q.as_deref().map(str::trim).unwrap_or_default();
+    if q.is_empty() {
+        return Err(AppError::Validation("q is required".into()));
+    }
+    if q.chars().count() > MAX_QUERY_CHARS {
+        return Err(AppError::Validation(format!(
+            "q must be at most {} characters",
+            MAX_QUERY_CHARS
+        )));
+    }
+    if let (Some(start), Some(end)) = (query.start_date, query.end_date) {
+        if start > end {
+            return Err(AppError::Validation(
+                "start_date must be on or before end_date".into(),
+            ));
+        }
+    }
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query
+        .per_page
+        .unwrap_or(DEFAULT_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE);
+
+    // Notes are history too: only the tier's analytics window is searched
+    let mut conn = state.db.acquire().await?;
+    let today = mood::local_today(&mut conn, auth_user.id).await?;
+    let limit_days = history::entitlements(&mut conn, auth_user.id)
+        .await?
+        .analytics_days as i64;
+    let window = HistoryWindow::clamp(
+        query
+            .start_date
+            .unwrap_or(today - Duration::days(limit_days - 1)),
+        query.end_date.unwrap_or(today),
+        today,
+        limit_days,
+    );
+    if window.is_empty() {
+        return Ok((
+            window.headers(),
+            Json(SearchResponse {
+                query: q.to_string(),
+                results: Vec::new(),
+                page,
+                per_page,
+                total: 0,
+            }),
+        ));
+    }
+
+    // websearch_to_tsquery accepts free user input ("quoted phrases", -exclusions)
+    // without raising syntax errors. The habit filter excludes daily logs,
+    // which aren't tied to a habit.
+    let results = sqlx::query_as::<_, SearchHit>(
+        r#"
+        WITH q AS (SELECT websearch_to_tsquery('english', $2) AS query),
+        hits AS (
+            SELECT
+                'checkin' AS kind,
+                c.id,
+                c.local_date_bucket AS date,
+                c.habit_id,
+                h.name AS habit_name,
+                h.color AS habit_color,
+                c.note AS body,
+                ts_rank(c.note_tsv, q.query) AS rank
+            FROM habit_checkins c
+            JOIN habits h ON h.id = c.habit_id
+            CROSS JOIN q
+            WHERE c.user_id = $1
+              AND c.note_tsv @@ q.query
+              AND ($3::uuid IS NULL OR c.habit_id = $3)
+              AND c.local_date_bucket BETWEEN $4 AND $5
+            UNION ALL
+            SELECT
+                'daily_log' AS kind,
+                m.id,
+                m.local_date_bucket AS date,
+                NULL::uuid AS habit_id,
+                NULL::text AS habit_name,
+                NULL::text AS habit_color,
+                m.note AS body,
+                ts_rank(m.note_tsv, q.query) AS rank
+            FROM mood_logs m
+            CROSS JOIN q
+            WHERE m.user_id = $1
+              AND m.note_tsv @@ q.query
+              AND $3::uuid IS NULL
+              AND m.local_date_bucket BETWEEN $4 AND $5
+        )
+        SELECT
+            hits.kind,
+            hits.id,
+            hits.date,
+            hits.habit_id,
+            hits.habit_name,
+            hits.habit_color,
+            ts_headline('english', hits.body, q.query, $8) AS snippet,
+            hits.rank,
+            COUNT(*) OVER () AS total
+        FROM hits
+        CROSS JOIN q
+        ORDER BY hits.rank DESC, hits.date DESC, hits.id
+        LIMIT $6 OFFSET $7
+        "#,
+    )
+    .bind(auth_user.id)
+    .bind(q)
+    .bind(query.habit_id)
+    .bind(window.start)
+    .bind(window.end)
+    .bind(per_page)
+    .bind((page - 1) * per_page)
+    .bind(format!(
+        "StartSel={}, StopSel={}, MaxFragments=2, MaxWords=20, MinWords=5",
+        MATCH_START, MATCH_END
+    ))
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|hit| SearchHit {
+        snippet: highlight(&hit.snippet),
+        ..hit
+    })
+    .collect::<Vec<_>>();
+
+    // The window count rides on every row; past the last page there are none
+    let total = match results.first() {
+        Some(hit) => hit.total,
+        None if page > 1 => count_hits(&mut conn, auth_user.id, q, query.habit_id, &window).await?,
+        None => 0,
+    };
+
+    Ok((
+        window.headers(),
+        Json(SearchResponse {
+            query: q.to_string(),
+            results,
+            page,
+            per_page,
+            total,
+        }),
+    ))
+}
+
+async fn count_hits(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    q: &str,
+    habit_id: Option<Uuid>,
+    window: &HistoryWindow,
+) -> AppResult<i64> {
+    let total = sqlx::query_scalar::<_, i64>(
+        r#"
+        WITH q AS (SELECT websearch_to_tsquery('english', $2) AS query)
+        SELECT
+            (SELECT COUNT(*) FROM habit_checkins c, q
+             WHERE c.user_id = $1 AND c.note_tsv @@ q.query
+               AND ($3::uuid IS NULL OR c.habit_id = $3)
+               AND c.local_date_bucket BETWEEN $4 AND $5)
+            +
+            (SELECT COUNT(*) FROM mood_logs m, q
+             WHERE m.user_id = $1 AND m.note_tsv @@ q.query
+               AND $3::uuid IS NULL
+               AND m.local_date_bucket BETWEEN $4 AND $5)
+        "#,
+    )
+    .bind(user_id)
+    .bind(q)
+    .bind(habit_id)
+    .bind(window.start)
+    .bind(window.end)
+    .fetch_one(conn)
+    .await?;
+
+    Ok(total)
+}
+
+/// Escape a ts_headline fragment for HTML and turn the match markers into
+/// <mark> tags.
+fn highlight(snippet: &str) -> String {
+    let mut out = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            MATCH_START => out.push_str("<mark>"),
+            MATCH_END => out.push_str("</mark>"),
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_escapes_user_text() {
+        let snippet = format!(
+            "<img src=x onerror=alert(1)> {}run{} & \"rest\"",
+            MATCH_START, MATCH_END
+        );
+        assert_eq!(
+            highlight(&snippet),
+            "&lt;img src=x onerror=alert(1)&gt; <mark>run</mark> &amp; &quot;rest&quot;"
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::{AppError, AppResult};
+use crate::handlers::completions::update_streak;
+use crate::handlers::habits::fetch_owned_habit;
+use crate::models::timer::{TimerSession, TimerSessionResponse, TimerStopResponse};
+use crate::services::checkins;
+use crate::services::mood;
+use crate::AppState;
+
+/// GET /api/timers — all open (running or paused) timers for the user
+pub async fn list_active_timers(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> AppResult<Json<Vec<TimerSessionResponse>>> {
+    let sessions = sqlx::query_as::<_, TimerSession>(
+        r#"
+        SELECT * FROM habit_timer_sessions
+        WHERE user_id = $1 AND status <> 'stopped'
+        ORDER BY started_at ASC
+        "#,
+    )
+    .bind(auth_user.id)
+    .fetch_all(&state.db)
+    .await?;
+
+    let now = Utc::now();
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|session| with_elapsed(session, now))
+            .collect(),
+    ))
+}
+
+/// GET /api/habits/:id/timer — the habit's open timer, or null
+pub async fn get_timer(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+) -> AppResult<Json<Option<TimerSessionResponse>>> {
+    fetch_owned_habit(&state, habit_id, auth_user.id).await?;
+
+    let session = sqlx::query_as::<_, TimerSession>(
+        "SELECT * FROM habit_timer_sessions WHERE habit_id = $1 AND status <> 'stopped'",
+    )
+    .bind(habit_id)
+    .fetch_optional(&state.db)
+    .await?;
+
+    Ok(Json(session.map(|s| with_elapsed(s, Utc::now()))))
+}
+
+/// POST /api/habits/:id/timer/start
+pub async fn start_timer(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+) -> AppResult<Json<TimerSessionResponse>> {
+    fetch_owned_habit(&state, habit_id, auth_user.id).await?;
+    let today = mood::local_today(&mut *state.db.acquire().await?, auth_user.id).await?;
+
+    // Partial unique index allows one open timer per habit
+    let session = sqlx::query_as::<_, TimerSession>(
+        r#"
+        INSERT INTO habit_timer_sessions (id, habit_id, user_id, status, local_date_bucket, started_at, resumed_at)
+        VALUES ($1, $2, $3, 'running', $4, NOW(), NOW())
+        ON CONFLICT (habit_id) WHERE status <> 'stopped' DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(habit_id)
+    .bind(auth_user.id)
+    .bind(today)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict("A timer is already active for this habit".into()))?;
+
+    broadcast_timer(&state, &session);
+    Ok(Json(with_elapsed(session, Utc::now())))
+}
+
+/// POST /api/habits/:id/timer/pause — closes the open segment
+pub async fn pause_timer(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+) -> AppResult<Json<TimerSessionResponse>> {
+    let session = sqlx::query_as::<_, TimerSession>(
+        r#"
+        UPDATE habit_timer_sessions SET
+            status = 'paused',
+            accumulated_secs = accumulated_secs
+                + GREATEST(FLOOR(EXTRACT(EPOCH FROM NOW() - resumed_at)), 0)::bigint,
+            resumed_at = NULL
+        WHERE habit_id = $1 AND user_id = $2 AND status = 'running'
+        RETURNING *
+        "#,
+    )
+    .bind(habit_id)
+    .bind(auth_user.id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict("No running timer for this habit".into()))?;
+
+    broadcast_timer(&state, &session);
+    Ok(Json(with_elapsed(session, Utc::now())))
+}
+
+/// POST /api/habits/:id/timer/resume — opens a new segment
+pub async fn resume_timer(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+) -> AppResult<Json<TimerSessionResponse>> {
+    let session = sqlx::query_as::<_, TimerSession>(
+        r#"
+        UPDATE habit_timer_sessions SET
+            status = 'running',
+            resumed_at = NOW()
+        WHERE habit_id = $1 AND user_id = $2 AND status = 'paused'
+        RETURNING *
+        "#,
+    )
+    .bind(habit_id)
+    .bind(auth_user.id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict("No paused timer for this habit".into()))?;
+
+    broadcast_timer(&state, &session);
+    Ok(Json(with_elapsed(session, Utc::now())))
+}
+
+/// POST /api/habits/:id/timer/stop — credits elapsed minutes to the day
+pub async fn stop_timer(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(habit_id): Path<Uuid>,
+) -> AppResult<Json<TimerStopResponse>> {
+    let habit = fetch_owned_habit(&state, habit_id, auth_user.id).await?;
+
+    let mut tx = state.db.begin().await?;
+    checkins::lock_habit(&mut tx, habit_id).await?;
+    let mut session = sqlx::query_as::<_, TimerSession>(
+        r#"
+        UPDATE habit_timer_sessions SET
+            accumulated_secs = accumulated_secs + CASE
+                WHEN status = 'running'
+                    THEN GREATEST(FLOOR(EXTRACT(EPOCH FROM NOW() - resumed_at)), 0)::bigint
+                ELSE 0
+            END,
+            status = 'stopped',
+            resumed_at = NULL,
+            stopped_at = NOW()
+        WHERE habit_id = $1 AND user_id = $2 AND status <> 'stopped'
+        RETURNING *
+        "#,
+    )
+    .bind(habit_id)
+    .bind(auth_user.id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Conflict("No active timer for this habit".into()))?;
+
+    let stopped_at = session.stopped_at.unwrap_or_else(Utc::now);
+    let minutes = session.credited_minutes(stopped_at);
+
+    // Under a minute is recorded on the session but not credited
+    let completion = if minutes > 0 {
+        // Windowed habits judge the session by when it started
+        let is_late =
+            checkins::is_late(&mut tx, &habit, session.completed_date, session.started_at).await?;
+        let checkin = checkins::record_checkin(
+            &mut tx,
+            checkins::NewCheckin {
+                habit_id,
+                user_id: auth_user.id,
+                date: session.completed_date,
+                checked_in_at: stopped_at,
+                value: minutes,
+                note: None,
+                is_late,
+            },
+        )
+        .await?;
+        session = sqlx::query_as::<_, TimerSession>(
+            "UPDATE habit_timer_sessions SET checkin_id = $2 WHERE id = $1 RETURNING *",
+        )
+        .bind(session.id)
+        .bind(checkin.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        checkins::rollup_day(&mut tx, habit_id, auth_user.id, session.completed_date)
+            .await?
+            .0
+    } else {
+        sqlx::query_as::<_, crate::models::completion::Completion>(
+            "SELECT * FROM habit_completions WHERE habit_id = $1 AND local_date_bucket = $2",
+        )
+        .bind(habit_id)
+        .bind(session.completed_date)
+        .fetch_optional(&mut *tx)
+        .await?
+    };
+    tx.commit().await?;
+
+    if minutes > 0 {
+        update_streak(&state, habit_id).await?;
+        if let Some(tx) = state.ws_tx.as_ref() {
+            let msg = serde_json::json!({
+                "type": "completion_changed",
+                "user_id": auth_user.id,
+                "habit_id": habit_id,
+                "checkin_id": session.checkin_id,
+            });
+            let _ = tx.send(msg.to_string());
+        }
+    }
+    broadcast_timer(&state, &session);
+
+    let is_complete = completion
+        .as_ref()
+        .is_some_and(|c| c.value >= habit.target_per_day);
+
+    Ok(Json(TimerStopResponse {
+        elapsed_secs: session.elapsed_secs(stopped_at),
+        session,
+        minutes_credited: minutes,
+        completion,
+        target_per_day: habit.target_per_day,
+        is_complete,
+    }))
+}
+
+fn with_elapsed(session: TimerSession, now: chrono::DateTime<Utc>) -> TimerSessionResponse {
+    TimerSessionResponse {
+        elapsed_secs: session.elapsed_secs(now),
+        session,
+    }
+}
+
+/// Sync timer state to the user's other devices
+fn broadcast_timer(state: &AppState, session: &TimerSession) {
+    if let Some(tx) = state.ws_tx.as_ref() {
+        let msg = serde_json::json!({
+            "type": "timer_changed",
+            "user_id": session.user_id,
+            "habit_id": session.habit_id,
+            "session": session,
+            "elapsed_secs": session.elapsed_secs(Utc::now()),
+        });
+        let _ = tx.send(msg.to_string());
+    }
+}
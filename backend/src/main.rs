@@ -12,6 +12,7 @@ use tower_http::trace::TraceLayer;
 mod auth;
 mod config;
 mod db;
+mod dto;
 mod error;
 mod handlers;
 mod models;
@@ -26,6 +27,7 @@ pub struct AppState {
     pub config: Arc<Config>,
     pub ws_tx: Option<broadcast::Sender<String>>,
     pub rate_limiter: RateLimitState,
+    pub insight_provider: Arc<dyn services::llm::InsightProvider>,
 }
 
 #[tokio::main]
@@ -60,11 +62,16 @@ async fn main() {
 
     let rate_limiter = RateLimitState::new();
 
+    let insight_provider =
+        services::llm::from_config(&config).expect("Failed to configure the insight model provider");
+    tracing::info!(provider = insight_provider.name(), "Insight model provider configured");
+
     let state = AppState {
         db,
         config: config.clone(),
         ws_tx: Some(ws_tx),
         rate_limiter,
+        insight_provider,
     };
 
     // Build routes
@@ -100,9 +107,11 @@ async fn main() {
 
     let protected_routes = Router::new()
         .route("/api/me", get(handlers::auth::me))
+        .route("/api/me/preferences", put(handlers::auth::update_preferences))
         // Habits
         .route("/api/habits", get(handlers::habits::list_habits))
         .route("/api/habits", post(handlers::habits::create_habit))
+        .route("/api/habits/today", get(handlers::habits::today_view))
         .route("/api/habits/:id", get(handlers::habits::get_habit))
         .route("/api/habits/:id", put(handlers::habits::update_habit))
         .route("/api/habits/:id", delete(handlers::habits::delete_habit))
@@ -123,22 +132,99 @@ async fn main() {
             "/api/completions/toggle",
             post(handlers::completions::toggle_completion),
         )
+        // Check-ins (event log behind the daily completion rollup)
+        .route(
+            "/api/habits/:id/checkins",
+            post(handlers::checkins::create_checkin),
+        )
+        .route(
+            "/api/habits/:id/checkins",
+            get(handlers::checkins::list_checkins),
+        )
+        .route(
+            "/api/habits/:id/checkins/latest",
+            delete(handlers::checkins::undo_checkin),
+        )
+        .route(
+            "/api/habits/:id/time-of-day",
+            get(handlers::checkins::get_time_of_day),
+        )
+        // Timer sessions (duration habits)
+        .route("/api/timers", get(handlers::timers::list_active_timers))
+        .route("/api/habits/:id/timer", get(handlers::timers::get_timer))
+        .route(
+            "/api/habits/:id/timer/start",
+            post(handlers::timers::start_timer),
+        )
+        .route(
+            "/api/habits/:id/timer/pause",
+            post(handlers::timers::pause_timer),
+        )
+        .route(
+            "/api/habits/:id/timer/resume",
+            post(handlers::timers::resume_timer),
+        )
+        .route(
+            "/api/habits/:id/timer/stop",
+            post(handlers::timers::stop_timer),
+        )
         // Stats & Streaks
         .route(
             "/api/habits/:id/streak",
             get(handlers::completions::get_streak),
         )
+        .route(
+            "/api/habits/:id/stats",
+            get(handlers::completions::get_habit_stats),
+        )
         .route(
             "/api/habits/:id/heatmap",
             get(handlers::completions::get_heatmap),
         )
         .route("/api/stats/daily", get(handlers::completions::get_daily_stats))
+        .route(
+            "/api/habits/:id/calendar",
+            get(handlers::calendar::get_habit_calendar),
+        )
+        .route("/api/calendar", get(handlers::calendar::get_calendar))
+        .route("/api/stats/heatmap", get(handlers::completions::get_aggregate_heatmap))
+        .route("/api/stats/score", get(handlers::completions::get_score_trend))
         .route("/api/stats/weekly-review", get(handlers::completions::get_weekly_review))
-        // Daily Logs
+        .route("/api/stats/monthly-review", get(handlers::reviews::get_monthly_review))
+        .route("/api/stats/yearly-review", get(handlers::reviews::get_yearly_review))
+        // Mood
+        .route("/api/mood", post(handlers::mood::upsert))
+        .route("/api/mood", get(handlers::mood::list))
+        .route("/api/mood/trend", get(handlers::mood::trend))
+        .route("/api/mood/:date", delete(handlers::mood::delete))
+        // Daily Logs (legacy alias over mood_logs)
         .route("/api/daily-logs", post(handlers::daily_logs::upsert_daily_log))
         .route("/api/daily-logs", get(handlers::daily_logs::list_daily_logs))
+        // Journal
+        .route("/api/journal", get(handlers::journal::list_entries))
+        .route("/api/journal", post(handlers::journal::create_entry))
+        .route("/api/journal/prompts", get(handlers::journal::get_prompts))
+        .route("/api/journal/:id", put(handlers::journal::update_entry))
+        .route("/api/journal/:id", delete(handlers::journal::delete_entry))
+        // Custom metrics
+        .route("/api/metrics", get(handlers::metrics::list_metrics))
+        .route("/api/metrics", post(handlers::metrics::create_metric))
+        .route("/api/metrics/values", get(handlers::metrics::list_values))
+        .route("/api/metrics/values", put(handlers::metrics::set_values))
+        .route("/api/metrics/:id", put(handlers::metrics::update_metric))
+        .route("/api/metrics/:id", delete(handlers::metrics::delete_metric))
+        // Search
+        .route("/api/search", get(handlers::search::search_notes))
         // Insights
         .route("/api/insights", get(handlers::insights::get_insights))
+        .route("/api/insights/generate", post(handlers::insights::get_insights))
+        .route("/api/insights/regenerate", post(handlers::insights::regenerate_insights))
+        .route("/api/insights/latest", get(handlers::insights::get_latest_insight))
+        .route("/api/insights/history", get(handlers::insights::get_insight_history))
+        .route(
+            "/api/insights/correlations",
+            get(handlers::insights::get_correlations),
+        )
         // Billing
         .route(
             "/api/billing/subscription",
@@ -188,10 +274,21 @@ async fn main() {
             axum::http::header::CONTENT_TYPE,
             axum::http::header::ACCEPT,
         ])
+        // Tier truncation metadata on history reads
+        .expose_headers([
+            axum::http::HeaderName::from_static(services::history::LIMIT_DAYS_HEADER),
+            axum::http::HeaderName::from_static(services::history::START_HEADER),
+            axum::http::HeaderName::from_static(services::history::TRUNCATED_DAYS_HEADER),
+        ])
         .allow_credentials(true);
 
     // Start demo cleanup worker (purges expired demo sessions every 5 min)
     handlers::demo::spawn_demo_cleanup_worker(state.db.clone());
+    // Enqueue "streak at risk" nudges at each user's local evening
+    services::notifications::spawn_streak_risk_worker(state.db.clone());
+    services::rollups::spawn_rollup_worker(state.db.clone());
+    // Generate weekly insights on Monday morning in each user's timezone
+    handlers::insights::spawn_weekly_insight_worker(state.clone());
 
     let app = Router::new()
         .merge(public_routes)
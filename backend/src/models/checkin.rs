@@ -0,0 +1,59 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::completion::Completion;
+
+/// A single timestamped check-in. The day's `habit_completions` row is the
+/// rollup (sum of `value`) of these events.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Checkin {
+    pub id: Uuid,
+    pub habit_id: Uuid,
+    pub user_id: Uuid,
+    #[sqlx(rename = "local_date_bucket")]
+    pub completed_date: NaiveDate,
+    pub checked_in_at: DateTime<Utc>,
+    pub value: i32,
+    pub note: Option<String>,
+    pub is_late: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCheckinRequest {
+    pub completed_date: Option<NaiveDate>,
+    pub checked_in_at: Option<DateTime<Utc>>,
+    pub value: Option<i32>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckinQuery {
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimeOfDayQuery {
+    pub days: Option<i64>,
+}
+
+/// Result of a check-in write: the event plus the refreshed daily rollup
+/// (`None` once the last check-in of the day is undone).
+#[derive(Debug, Serialize)]
+pub struct CheckinResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkin: Option<Checkin>,
+    pub completion: Option<Completion>,
+    pub checkins_today: i64,
+    pub target_per_day: i32,
+    pub is_complete: bool,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct HourBucket {
+    pub hour: i32,
+    pub checkins: i64,
+    pub value: i64,
+}
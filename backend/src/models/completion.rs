@@ -12,6 +12,7 @@ pub struct Completion {
     pub completed_date: NaiveDate,
     pub value: i32,
     pub note: Option<String>,
+    pub is_late: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -45,4 +46,6 @@ pub struct DailyStats {
     pub total_habits: i64,
     pub completed_habits: i64,
     pub completion_rate: f64,
+    /// Completion weighted by habit priority × difficulty, 0–100
+    pub score: Option<f64>,
 }
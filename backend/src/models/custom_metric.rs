@@ -0,0 +1,243 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Metrics a user can define (archived ones included)
+pub const MAX_METRICS: i64 = 20;
+pub const MAX_NAME_LEN: usize = 50;
+pub const MAX_UNIT_LEN: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MetricKind {
+    /// Whole numbers within a required range, e.g. pain 0–10
+    Scale,
+    /// Any number, optionally bounded, e.g. sleep hours or weight
+    Number,
+    /// Yes/no, stored as 1/0
+    Boolean,
+}
+
+/// A user-defined daily metric (`custom_metrics`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CustomMetric {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub kind: MetricKind,
+    pub unit: Option<String>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub sort_order: i32,
+    pub is_archived: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CustomMetric {
+    /// Whether `[min, max]` excludes values the current range allows.
+    pub fn range_narrows(&self, min: Option<f64>, max: Option<f64>) -> bool {
+        min.is_some_and(|min| self.min_value.map_or(true, |old| min > old))
+            || max.is_some_and(|max| self.max_value.map_or(true, |old| max < old))
+    }
+
+    /// Check a value against the metric's kind and range.
+    pub fn check_value(&self, value: f64) -> Result<f64, String> {
+        if !value.is_finite() {
+            return Err(format!("{} must be a number", self.name));
+        }
+        match self.kind {
+            MetricKind::Boolean if value != 0.0 && value != 1.0 => {
+                return Err(format!("{} must be 0 or 1", self.name));
+            }
+            MetricKind::Scale if value.fract() != 0.0 => {
+                return Err(format!("{} must be a whole number", self.name));
+            }
+            _ => {}
+        }
+        let below = self.min_value.is_some_and(|min| value < min);
+        let above = self.max_value.is_some_and(|max| value > max);
+        if below || above {
+            let bound = |b: Option<f64>| b.map_or("…".to_string(), |b| b.to_string());
+            return Err(format!(
+                "{} must be between {} and {}",
+                self.name,
+                bound(self.min_value),
+                bound(self.max_value)
+            ));
+        }
+        Ok(value)
+    }
+}
+
+/// Validate a metric definition as it will be stored.
+pub fn validate_definition(
+    name: &str,
+    kind: MetricKind,
+    unit: Option<&str>,
+    min_value: Option<f64>,
+    max_value: Option<f64>,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("Metric name must be 1-{} characters", MAX_NAME_LEN));
+    }
+    if unit.is_some_and(|u| u.chars().count() > MAX_UNIT_LEN) {
+        return Err(format!("Unit must be at most {} characters", MAX_UNIT_LEN));
+    }
+    if [min_value, max_value]
+        .iter()
+        .flatten()
+        .any(|v| !v.is_finite())
+    {
+        return Err("Range bounds must be numbers".into());
+    }
+    if let (Some(min), Some(max)) = (min_value, max_value) {
+        if min >= max {
+            return Err("min_value must be below max_value".into());
+        }
+    }
+    match kind {
+        MetricKind::Scale => {
+            let (Some(min), Some(max)) = (min_value, max_value) else {
+                return Err("Scale metrics need min_value and max_value".into());
+            };
+            if min.fract() != 0.0 || max.fract() != 0.0 {
+                return Err("Scale bounds must be whole numbers".into());
+            }
+        }
+        MetricKind::Boolean if unit.is_some() || min_value.is_some() || max_value.is_some() => {
+            return Err("Boolean metrics take no unit or range".into());
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// One day's value of a metric (`custom_metric_values`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MetricValue {
+    pub metric_id: Uuid,
+    pub local_date_bucket: NaiveDate,
+    pub value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateMetricRequest {
+    pub name: String,
+    pub kind: MetricKind,
+    pub unit: Option<String>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+}
+
+/// The kind can't change once values may have been recorded under it.
+#[derive(Debug, Deserialize)]
+pub struct UpdateMetricRequest {
+    pub name: Option<String>,
+    pub unit: Option<String>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    /// Removes the unit (`unit` is ignored)
+    pub clear_unit: Option<bool>,
+    /// Removes both bounds (`min_value`/`max_value` are ignored)
+    pub clear_range: Option<bool>,
+    pub sort_order: Option<i32>,
+    pub is_archived: Option<bool>,
+}
+
+/// PUT /api/metrics/values — set (or with `value: null`, remove) a day's values
+#[derive(Debug, Deserialize)]
+pub struct SetMetricValuesRequest {
+    /// Default: today in the user's timezone
+    pub date: Option<NaiveDate>,
+    pub values: Vec<MetricValueInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetricValueInput {
+    pub metric_id: Uuid,
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetricValuesQuery {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(kind: MetricKind, min_value: Option<f64>, max_value: Option<f64>) -> CustomMetric {
+        CustomMetric {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            name: "Pain".into(),
+            kind,
+            unit: None,
+            min_value,
+            max_value,
+            sort_order: 0,
+            is_archived: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_check_value_by_kind() {
+        let scale = metric(MetricKind::Scale, Some(0.0), Some(10.0));
+        assert_eq!(scale.check_value(7.0), Ok(7.0));
+        assert!(scale.check_value(7.5).is_err());
+        assert_eq!(
+            scale.check_value(11.0).unwrap_err(),
+            "Pain must be between 0 and 10"
+        );
+
+        let boolean = metric(MetricKind::Boolean, None, None);
+        assert!(boolean.check_value(1.0).is_ok());
+        assert!(boolean.check_value(2.0).is_err());
+
+        let number = metric(MetricKind::Number, Some(0.0), None);
+        assert!(number.check_value(7.25).is_ok());
+        assert!(number.check_value(-1.0).is_err());
+        assert!(number.check_value(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_range_narrows() {
+        let m = metric(MetricKind::Scale, Some(0.0), Some(10.0));
+        assert!(!m.range_narrows(Some(0.0), Some(10.0)));
+        assert!(!m.range_narrows(Some(-5.0), None));
+        assert!(m.range_narrows(Some(1.0), Some(10.0)));
+        assert!(m.range_narrows(Some(0.0), Some(5.0)));
+        assert!(metric(MetricKind::Number, None, None).range_narrows(None, Some(100.0)));
+    }
+
+    #[test]
+    fn test_validate_definition() {
+        assert!(validate_definition(
+            "Sleep",
+            MetricKind::Number,
+            Some("hours"),
+            Some(0.0),
+            Some(24.0)
+        )
+        .is_ok());
+        assert!(validate_definition("Pain", MetricKind::Scale, None, Some(0.0), None).is_err());
+        assert!(
+            validate_definition("Pain", MetricKind::Scale, None, Some(0.5), Some(10.0)).is_err()
+        );
+        assert!(
+            validate_definition("Coffee", MetricKind::Boolean, Some("cups"), None, None).is_err()
+        );
+        assert!(
+            validate_definition("Weight", MetricKind::Number, None, Some(5.0), Some(5.0)).is_err()
+        );
+        assert!(validate_definition("  ", MetricKind::Number, None, None, None).is_err());
+    }
+}
@@ -1,9 +1,11 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+use super::mood_log::MoodLog;
+
+/// Legacy shape of a mood log, kept for `/api/daily-logs` clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DailyLog {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -16,6 +18,22 @@ pub struct DailyLog {
     pub updated_at: DateTime<Utc>,
 }
 
+impl From<MoodLog> for DailyLog {
+    fn from(log: MoodLog) -> Self {
+        DailyLog {
+            id: log.id,
+            user_id: log.user_id,
+            log_date: log.local_date_bucket,
+            mood: log.mood.map(i32::from),
+            energy: log.energy.map(i32::from),
+            stress: log.stress.map(i32::from),
+            note: log.note,
+            created_at: log.created_at,
+            updated_at: log.updated_at,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpsertDailyLogRequest {
     pub log_date: Option<NaiveDate>,
@@ -1,4 +1,4 @@
-use chrono::{DateTime, NaiveTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
@@ -15,7 +15,15 @@ pub struct Habit {
     pub frequency_config: serde_json::Value,
     pub target_per_day: i32,
     pub reminder_time: Option<NaiveTime>,
+    pub window_start: Option<NaiveTime>,
+    pub window_end: Option<NaiveTime>,
+    pub streak_on_time_only: bool,
     pub is_archived: bool,
+    /// When the habit was last archived (None while active)
+    pub archived_at: Option<DateTime<Utc>>,
+    /// 1–3, default 2; see `Habit::weight`
+    pub priority: i16,
+    pub difficulty: i16,
     pub sort_order: i32,
     pub current_streak: i32,
     pub longest_streak: i32,
@@ -24,10 +32,11 @@ pub struct Habit {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "habit_frequency", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum HabitFrequency {
+    #[default]
     Daily,
     #[sqlx(rename = "weekly_days")]
     #[serde(rename = "weekly_days")]
@@ -37,12 +46,6 @@ pub enum HabitFrequency {
     WeeklyTarget,
 }
 
-impl Default for HabitFrequency {
-    fn default() -> Self {
-        Self::Daily
-    }
-}
-
 #[derive(Debug, Deserialize)]
 pub struct CreateHabitRequest {
     pub name: String,
@@ -53,6 +56,11 @@ pub struct CreateHabitRequest {
     pub frequency_config: Option<serde_json::Value>,
     pub target_per_day: Option<i32>,
     pub reminder_time: Option<NaiveTime>,
+    pub window_start: Option<NaiveTime>,
+    pub window_end: Option<NaiveTime>,
+    pub streak_on_time_only: Option<bool>,
+    pub priority: Option<i16>,
+    pub difficulty: Option<i16>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -65,6 +73,13 @@ pub struct UpdateHabitRequest {
     pub frequency_config: Option<serde_json::Value>,
     pub target_per_day: Option<i32>,
     pub reminder_time: Option<NaiveTime>,
+    pub window_start: Option<NaiveTime>,
+    pub window_end: Option<NaiveTime>,
+    /// Removes the completion window (window_start/window_end are ignored)
+    pub clear_window: Option<bool>,
+    pub streak_on_time_only: Option<bool>,
+    pub priority: Option<i16>,
+    pub difficulty: Option<i16>,
     pub is_archived: Option<bool>,
     pub sort_order: Option<i32>,
 }
@@ -76,4 +91,176 @@ pub struct HabitWithStatus {
     pub completed_today: i32,
     pub is_complete: bool,
     pub is_due_today: bool,
+    /// Share of today's weighted score this habit carries (0–100); only when due
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_share: Option<f64>,
+    /// Chance of missing today; only for due habits not yet complete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk: Option<crate::services::risk::HabitRisk>,
+}
+
+/// GET /api/habits/today
+#[derive(Debug, Serialize)]
+pub struct TodayView {
+    pub date: NaiveDate,
+    /// Weighted score of the habits due today (0–100); None when nothing is due
+    pub score: Option<f64>,
+    pub habits: Vec<HabitWithStatus>,
+}
+
+pub const MIN_WEIGHT: i16 = 1;
+pub const MAX_WEIGHT: i16 = 3;
+
+impl Habit {
+    /// Weight in the daily score: priority × difficulty
+    pub fn weight(&self) -> i64 {
+        self.priority as i64 * self.difficulty as i64
+    }
+
+    /// Whether a local time of day falls inside the habit's completion window.
+    /// Habits without a window accept any time.
+    pub fn is_within_window(&self, local_time: NaiveTime) -> bool {
+        match (self.window_start, self.window_end) {
+            (Some(start), Some(end)) => time_in_window(local_time, start, end),
+            _ => true,
+        }
+    }
+}
+
+/// `[start, end)` window check; `start > end` wraps past midnight.
+pub fn time_in_window(t: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        t >= start && t < end
+    } else {
+        t >= start || t < end
+    }
+}
+
+/// Priority and difficulty, when given, must be within 1–3.
+pub fn validate_weights(priority: Option<i16>, difficulty: Option<i16>) -> Result<(), String> {
+    for (name, value) in [("priority", priority), ("difficulty", difficulty)] {
+        if value.is_some_and(|v| !(MIN_WEIGHT..=MAX_WEIGHT).contains(&v)) {
+            return Err(format!("{name} must be between {MIN_WEIGHT} and {MAX_WEIGHT}"));
+        }
+    }
+    Ok(())
+}
+
+/// Both bounds or neither, and never an empty window.
+pub fn validate_window(start: Option<NaiveTime>, end: Option<NaiveTime>) -> Result<(), String> {
+    match (start, end) {
+        (None, None) => Ok(()),
+        (Some(s), Some(e)) if s == e => Err("window_start and window_end must differ".into()),
+        (Some(_), Some(_)) => Ok(()),
+        _ => Err("window_start and window_end must be set together".into()),
+    }
+}
+
+/// Habits for unit tests: a daily, unweighted, windowless habit created
+/// on 2026-01-01 unless told otherwise.
+#[cfg(test)]
+pub struct HabitBuilder(Habit);
+
+#[cfg(test)]
+impl HabitBuilder {
+    pub fn new(id: u128) -> Self {
+        let created = "2026-01-01T00:00:00Z".parse().unwrap();
+        Self(Habit {
+            id: Uuid::from_u128(id),
+            user_id: Uuid::nil(),
+            name: "h".into(),
+            description: None,
+            color: "#000000".into(),
+            icon: "target".into(),
+            frequency: HabitFrequency::Daily,
+            frequency_config: serde_json::json!({}),
+            target_per_day: 1,
+            reminder_time: None,
+            window_start: None,
+            window_end: None,
+            streak_on_time_only: false,
+            is_archived: false,
+            archived_at: None,
+            priority: 2,
+            difficulty: 2,
+            sort_order: 0,
+            current_streak: 0,
+            longest_streak: 0,
+            total_completions: 0,
+            created_at: created,
+            updated_at: created,
+        })
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.0.name = name.into();
+        self
+    }
+
+    pub fn frequency(mut self, frequency: HabitFrequency, config: serde_json::Value) -> Self {
+        self.0.frequency = frequency;
+        self.0.frequency_config = config;
+        self
+    }
+
+    /// RFC 3339 creation time
+    pub fn created(mut self, at: &str) -> Self {
+        self.0.created_at = at.parse().unwrap();
+        self.0.updated_at = self.0.created_at;
+        self
+    }
+
+    pub fn streak(mut self, current: i32, longest: i32) -> Self {
+        self.0.current_streak = current;
+        self.0.longest_streak = longest;
+        self
+    }
+
+    pub fn build(self) -> Habit {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_window_same_day() {
+        // "before 9am" expressed as 05:00-09:00
+        assert!(time_in_window(t(7, 30), t(5, 0), t(9, 0)));
+        assert!(time_in_window(t(5, 0), t(5, 0), t(9, 0)));
+        assert!(!time_in_window(t(9, 0), t(5, 0), t(9, 0)));
+        assert!(!time_in_window(t(4, 59), t(5, 0), t(9, 0)));
+    }
+
+    #[test]
+    fn test_window_wraps_midnight() {
+        // 22:00-06:00
+        assert!(time_in_window(t(23, 15), t(22, 0), t(6, 0)));
+        assert!(time_in_window(t(0, 30), t(22, 0), t(6, 0)));
+        assert!(!time_in_window(t(6, 0), t(22, 0), t(6, 0)));
+        assert!(!time_in_window(t(12, 0), t(22, 0), t(6, 0)));
+    }
+
+    #[test]
+    fn test_validate_window() {
+        assert!(validate_window(None, None).is_ok());
+        assert!(validate_window(Some(t(5, 0)), Some(t(9, 0))).is_ok());
+        assert!(validate_window(Some(t(5, 0)), None).is_err());
+        assert!(validate_window(None, Some(t(9, 0))).is_err());
+        assert!(validate_window(Some(t(9, 0)), Some(t(9, 0))).is_err());
+    }
+
+    #[test]
+    fn test_validate_weights() {
+        assert!(validate_weights(None, None).is_ok());
+        assert!(validate_weights(Some(1), Some(3)).is_ok());
+        assert!(validate_weights(Some(0), None).is_err());
+        assert!(validate_weights(None, Some(4)).is_err());
+    }
 }
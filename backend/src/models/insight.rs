@@ -0,0 +1,40 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Generated insight text, from the model or the fallback, before storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsightContent {
+    pub summary: String,
+    pub wins: Vec<String>,
+    pub improvements: Vec<String>,
+    pub mood_correlation: Option<String>,
+    pub streak_analysis: String,
+    pub tip_of_the_week: String,
+    /// The provider's name ("anthropic", "openai", "fake") or "fallback";
+    /// not part of the model's JSON
+    #[serde(default)]
+    pub source: String,
+}
+
+/// One row of `insights`.
+#[derive(Debug, Clone, FromRow)]
+pub struct Insight {
+    pub id: Uuid,
+    pub week_start_date: NaiveDate,
+    pub source: String,
+    pub summary: String,
+    pub wins: sqlx::types::Json<Vec<String>>,
+    pub improvements: sqlx::types::Json<Vec<String>>,
+    pub mood_correlation: Option<String>,
+    pub streak_analysis: String,
+    pub tip_of_the_week: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InsightHistoryQuery {
+    /// Default 12, max 52
+    pub limit: Option<i64>,
+}
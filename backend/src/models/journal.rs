@@ -0,0 +1,104 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const MAX_BODY_LEN: usize = 10_000;
+pub const MAX_TAGS: usize = 10;
+pub const MAX_TAG_LEN: usize = 32;
+
+/// A journal entry with the habits it links to.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct JournalEntry {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub local_date_bucket: NaiveDate,
+    /// Id of the prompt this entry answers, if any
+    pub prompt_id: Option<String>,
+    pub body: String,
+    pub tags: Vec<String>,
+    pub habit_ids: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateJournalEntryRequest {
+    /// Default: today in the user's timezone
+    pub date: Option<NaiveDate>,
+    pub body: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub habit_ids: Vec<Uuid>,
+    pub prompt_id: Option<String>,
+}
+
+/// Partial update; `tags` and `habit_ids` replace the whole list when given.
+#[derive(Debug, Deserialize)]
+pub struct UpdateJournalEntryRequest {
+    pub date: Option<NaiveDate>,
+    pub body: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub habit_ids: Option<Vec<Uuid>>,
+    pub prompt_id: Option<String>,
+    /// Detaches the entry from its prompt (`prompt_id` is ignored)
+    pub clear_prompt: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JournalQuery {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub tag: Option<String>,
+    pub habit_id: Option<Uuid>,
+}
+
+pub fn validate_body(body: &str) -> Result<(), String> {
+    if body.trim().is_empty() {
+        return Err("Journal entry can't be empty".into());
+    }
+    if body.chars().count() > MAX_BODY_LEN {
+        return Err(format!(
+            "Journal entry must be under {} characters",
+            MAX_BODY_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Trim, lowercase and de-duplicate tags ("#Sleep " → "sleep"), keeping the
+/// order they were given in.
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').trim().to_lowercase();
+        if tag.is_empty() {
+            return Err("Tags can't be empty".into());
+        }
+        if tag.chars().count() > MAX_TAG_LEN {
+            return Err(format!("Tags must be at most {} characters", MAX_TAG_LEN));
+        }
+        if !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    if out.len() > MAX_TAGS {
+        return Err(format!("An entry can have at most {} tags", MAX_TAGS));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = vec!["#Sleep ".to_string(), "work".into(), "sleep".into()];
+        assert_eq!(normalize_tags(&tags).unwrap(), vec!["sleep", "work"]);
+        assert!(normalize_tags(&["#".to_string()]).is_err());
+        let many: Vec<String> = (0..11).map(|i| format!("t{}", i)).collect();
+        assert!(normalize_tags(&many).is_err());
+    }
+}
@@ -1,4 +1,10 @@
 pub mod user;
 pub mod habit;
 pub mod completion;
+pub mod checkin;
+pub mod timer;
 pub mod daily_log;
+pub mod custom_metric;
+pub mod journal;
+pub mod insight;
+pub mod mood_log;
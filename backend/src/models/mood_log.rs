@@ -0,0 +1,155 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// One row of `mood_logs`: a user's mood, energy and stress for a local day.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MoodLog {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub local_date_bucket: NaiveDate,
+    pub mood: Option<i16>,
+    pub energy: Option<i16>,
+    pub stress: Option<i16>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A field a partial update can remove.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MoodField {
+    Mood,
+    Energy,
+    Stress,
+    Note,
+}
+
+impl MoodField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MoodField::Mood => "mood",
+            MoodField::Energy => "energy",
+            MoodField::Stress => "stress",
+            MoodField::Note => "note",
+        }
+    }
+}
+
+/// The values a log will hold after an update.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MoodValues {
+    pub mood: Option<i16>,
+    pub energy: Option<i16>,
+    pub stress: Option<i16>,
+    pub note: Option<String>,
+}
+
+impl MoodValues {
+    /// Apply a partial update over `existing`: given fields overwrite, cleared
+    /// fields are removed, the rest are kept. The result must still hold one
+    /// of mood, energy or stress (the table requires it).
+    pub fn merge(
+        existing: Option<&MoodLog>,
+        update: MoodValues,
+        clear: &[MoodField],
+    ) -> Result<Self, String> {
+        for field in clear {
+            let also_set = match field {
+                MoodField::Mood => update.mood.is_some(),
+                MoodField::Energy => update.energy.is_some(),
+                MoodField::Stress => update.stress.is_some(),
+                MoodField::Note => update.note.is_some(),
+            };
+            if also_set {
+                return Err(format!("{} can't be both set and cleared", field.as_str()));
+            }
+        }
+
+        let keep = |field: MoodField| !clear.contains(&field);
+        let merged = MoodValues {
+            mood: update
+                .mood
+                .or(existing.and_then(|e| e.mood))
+                .filter(|_| keep(MoodField::Mood)),
+            energy: update
+                .energy
+                .or(existing.and_then(|e| e.energy))
+                .filter(|_| keep(MoodField::Energy)),
+            stress: update
+                .stress
+                .or(existing.and_then(|e| e.stress))
+                .filter(|_| keep(MoodField::Stress)),
+            note: update
+                .note
+                .or_else(|| existing.and_then(|e| e.note.clone()))
+                .filter(|_| keep(MoodField::Note)),
+        };
+
+        if merged.mood.is_none() && merged.energy.is_none() && merged.stress.is_none() {
+            return Err(
+                "A mood log needs at least one of mood, energy or stress; delete the log instead"
+                    .into(),
+            );
+        }
+        Ok(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn existing() -> MoodLog {
+        MoodLog {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            local_date_bucket: NaiveDate::from_ymd_opt(2026, 2, 10).unwrap(),
+            mood: Some(4),
+            energy: Some(3),
+            stress: None,
+            note: Some("ran".into()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_merge_keeps_overwrites_and_clears() {
+        let update = MoodValues {
+            stress: Some(2),
+            ..MoodValues::default()
+        };
+        let merged = MoodValues::merge(
+            Some(&existing()),
+            update,
+            &[MoodField::Energy, MoodField::Note],
+        )
+        .unwrap();
+        assert_eq!(
+            merged,
+            MoodValues {
+                mood: Some(4),
+                energy: None,
+                stress: Some(2),
+                note: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_rejects_empty_result_and_conflicts() {
+        let clear_all = [MoodField::Mood, MoodField::Energy];
+        assert!(MoodValues::merge(Some(&existing()), MoodValues::default(), &clear_all).is_err());
+        assert!(MoodValues::merge(None, MoodValues::default(), &[]).is_err());
+
+        let update = MoodValues {
+            mood: Some(5),
+            ..MoodValues::default()
+        };
+        let err = MoodValues::merge(Some(&existing()), update, &[MoodField::Mood]).unwrap_err();
+        assert_eq!(err, "mood can't be both set and cleared");
+    }
+}
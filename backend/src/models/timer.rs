@@ -0,0 +1,127 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::completion::Completion;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "timer_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TimerStatus {
+    Running,
+    Paused,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TimerSession {
+    pub id: Uuid,
+    pub habit_id: Uuid,
+    pub user_id: Uuid,
+    pub status: TimerStatus,
+    #[sqlx(rename = "local_date_bucket")]
+    pub completed_date: NaiveDate,
+    pub started_at: DateTime<Utc>,
+    pub resumed_at: Option<DateTime<Utc>>,
+    pub accumulated_secs: i64,
+    pub stopped_at: Option<DateTime<Utc>>,
+    pub checkin_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TimerSession {
+    /// Total elapsed seconds as of `now`, including the open segment if running.
+    pub fn elapsed_secs(&self, now: DateTime<Utc>) -> i64 {
+        let open = match (self.status, self.resumed_at) {
+            (TimerStatus::Running, Some(resumed_at)) => (now - resumed_at).num_seconds().max(0),
+            _ => 0,
+        };
+        self.accumulated_secs + open
+    }
+
+    /// Whole minutes credited to the day's completion when the timer stops.
+    pub fn credited_minutes(&self, now: DateTime<Utc>) -> i32 {
+        (self.elapsed_secs(now) / 60).min(i32::MAX as i64) as i32
+    }
+}
+
+/// Timer with its elapsed time computed at response time, so clients on any
+/// device can render the clock without replaying segments.
+#[derive(Debug, Serialize)]
+pub struct TimerSessionResponse {
+    #[serde(flatten)]
+    pub session: TimerSession,
+    pub elapsed_secs: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimerStopResponse {
+    #[serde(flatten)]
+    pub session: TimerSession,
+    pub elapsed_secs: i64,
+    pub minutes_credited: i32,
+    pub completion: Option<Completion>,
+    pub target_per_day: i32,
+    pub is_complete: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn session(
+        status: TimerStatus,
+        accumulated_secs: i64,
+        resumed_at: Option<DateTime<Utc>>,
+    ) -> TimerSession {
+        let t0 = DateTime::parse_from_rfc3339("2026-02-10T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        TimerSession {
+            id: Uuid::nil(),
+            habit_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            status,
+            completed_date: NaiveDate::from_ymd_opt(2026, 2, 10).unwrap(),
+            started_at: t0,
+            resumed_at,
+            accumulated_secs,
+            stopped_at: None,
+            checkin_id: None,
+            created_at: t0,
+            updated_at: t0,
+        }
+    }
+
+    #[test]
+    fn test_running_timer_counts_open_segment() {
+        let resumed = Utc::now() - Duration::seconds(600);
+        let s = session(TimerStatus::Running, 120, Some(resumed));
+        let now = resumed + Duration::seconds(600);
+        assert_eq!(s.elapsed_secs(now), 720);
+        assert_eq!(s.credited_minutes(now), 12);
+    }
+
+    #[test]
+    fn test_paused_timer_ignores_wall_clock() {
+        let s = session(TimerStatus::Paused, 1_805, None);
+        assert_eq!(s.elapsed_secs(Utc::now()), 1_805);
+        assert_eq!(s.credited_minutes(Utc::now()), 30);
+    }
+
+    #[test]
+    fn test_under_one_minute_credits_nothing() {
+        let s = session(TimerStatus::Stopped, 59, None);
+        assert_eq!(s.credited_minutes(Utc::now()), 0);
+    }
+
+    #[test]
+    fn test_clock_skew_never_goes_negative() {
+        let resumed = Utc::now();
+        let s = session(TimerStatus::Running, 0, Some(resumed));
+        assert_eq!(s.elapsed_secs(resumed - Duration::seconds(30)), 0);
+    }
+}
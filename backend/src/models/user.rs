@@ -17,6 +17,11 @@ pub struct User {
     pub demo_expires_at: Option<DateTime<Utc>>,
     pub demo_insight_calls_used: i32,
     pub timezone: String,
+    pub week_start: WeekStart,
+    /// Opt-in: let insights read journal entries
+    pub journal_in_insights: bool,
+    /// Notify when the Monday insight is ready
+    pub insight_notifications: bool,
     pub stripe_customer_id: Option<String>,
     pub subscription_tier: SubscriptionTier,
     pub subscription_status: SubscriptionStatus,
@@ -24,22 +29,17 @@ pub struct User {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "subscription_tier", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum SubscriptionTier {
+    #[default]
     Free,
     Plus,
     Pro,
 }
 
-impl Default for SubscriptionTier {
-    fn default() -> Self {
-        Self::Free
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "subscription_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum SubscriptionStatus {
@@ -47,13 +47,25 @@ pub enum SubscriptionStatus {
     Trialing,
     PastDue,
     Canceled,
+    #[default]
     Inactive,
 }
 
-impl Default for SubscriptionStatus {
-    fn default() -> Self {
-        Self::Inactive
-    }
+/// First day of the user's week for reviews and week navigation
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "week_start_day", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePreferencesRequest {
+    pub week_start: Option<WeekStart>,
+    pub journal_in_insights: Option<bool>,
+    pub insight_notifications: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -66,6 +78,9 @@ pub struct UserProfile {
     pub is_demo: bool,
     pub demo_expires_at: Option<DateTime<Utc>>,
     pub timezone: String,
+    pub week_start: WeekStart,
+    pub journal_in_insights: bool,
+    pub insight_notifications: bool,
     pub subscription_tier: SubscriptionTier,
     pub subscription_status: SubscriptionStatus,
     pub entitlements: UserEntitlements,
@@ -78,6 +93,8 @@ pub struct UserEntitlements {
     pub schedule_types: Vec<String>,
     pub analytics_days: i32,
     pub heatmap_months: i32,
+    /// Model-generated insights per ISO week: Some(0) = disabled (fallback
+    /// only), None = unlimited (throttled, see `services::ai_quota`)
     pub ai_insights_per_week: Option<i32>,
     pub reminders: RemindersEntitlement,
     pub data_export: bool,
@@ -100,7 +117,7 @@ impl UserEntitlements {
                 schedule_types: vec!["daily".into()],
                 analytics_days: 7,
                 heatmap_months: 1,
-                ai_insights_per_week: None,
+                ai_insights_per_week: Some(0),
                 reminders: RemindersEntitlement::Limited(1),
                 data_export: false,
             },
@@ -138,6 +155,9 @@ impl From<User> for UserProfile {
             is_demo: u.is_demo,
             demo_expires_at: u.demo_expires_at,
             timezone: u.timezone,
+            week_start: u.week_start,
+            journal_in_insights: u.journal_in_insights,
+            insight_notifications: u.insight_notifications,
             subscription_tier: u.subscription_tier,
             subscription_status: u.subscription_status,
             entitlements,
@@ -0,0 +1,239 @@
+//! `ai_insights_per_week` enforcement.
+//!
+//! `Some(0)` disables model calls (Free gets the deterministic fallback),
+//! `Some(n)` allows n calls per ISO week of the user's local calendar (Plus;
+//! the same week insights are stored under), and `None` is unlimited but
+//! throttled to one call per `UNLIMITED_INTERVAL` (Pro). Calls are recorded in
+//! `ai_insight_usage`; a call is reserved before it is made, under a row lock
+//! on the user so concurrent requests can't both take the last slot. It is
+//! refunded only if no model call completed: a reply that fails parsing was
+//! still billed, so it keeps its row and `model_calls` records the repair.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Minimum time between model calls without a weekly limit
+pub const UNLIMITED_INTERVAL: Duration = Duration::hours(1);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InsightQuota {
+    /// Model calls allowed per ISO week; None = unlimited
+    pub limit_per_week: Option<i32>,
+    pub used_this_week: i64,
+    /// None when unlimited
+    pub remaining_this_week: Option<i64>,
+    /// Whether a model call can be made now
+    pub available: bool,
+    /// When the next call becomes possible, if not now
+    pub next_available_at: Option<DateTime<Utc>>,
+    /// Start of the user's next local ISO week
+    pub resets_at: DateTime<Utc>,
+}
+
+impl InsightQuota {
+    /// Quota state from this week's usage and the time of the last call.
+    pub fn evaluate(
+        limit: Option<i32>,
+        used_this_week: i64,
+        last_used: Option<DateTime<Utc>>,
+        resets_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let (remaining, next_available_at) = match limit {
+            Some(limit) => {
+                let remaining = (limit as i64 - used_this_week).max(0);
+                // A disabled quota never opens up
+                let next = match (remaining, limit) {
+                    (0, 0) => None,
+                    (0, _) => Some(resets_at),
+                    _ => None,
+                };
+                (Some(remaining), next)
+            }
+            None => {
+                let next = last_used
+                    .map(|t| t + UNLIMITED_INTERVAL)
+                    .filter(|t| *t > now);
+                (None, next)
+            }
+        };
+        let available = match (limit, remaining) {
+            (Some(_), Some(r)) => r > 0,
+            _ => next_available_at.is_none(),
+        };
+        InsightQuota {
+            limit_per_week: limit,
+            used_this_week,
+            remaining_this_week: remaining,
+            available,
+            next_available_at,
+            resets_at,
+        }
+    }
+}
+
+/// Local midnight starting `date` as an instant, given the user's local
+/// time at `now`.
+fn local_midnight(date: NaiveDate, local_now: NaiveDateTime, now: DateTime<Utc>) -> DateTime<Utc> {
+    now + (date.and_time(NaiveTime::MIN) - local_now)
+}
+
+/// This week's usage count, the last call time and the user's local time.
+async fn usage(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    week_start: NaiveDate,
+    now: DateTime<Utc>,
+) -> Result<(i64, Option<DateTime<Utc>>, NaiveDateTime), sqlx::Error> {
+    sqlx::query_as::<_, (i64, Option<DateTime<Utc>>, NaiveDateTime)>(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM ai_insight_usage a WHERE a.user_id = u.id AND a.week_start_date = $2),
+            (SELECT MAX(a.used_at) FROM ai_insight_usage a WHERE a.user_id = u.id),
+            to_user_local($3, u.timezone)
+        FROM users u
+        WHERE u.id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(week_start)
+    .bind(now)
+    .fetch_one(conn)
+    .await
+}
+
+/// Quota state for the local ISO week starting `week_start`.
+pub async fn status(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    limit: Option<i32>,
+    week_start: NaiveDate,
+    now: DateTime<Utc>,
+) -> Result<InsightQuota, sqlx::Error> {
+    let (used, last, local_now) = usage(conn, user_id, week_start, now).await?;
+    let resets_at = local_midnight(week_start + Duration::days(7), local_now, now);
+    Ok(InsightQuota::evaluate(limit, used, last, resets_at, now))
+}
+
+/// Reserve a model call against the local ISO week starting `week_start`
+/// if the quota allows one. Returns the usage row id to refund on failure,
+/// or None when the quota is spent. Run inside a transaction: the user row
+/// is locked until it commits.
+pub async fn reserve(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    limit: Option<i32>,
+    week_start: NaiveDate,
+    now: DateTime<Utc>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query("SELECT 1 FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    if !status(&mut *conn, user_id, limit, week_start, now)
+        .await?
+        .available
+    {
+        return Ok(None);
+    }
+    let id = sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO ai_insight_usage (user_id, week_start_date, used_at) VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(user_id)
+    .bind(week_start)
+    .bind(now)
+    .fetch_one(conn)
+    .await?;
+    Ok(Some(id))
+}
+
+/// Record how many model calls a reservation used (a repair makes two).
+pub async fn record_calls(
+    conn: &mut PgConnection,
+    usage_id: Uuid,
+    calls: u32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE ai_insight_usage SET model_calls = $2 WHERE id = $1")
+        .bind(usage_id)
+        .bind(calls as i32)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Give a reserved call back (no model call completed).
+pub async fn refund(conn: &mut PgConnection, usage_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM ai_insight_usage WHERE id = $1")
+        .bind(usage_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn local(s: &str) -> NaiveDateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_resets_at_local_midnight() {
+        let next_monday = NaiveDate::from_ymd_opt(2026, 2, 16).unwrap();
+        let now = at("2026-02-11T12:00:00Z");
+        assert_eq!(
+            local_midnight(next_monday, local("2026-02-11T12:00:00"), now),
+            at("2026-02-16T00:00:00Z")
+        );
+        // UTC+13: local Monday starts on Sunday 11:00 UTC
+        assert_eq!(
+            local_midnight(next_monday, local("2026-02-12T01:00:00"), now),
+            at("2026-02-15T11:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_weekly_limit() {
+        // Wednesday 2026-02-11
+        let now = at("2026-02-11T12:00:00Z");
+        let resets_at = at("2026-02-16T00:00:00Z");
+        let q = InsightQuota::evaluate(Some(1), 0, None, resets_at, now);
+        assert!(q.available);
+        assert_eq!(q.remaining_this_week, Some(1));
+        assert_eq!(q.resets_at, resets_at);
+
+        let q = InsightQuota::evaluate(Some(1), 1, Some(now), resets_at, now);
+        assert!(!q.available);
+        assert_eq!(q.next_available_at, Some(at("2026-02-16T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_disabled_never_available() {
+        let now = at("2026-02-11T12:00:00Z");
+        let q = InsightQuota::evaluate(Some(0), 0, None, at("2026-02-16T00:00:00Z"), now);
+        assert!(!q.available);
+        assert_eq!(q.next_available_at, None);
+    }
+
+    #[test]
+    fn test_unlimited_is_throttled_hourly() {
+        let now = at("2026-02-11T12:00:00Z");
+        let resets_at = at("2026-02-16T00:00:00Z");
+        let q = InsightQuota::evaluate(None, 5, Some(at("2026-02-11T11:30:00Z")), resets_at, now);
+        assert!(!q.available);
+        assert_eq!(q.next_available_at, Some(at("2026-02-11T12:30:00Z")));
+        assert_eq!(q.remaining_this_week, None);
+
+        assert!(
+            InsightQuota::evaluate(None, 5, Some(at("2026-02-11T11:00:00Z")), resets_at, now)
+                .available
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Per-day habit states for month calendars.
+//!
+//! Unlike the heatmap, which only lists days with completions, every date
+//! gets an explicit state from the habit's lifecycle, schedule and history.
+//! Weekly-target habits follow the same rules as `stats::daily_tallies`: a
+//! past day without a session is only missed once the quota could no longer
+//! be met without it, and nothing is due once the week's quota is met.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::schedule::Schedule;
+use super::stats::HabitDays;
+use crate::models::user::WeekStart;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DayState {
+    Done,
+    Missed,
+    /// Scheduled today or later and not done yet
+    Due,
+    NotScheduled,
+    /// Before the habit was created
+    BeforeStart,
+    /// On or after the day the habit was archived
+    Archived,
+}
+
+pub fn day_state(
+    habit: &HabitDays,
+    date: NaiveDate,
+    today: NaiveDate,
+    first: WeekStart,
+) -> DayState {
+    if date < habit.from {
+        return DayState::BeforeStart;
+    }
+    if habit.until.is_some_and(|until| date >= until) {
+        return DayState::Archived;
+    }
+    if habit.done.contains_key(&date) {
+        return DayState::Done;
+    }
+
+    let scheduled = match habit.schedule {
+        Schedule::WeeklyTarget(_) if date < today => habit.counts_on(date, first),
+        Schedule::WeeklyTarget(_) => habit.still_needed(date, first) > 0,
+        _ => habit.schedule.is_due(date),
+    };
+    match (scheduled, date < today) {
+        (false, _) => DayState::NotScheduled,
+        (true, true) => DayState::Missed,
+        (true, false) => DayState::Due,
+    }
+}
+
+/// States for each date in `[start, end]`.
+pub fn month_states(
+    habit: &HabitDays,
+    start: NaiveDate,
+    end: NaiveDate,
+    today: NaiveDate,
+    first: WeekStart,
+) -> Vec<(NaiveDate, DayState)> {
+    start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .map(|d| (d, day_state(habit, d, today, first)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::stats::DoneDays;
+
+    fn d(day: u32) -> NaiveDate {
+        // 2026-02-02 is a Monday
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    fn span(schedule: Schedule, until: Option<NaiveDate>, done: &DoneDays) -> HabitDays<'_> {
+        HabitDays {
+            schedule,
+            from: d(3),
+            until,
+            weight: 1,
+            done,
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_and_schedule_states() {
+        // Mon/Wed/Fri, created Tuesday, archived the following Monday
+        let mut days = [false; 7];
+        days[0] = true;
+        days[2] = true;
+        days[4] = true;
+        let done: DoneDays = [(d(4), 1)].into_iter().collect();
+        let habit = span(Schedule::WeeklyDays(days), Some(d(9)), &done);
+        let states: Vec<DayState> = month_states(&habit, d(2), d(9), d(7), WeekStart::Monday)
+            .into_iter()
+            .map(|(_, s)| s)
+            .collect();
+        use DayState::*;
+        assert_eq!(
+            states,
+            vec![
+                BeforeStart,
+                NotScheduled,
+                Done,
+                NotScheduled,
+                Missed,
+                NotScheduled,
+                NotScheduled,
+                Archived
+            ]
+        );
+    }
+
+    #[test]
+    fn test_weekly_target_due_until_quota_met() {
+        // 2x/week, done Monday; today is Thursday
+        let done: DoneDays = [(d(2), 1)].into_iter().collect();
+        let mut habit = span(Schedule::WeeklyTarget(2), None, &done);
+        habit.from = d(1);
+        let first = WeekStart::Monday;
+        assert_eq!(day_state(&habit, d(2), d(5), first), DayState::Done);
+        // Tuesday passed without a session but the quota was still reachable
+        assert_eq!(day_state(&habit, d(3), d(5), first), DayState::NotScheduled);
+        assert_eq!(day_state(&habit, d(5), d(5), first), DayState::Due);
+        // Sunday with the quota still open was the last chance
+        assert_eq!(day_state(&habit, d(8), d(9), first), DayState::Missed);
+    }
+}
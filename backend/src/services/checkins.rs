@@ -0,0 +1,326 @@
+//! Check-in event log and the per-day `habit_completions` rollup.
+//!
+//! Every write path (check-ins, completions, toggle) goes through these
+//! helpers so the rollup row always equals the sum of the day's check-ins.
+//! Callers pass a transaction connection, take `lock_habit` before the first
+//! write, and commit once both tables agree.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::models::checkin::Checkin;
+use crate::models::completion::Completion;
+use crate::models::habit::Habit;
+
+/// Whether a check-in at `at` for `date` misses the habit's completion
+/// window, judged in the user's local time. Only a check-in made on `date`
+/// itself can be late; backfills made on another day are not. Habits
+/// without a window are never late.
+pub async fn is_late(
+    conn: &mut PgConnection,
+    habit: &Habit,
+    date: NaiveDate,
+    at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    if habit.window_start.is_none() || habit.window_end.is_none() {
+        return Ok(false);
+    }
+    let local = sqlx::query_scalar::<_, NaiveDateTime>(
+        "SELECT to_user_local($1, timezone) FROM users WHERE id = $2",
+    )
+    .bind(at)
+    .bind(habit.user_id)
+    .fetch_one(conn)
+    .await?;
+    Ok(local.date() == date && !habit.is_within_window(local.time()))
+}
+
+/// Lock the habit row until the transaction ends. Writers to a habit's
+/// check-ins take it first, so they roll the day up one after another and
+/// none sums the day without a concurrent writer's check-in.
+pub async fn lock_habit(conn: &mut PgConnection, habit_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT 1 FROM habits WHERE id = $1 FOR UPDATE")
+        .bind(habit_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Fields for a new check-in row.
+pub struct NewCheckin<'a> {
+    pub habit_id: Uuid,
+    pub user_id: Uuid,
+    pub date: NaiveDate,
+    pub checked_in_at: DateTime<Utc>,
+    pub value: i32,
+    pub note: Option<&'a str>,
+    pub is_late: bool,
+}
+
+/// Append a check-in to the event log. Does not touch the rollup.
+pub async fn record_checkin(
+    conn: &mut PgConnection,
+    new: NewCheckin<'_>,
+) -> Result<Checkin, sqlx::Error> {
+    sqlx::query_as::<_, Checkin>(
+        r#"
+        INSERT INTO habit_checkins (id, habit_id, user_id, local_date_bucket, checked_in_at, value, note, is_late)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(new.habit_id)
+    .bind(new.user_id)
+    .bind(new.date)
+    .bind(new.checked_in_at)
+    .bind(new.value)
+    .bind(new.note)
+    .bind(new.is_late)
+    .fetch_one(conn)
+    .await
+}
+
+/// Remove the most recent check-in of the day, if any.
+pub async fn undo_last_checkin(
+    conn: &mut PgConnection,
+    habit_id: Uuid,
+    date: NaiveDate,
+) -> Result<Option<Checkin>, sqlx::Error> {
+    sqlx::query_as::<_, Checkin>(
+        r#"
+        DELETE FROM habit_checkins
+        WHERE id = (
+            SELECT id FROM habit_checkins
+            WHERE habit_id = $1 AND local_date_bucket = $2
+            ORDER BY checked_in_at DESC, created_at DESC
+            LIMIT 1
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(habit_id)
+    .bind(date)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Remove every check-in of the day (toggle-off / completion delete).
+pub async fn clear_day(
+    conn: &mut PgConnection,
+    habit_id: Uuid,
+    date: NaiveDate,
+) -> Result<u64, sqlx::Error> {
+    let result =
+        sqlx::query("DELETE FROM habit_checkins WHERE habit_id = $1 AND local_date_bucket = $2")
+            .bind(habit_id)
+            .bind(date)
+            .execute(conn)
+            .await?;
+    Ok(result.rows_affected())
+}
+
+/// Recompute the day's rollup row from its check-ins.
+///
+/// Upserts `habit_completions` with the summed value, or deletes the row
+/// when no check-ins remain. The day is late only if every check-in was late,
+/// and its note is the latest remaining check-in note, so undo takes one back.
+/// Returns the rollup and the check-in count.
+pub async fn rollup_day(
+    conn: &mut PgConnection,
+    habit_id: Uuid,
+    user_id: Uuid,
+    date: NaiveDate,
+) -> Result<(Option<Completion>, i64), sqlx::Error> {
+    let (total, count, all_late) = sqlx::query_as::<_, (i64, i64, bool)>(
+        r#"
+        SELECT COALESCE(SUM(value), 0), COUNT(*), COALESCE(BOOL_AND(is_late), false)
+        FROM habit_checkins
+        WHERE habit_id = $1 AND local_date_bucket = $2
+        "#,
+    )
+    .bind(habit_id)
+    .bind(date)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    // A backfill may change a closed period's cached review
+    super::reviews::invalidate_cached(&mut *conn, user_id, date).await?;
+
+    if total == 0 {
+        sqlx::query("DELETE FROM habit_completions WHERE habit_id = $1 AND local_date_bucket = $2")
+            .bind(habit_id)
+            .bind(date)
+            .execute(&mut *conn)
+            .await?;
+        super::rollups::refresh_after_write(&mut *conn, user_id, date).await?;
+        return Ok((None, 0));
+    }
+
+    let completion = sqlx::query_as::<_, Completion>(
+        r#"
+        INSERT INTO habit_completions (id, habit_id, user_id, local_date_bucket, value, is_late, note)
+        VALUES ($1, $2, $3, $4, $5, $6, (
+            SELECT note FROM habit_checkins
+            WHERE habit_id = $2 AND local_date_bucket = $4 AND note IS NOT NULL
+            ORDER BY checked_in_at DESC
+            LIMIT 1
+        ))
+        ON CONFLICT (habit_id, local_date_bucket) DO UPDATE
+            SET value = EXCLUDED.value,
+                is_late = EXCLUDED.is_late,
+                note = EXCLUDED.note
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(habit_id)
+    .bind(user_id)
+    .bind(date)
+    .bind(total.min(i32::MAX as i64) as i32)
+    .bind(all_late)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    super::rollups::refresh_after_write(&mut *conn, user_id, date).await?;
+    Ok((Some(completion), count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Connection;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    async fn check_in(
+        conn: &mut PgConnection,
+        habit_id: Uuid,
+        user_id: Uuid,
+        date: NaiveDate,
+        time: &str,
+        value: i32,
+    ) {
+        let new = NewCheckin {
+            habit_id,
+            user_id,
+            date,
+            checked_in_at: at(time),
+            value,
+            note: None,
+            is_late: false,
+        };
+        record_checkin(conn, new).await.unwrap();
+    }
+
+    /// A user with a "Water" habit; returns (user_id, habit_id).
+    async fn water_habit(conn: &mut PgConnection) -> (Uuid, Uuid) {
+        let user_id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO users (email, password_hash) VALUES ($1, 'x') RETURNING id",
+        )
+        .bind(format!("{}@checkins.test", Uuid::new_v4()))
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+        let habit_id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO habits (user_id, name, target_per_day) VALUES ($1, 'Water', 8) RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+        (user_id, habit_id)
+    }
+
+    /// Several timestamped check-ins on one day roll up into a single
+    /// completion, and undo takes back the latest by check-in time. Needs a
+    /// migrated database, so it only runs when DATABASE_URL is set (as in
+    /// CI); everything happens in a transaction that is rolled back.
+    #[tokio::test]
+    async fn test_checkins_roll_up_into_one_completion() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return;
+        };
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+        let (user_id, habit_id) = water_habit(&mut tx).await;
+        let day = NaiveDate::from_ymd_opt(2026, 2, 11).unwrap();
+
+        check_in(&mut tx, habit_id, user_id, day, "2026-02-11T07:00:00Z", 1).await;
+        check_in(&mut tx, habit_id, user_id, day, "2026-02-11T21:00:00Z", 1).await;
+        // Recorded last, but earlier in the day
+        check_in(&mut tx, habit_id, user_id, day, "2026-02-11T12:00:00Z", 2).await;
+        let (completion, count) = rollup_day(&mut tx, habit_id, user_id, day).await.unwrap();
+        assert_eq!((completion.unwrap().value, count), (4, 3));
+
+        let undone = undo_last_checkin(&mut tx, habit_id, day)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(undone.checked_in_at, at("2026-02-11T21:00:00Z"));
+        let (completion, count) = rollup_day(&mut tx, habit_id, user_id, day).await.unwrap();
+        assert_eq!((completion.unwrap().value, count), (3, 2));
+
+        assert_eq!(clear_day(&mut tx, habit_id, day).await.unwrap(), 2);
+        let (completion, count) = rollup_day(&mut tx, habit_id, user_id, day).await.unwrap();
+        assert!(completion.is_none());
+        assert_eq!(count, 0);
+        let rows = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM habit_completions WHERE habit_id = $1",
+        )
+        .bind(habit_id)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        assert_eq!(rows, 0);
+    }
+
+    /// Two check-ins committed concurrently both count: the habit lock makes
+    /// the second rollup wait for the first check-in. Needs a migrated
+    /// database; the rows are committed, so the user is deleted at the end.
+    #[tokio::test]
+    async fn test_concurrent_checkins_both_count() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return;
+        };
+        let mut setup = PgConnection::connect(&url).await.unwrap();
+        let (user_id, habit_id) = water_habit(&mut setup).await;
+        let day = NaiveDate::from_ymd_opt(2026, 2, 11).unwrap();
+
+        let check_in_locked = |time: &'static str| {
+            let url = url.clone();
+            async move {
+                let mut conn = PgConnection::connect(&url).await.unwrap();
+                let mut tx = conn.begin().await.unwrap();
+                lock_habit(&mut tx, habit_id).await.unwrap();
+                check_in(&mut tx, habit_id, user_id, day, time, 1).await;
+                // Give the other writer time to reach the lock
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                rollup_day(&mut tx, habit_id, user_id, day).await.unwrap();
+                tx.commit().await.unwrap();
+            }
+        };
+        tokio::join!(
+            check_in_locked("2026-02-11T07:00:00Z"),
+            check_in_locked("2026-02-11T07:00:01Z")
+        );
+
+        let value = sqlx::query_scalar::<_, i32>(
+            "SELECT value FROM habit_completions WHERE habit_id = $1 AND local_date_bucket = $2",
+        )
+        .bind(habit_id)
+        .bind(day)
+        .fetch_one(&mut setup)
+        .await
+        .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&mut setup)
+            .await
+            .unwrap();
+        assert_eq!(value, 2);
+    }
+}
@@ -0,0 +1,402 @@
+//! Mood ↔ habit correlation.
+//!
+//! For each habit, compares average mood, energy and stress on days the
+//! habit was done against days it was due but not done. Only days with a
+//! mood log count, and only from the habit's creation onwards. An effect is
+//! flagged significant when both sides have enough samples and Welch's t
+//! clears roughly the 95% level; anything weaker is reported but shouldn't
+//! be presented to users as a finding.
+//!
+//! User-defined metrics (`services::metrics`) are compared the same way, on
+//! the days they have a value; boolean metrics become the share of days
+//! answered "yes".
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use super::metrics::MetricSeries;
+use super::schedule::{effective_start, Schedule};
+use super::stats::DoneDays;
+use crate::models::custom_metric::MetricKind;
+use crate::models::habit::Habit;
+
+/// Minimum logged days on each side before an effect can be significant
+pub const MIN_SAMPLES: usize = 5;
+/// |t| above this is treated as significant (two-sided, ~p < 0.05)
+pub const T_THRESHOLD: f64 = 1.96;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MoodMetric {
+    Mood,
+    Energy,
+    Stress,
+}
+
+impl MoodMetric {
+    pub const ALL: [MoodMetric; 3] = [MoodMetric::Mood, MoodMetric::Energy, MoodMetric::Stress];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MoodMetric::Mood => "mood",
+            MoodMetric::Energy => "energy",
+            MoodMetric::Stress => "stress",
+        }
+    }
+
+    fn pick(self, log: &MoodDay) -> Option<i16> {
+        match self {
+            MoodMetric::Mood => log.mood,
+            MoodMetric::Energy => log.energy,
+            MoodMetric::Stress => log.stress,
+        }
+    }
+}
+
+/// One day's mood log scores.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct MoodDay {
+    pub date: NaiveDate,
+    pub mood: Option<i16>,
+    pub energy: Option<i16>,
+    pub stress: Option<i16>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MetricEffect {
+    /// "mood", "energy", "stress", or a custom metric's name
+    pub metric: String,
+    /// Set for custom metrics
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metric_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<MetricKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    pub done_avg: Option<f64>,
+    pub not_done_avg: Option<f64>,
+    pub done_n: usize,
+    pub not_done_n: usize,
+    /// done_avg − not_done_avg
+    pub difference: Option<f64>,
+    /// Cohen's d (pooled standard deviation)
+    pub effect_size: Option<f64>,
+    pub significant: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HabitCorrelation {
+    pub habit_id: Uuid,
+    pub habit_name: String,
+    pub effects: Vec<MetricEffect>,
+}
+
+/// Correlations for every habit over the mood days and custom metrics given.
+pub fn correlate(
+    habits: &[Habit],
+    done: &HashMap<Uuid, DoneDays>,
+    moods: &[MoodDay],
+    custom: &[MetricSeries],
+) -> Vec<HabitCorrelation> {
+    let empty = DoneDays::new();
+    habits
+        .iter()
+        .map(|habit| {
+            let schedule = Schedule::from_habit(habit);
+            let days = done.get(&habit.id).unwrap_or(&empty);
+            let counts =
+                |date: NaiveDate| effective_start(habit, date) <= date && schedule.is_due(date);
+            let relevant: Vec<&MoodDay> = moods.iter().filter(|m| counts(m.date)).collect();
+            let mood_effects = MoodMetric::ALL
+                .iter()
+                .map(|metric| {
+                    let mut on = Vec::new();
+                    let mut off = Vec::new();
+                    for m in &relevant {
+                        if let Some(score) = metric.pick(m) {
+                            if days.contains_key(&m.date) {
+                                on.push(score as f64);
+                            } else {
+                                off.push(score as f64);
+                            }
+                        }
+                    }
+                    metric_effect(metric.as_str(), &on, &off)
+                })
+                .collect::<Vec<_>>();
+            let custom_effects = custom.iter().map(|series| {
+                let mut on = Vec::new();
+                let mut off = Vec::new();
+                for (date, value) in series.values.iter().filter(|(date, _)| counts(**date)) {
+                    if days.contains_key(date) {
+                        on.push(*value);
+                    } else {
+                        off.push(*value);
+                    }
+                }
+                MetricEffect {
+                    metric_id: Some(series.metric.id),
+                    kind: Some(series.metric.kind),
+                    unit: series.metric.unit.clone(),
+                    ..metric_effect(&series.metric.name, &on, &off)
+                }
+            });
+            let effects = mood_effects.into_iter().chain(custom_effects).collect();
+            HabitCorrelation {
+                habit_id: habit.id,
+                habit_name: habit.name.clone(),
+                effects,
+            }
+        })
+        .collect()
+}
+
+pub fn metric_effect(metric: &str, on: &[f64], off: &[f64]) -> MetricEffect {
+    let (done_avg, done_var) = mean_var(on);
+    let (not_done_avg, not_done_var) = mean_var(off);
+
+    let difference = match (done_avg, not_done_avg) {
+        (Some(a), Some(b)) => Some(a - b),
+        _ => None,
+    };
+
+    let effect_size = match (difference, done_var, not_done_var) {
+        (Some(diff), Some(va), Some(vb)) => {
+            let pooled = (((on.len() - 1) as f64 * va + (off.len() - 1) as f64 * vb)
+                / (on.len() + off.len() - 2) as f64)
+                .sqrt();
+            (pooled > 0.0).then(|| diff / pooled)
+        }
+        _ => None,
+    };
+
+    let t = match (difference, done_var, not_done_var) {
+        (Some(diff), Some(va), Some(vb)) => {
+            let se = (va / on.len() as f64 + vb / off.len() as f64).sqrt();
+            if se > 0.0 {
+                Some(diff / se)
+            } else if diff != 0.0 {
+                // No spread on either side but a clear gap
+                Some(f64::INFINITY)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    let significant = on.len() >= MIN_SAMPLES
+        && off.len() >= MIN_SAMPLES
+        && t.is_some_and(|t| t.abs() >= T_THRESHOLD);
+
+    MetricEffect {
+        metric: metric.to_string(),
+        metric_id: None,
+        kind: None,
+        unit: None,
+        done_avg,
+        not_done_avg,
+        done_n: on.len(),
+        not_done_n: off.len(),
+        difference,
+        effect_size,
+        significant,
+    }
+}
+
+/// Mean and sample variance (None below two samples for the variance).
+fn mean_var(xs: &[f64]) -> (Option<f64>, Option<f64>) {
+    if xs.is_empty() {
+        return (None, None);
+    }
+    let n = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / n;
+    if xs.len() < 2 {
+        return (Some(mean), None);
+    }
+    let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (Some(mean), Some(var))
+}
+
+/// Significant effects, strongest first.
+pub fn significant_effects(
+    correlations: &[HabitCorrelation],
+) -> Vec<(&HabitCorrelation, &MetricEffect)> {
+    let mut found: Vec<(&HabitCorrelation, &MetricEffect)> = correlations
+        .iter()
+        .flat_map(|c| {
+            c.effects
+                .iter()
+                .filter(|e| e.significant)
+                .map(move |e| (c, e))
+        })
+        .collect();
+    found.sort_by(|a, b| {
+        let da = a.1.difference.unwrap_or(0.0).abs();
+        let db = b.1.difference.unwrap_or(0.0).abs();
+        db.partial_cmp(&da).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    found
+}
+
+/// One-sentence description of an effect for users.
+pub fn describe(habit: &HabitCorrelation, effect: &MetricEffect) -> String {
+    let diff = effect.difference.unwrap_or(0.0);
+    let direction = if diff >= 0.0 { "higher" } else { "lower" };
+    if effect.kind == Some(MetricKind::Boolean) {
+        return format!(
+            "On days you complete {}, {} was a yes {:.0}% of the time vs {:.0}% otherwise ({} vs {} days logged).",
+            habit.habit_name,
+            effect.metric,
+            effect.done_avg.unwrap_or(0.0) * 100.0,
+            effect.not_done_avg.unwrap_or(0.0) * 100.0,
+            effect.done_n,
+            effect.not_done_n,
+        );
+    }
+    format!(
+        "On days you complete {}, your {} averages {:.1} vs {:.1} — {:.1} {} {} ({} vs {} days logged).",
+        habit.habit_name,
+        effect.metric,
+        effect.done_avg.unwrap_or(0.0),
+        effect.not_done_avg.unwrap_or(0.0),
+        diff.abs(),
+        effect.unit.as_deref().unwrap_or("points"),
+        direction,
+        effect.done_n,
+        effect.not_done_n,
+    )
+}
+
+/// Load habits' completions and mood logs for `[start, end]` and correlate.
+pub async fn load(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    habits: &[Habit],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<HabitCorrelation>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, NaiveDate, i32)>(
+        r#"
+        SELECT habit_id, local_date_bucket, value FROM habit_completions
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let moods = sqlx::query_as::<_, MoodDay>(
+        r#"
+        SELECT local_date_bucket AS date, mood, energy, stress FROM mood_logs
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        ORDER BY local_date_bucket
+        "#,
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let custom = super::metrics::load_series(conn, user_id, start, end).await?;
+    Ok(correlate(
+        habits,
+        &super::reviews::done_by_habit(rows),
+        &moods,
+        &custom,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::habit::HabitBuilder;
+
+    #[test]
+    fn test_clear_difference_is_significant() {
+        let on = [5.0, 4.0, 5.0, 4.0, 5.0, 4.0];
+        let off = [2.0, 3.0, 2.0, 3.0, 2.0, 3.0];
+        let e = metric_effect("mood", &on, &off);
+        assert_eq!(e.difference, Some(2.0));
+        assert!(e.significant);
+        assert!(e.effect_size.unwrap() > 1.0);
+    }
+
+    #[test]
+    fn test_small_samples_never_significant() {
+        let e = metric_effect("energy", &[5.0, 5.0, 4.0], &[1.0, 1.0, 2.0]);
+        assert!(!e.significant);
+        assert_eq!(e.done_n, 3);
+    }
+
+    #[test]
+    fn test_noise_is_not_significant() {
+        let on = [3.0, 4.0, 2.0, 5.0, 3.0, 1.0];
+        let off = [4.0, 2.0, 3.0, 3.0, 5.0, 1.0];
+        assert!(!metric_effect("stress", &on, &off).significant);
+    }
+
+    #[test]
+    fn test_custom_metric_compared_on_logged_days() {
+        let habit = HabitBuilder::new(1).name("Run").build();
+        let day = |d: u32| NaiveDate::from_ymd_opt(2026, 2, d).unwrap();
+        // Ran on the 1st-6th, slept well on those days only; the 20th has no value
+        let done: DoneDays = (1..=6).map(|d| (day(d), 1)).chain([(day(20), 1)]).collect();
+        let metric: crate::models::custom_metric::CustomMetric =
+            serde_json::from_value(serde_json::json!({
+                "id": Uuid::from_u128(9),
+                "user_id": Uuid::nil(),
+                "name": "Slept well",
+                "kind": "boolean",
+                "unit": null,
+                "min_value": null,
+                "max_value": null,
+                "sort_order": 0,
+                "is_archived": false,
+                "created_at": "2026-01-01T00:00:00Z",
+                "updated_at": "2026-01-01T00:00:00Z",
+            }))
+            .unwrap();
+        let series = MetricSeries {
+            metric,
+            values: (1..=12)
+                .map(|d| (day(d), if d <= 6 { 1.0 } else { 0.0 }))
+                .collect(),
+        };
+
+        let result = correlate(
+            std::slice::from_ref(&habit),
+            &HashMap::from([(habit.id, done)]),
+            &[],
+            &[series],
+        );
+        let effect = result[0]
+            .effects
+            .iter()
+            .find(|e| e.metric == "Slept well")
+            .unwrap();
+        assert_eq!((effect.done_n, effect.not_done_n), (6, 6));
+        assert_eq!(effect.metric_id, Some(Uuid::from_u128(9)));
+        assert!(effect.significant);
+        assert_eq!(
+            describe(&result[0], effect),
+            "On days you complete Run, Slept well was a yes 100% of the time vs 0% otherwise (6 vs 6 days logged)."
+        );
+    }
+
+    #[test]
+    fn test_missing_side_has_no_difference() {
+        let e = metric_effect("mood", &[4.0, 5.0], &[]);
+        assert_eq!(e.done_avg, Some(4.5));
+        assert_eq!(e.not_done_avg, None);
+        assert_eq!(e.difference, None);
+        assert!(!e.significant);
+    }
+}
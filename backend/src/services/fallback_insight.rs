@@ -0,0 +1,675 @@
+//! Rule-based weekly insight, used when the model is unavailable or the
+//! user's tier has no AI insights.
+//!
+//! Each rule looks at the last 30 days (schedule-aware, through yesterday)
+//! and emits findings: a kind, a score in 0..=1 and a sentence. Findings
+//! are ranked by score (ties keep rule order), and the best few fill wins
+//! and improvements. The tip of the week comes from a fixed library: tips
+//! about the top improvement's rule if there are any, otherwise the general
+//! ones, rotating by ISO week so the same data doesn't repeat a tip.
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use uuid::Uuid;
+
+use super::correlations::{self, HabitCorrelation};
+use super::recovery::{self, Recovery};
+use super::reviews::{self, HabitInput};
+use super::stats::{self, DoneDays, WEEKDAY_NAMES};
+use crate::models::habit::Habit;
+use crate::models::insight::InsightContent;
+use crate::models::user::WeekStart;
+
+/// Wins and improvements kept after ranking
+pub const MAX_FINDINGS: usize = 3;
+/// Days at the end of the window compared against the rest for slumps
+const RECENT_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingKind {
+    Win,
+    Improvement,
+    MoodLink,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub kind: FindingKind,
+    /// 0..=1, higher ranks first
+    pub score: f64,
+    pub text: String,
+}
+
+/// Everything the rules read.
+pub struct FallbackInput<'a> {
+    pub habits: &'a [Habit],
+    /// Completions in `[start, end]`
+    pub done: &'a HashMap<Uuid, DoneDays>,
+    pub correlations: &'a [HabitCorrelation],
+    pub recovery: &'a HashMap<Uuid, Recovery>,
+    pub total_recovery: &'a Recovery,
+    pub start: NaiveDate,
+    /// Last settled day (yesterday)
+    pub end: NaiveDate,
+    pub week_start: WeekStart,
+}
+
+impl FallbackInput<'_> {
+    fn inputs(&self) -> Vec<HabitInput<'_>> {
+        static EMPTY: DoneDays = DoneDays::new();
+        self.habits
+            .iter()
+            .map(|h| HabitInput::new(h, self.done.get(&h.id).unwrap_or(&EMPTY)))
+            .collect()
+    }
+
+    /// Schedule-aware rate and expected completions per habit over `[start, end]`.
+    fn rates(&self, start: NaiveDate, end: NaiveDate) -> HashMap<Uuid, (f64, f64)> {
+        reviews::period_totals(&self.inputs(), start, end, self.week_start)
+            .habits
+            .into_iter()
+            .map(|t| (t.habit_id, (t.rate, t.possible)))
+            .collect()
+    }
+}
+
+type Rule = fn(&FallbackInput) -> Vec<Finding>;
+
+/// In tie-break order
+const RULES: &[Rule] = &[
+    new_personal_best,
+    recovering_streak,
+    consistent_habit,
+    never_miss_twice,
+    slumping_habit,
+    weekday_dip,
+    over_commitment,
+    double_misses,
+    mood_link,
+];
+
+/// Every rule's findings, best first.
+pub fn findings(input: &FallbackInput) -> Vec<Finding> {
+    let mut all: Vec<Finding> = RULES.iter().flat_map(|rule| rule(input)).collect();
+    // Stable sort keeps rule order on ties
+    all.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    all
+}
+
+/// A streak that is the habit's longest ever
+fn new_personal_best(input: &FallbackInput) -> Vec<Finding> {
+    input
+        .habits
+        .iter()
+        .filter(|h| h.current_streak >= 3 && h.current_streak >= h.longest_streak)
+        .map(|h| Finding {
+            rule: "new_personal_best",
+            kind: FindingKind::Win,
+            score: (0.6 + h.current_streak as f64 / 100.0).min(1.0),
+            text: format!(
+                "New personal best: {} days in a row on {}. This is the longest you've ever kept it going.",
+                h.current_streak, h.name
+            ),
+        })
+        .collect()
+}
+
+/// Back on a streak after a miss, not yet at the old best
+fn recovering_streak(input: &FallbackInput) -> Vec<Finding> {
+    input
+        .habits
+        .iter()
+        .filter(|h| {
+            h.current_streak >= 3
+                && h.current_streak < h.longest_streak
+                && input.recovery.get(&h.id).is_some_and(|r| r.misses > 0)
+        })
+        .map(|h| Finding {
+            rule: "recovering_streak",
+            kind: FindingKind::Win,
+            score: 0.4 + 0.4 * h.current_streak as f64 / h.longest_streak as f64,
+            text: format!(
+                "{} is back on a {}-day streak after a miss, {} days short of your best of {}.",
+                h.name,
+                h.current_streak,
+                h.longest_streak - h.current_streak,
+                h.longest_streak
+            ),
+        })
+        .collect()
+}
+
+/// Habits done at least 80% of the times they were due
+fn consistent_habit(input: &FallbackInput) -> Vec<Finding> {
+    let rates = input.rates(input.start, input.end);
+    input
+        .habits
+        .iter()
+        .filter_map(|h| rates.get(&h.id).map(|r| (h, *r)))
+        .filter(|(_, (rate, possible))| *rate >= 0.8 && *possible >= 7.0)
+        .map(|(h, (rate, _))| Finding {
+            rule: "consistent_habit",
+            kind: FindingKind::Win,
+            score: 0.5 * rate,
+            text: format!(
+                "{} done {:.0}% of the times it was due. That's real consistency.",
+                h.name,
+                rate * 100.0
+            ),
+        })
+        .collect()
+}
+
+/// Most single misses were followed by a completion
+fn never_miss_twice(input: &FallbackInput) -> Vec<Finding> {
+    let total = input.total_recovery;
+    match (recovery::describe(total), total.rate) {
+        (Some(summary), Some(rate)) if rate >= 0.75 && total.misses >= 2 => vec![Finding {
+            rule: "never_miss_twice",
+            kind: FindingKind::Win,
+            score: 0.45 * rate,
+            text: format!("{}. You're not letting a slip turn into a slump.", summary),
+        }],
+        _ => vec![],
+    }
+}
+
+/// Recent week well below the habit's rate before it
+fn slumping_habit(input: &FallbackInput) -> Vec<Finding> {
+    let split = input.end - Duration::days(RECENT_DAYS - 1);
+    if split <= input.start {
+        return vec![];
+    }
+    let before = input.rates(input.start, split - Duration::days(1));
+    let recent = input.rates(split, input.end);
+    input
+        .habits
+        .iter()
+        .filter_map(|h| {
+            let (prior, prior_n) = *before.get(&h.id)?;
+            let (now, now_n) = *recent.get(&h.id)?;
+            let drop = prior - now;
+            (prior_n >= 5.0 && now_n >= 3.0 && prior >= 0.6 && drop >= 0.3).then(|| Finding {
+                rule: "slumping_habit",
+                kind: FindingKind::Improvement,
+                score: 0.5 + drop / 2.0,
+                text: format!(
+                    "{} dropped to {:.0}% this week from {:.0}% before. Shrink it to a two-minute version until it's back.",
+                    h.name,
+                    now * 100.0,
+                    prior * 100.0
+                ),
+            })
+        })
+        .collect()
+}
+
+/// One weekday clearly weaker than the others
+fn weekday_dip(input: &FallbackInput) -> Vec<Finding> {
+    let rates = reviews::weekday_rates(&input.inputs(), input.start, input.end);
+    let with_data: Vec<f64> = rates.iter().flatten().copied().collect();
+    if with_data.len() < 4 {
+        return vec![];
+    }
+    let (_, Some(worst)) = stats::best_and_worst(&rates) else {
+        return vec![];
+    };
+    let worst_rate = rates[worst].unwrap_or(0.0);
+    let others = (with_data.iter().sum::<f64>() - worst_rate) / (with_data.len() - 1) as f64;
+    let gap = others - worst_rate;
+    if gap < 0.25 {
+        return vec![];
+    }
+    vec![Finding {
+        rule: "weekday_dip",
+        kind: FindingKind::Improvement,
+        score: 0.4 + gap / 2.0,
+        text: format!(
+            "{}s are your weakest day: {:.0}% done vs {:.0}% on other days. Plan a lighter routine for {}s.",
+            WEEKDAY_NAMES[worst],
+            worst_rate * 100.0,
+            others * 100.0,
+            WEEKDAY_NAMES[worst]
+        ),
+    }]
+}
+
+/// Many habits, low overall rate, several barely started
+fn over_commitment(input: &FallbackInput) -> Vec<Finding> {
+    let totals = reviews::period_totals(&input.inputs(), input.start, input.end, input.week_start);
+    let struggling = totals
+        .habits
+        .iter()
+        .filter(|t| t.possible >= 7.0 && t.rate < 0.3)
+        .count();
+    if totals.habits.len() < 4 || totals.rate >= 0.5 || struggling < 2 {
+        return vec![];
+    }
+    vec![Finding {
+        rule: "over_commitment",
+        kind: FindingKind::Improvement,
+        score: (0.6 + (0.5 - totals.rate)).min(1.0),
+        text: format!(
+            "You're tracking {} habits and completing {:.0}% of what they ask. Pausing the {} you rarely get to could help the rest stick.",
+            totals.habits.len(),
+            totals.rate * 100.0,
+            struggling
+        ),
+    }]
+}
+
+/// The habit with the most back-to-back misses
+fn double_misses(input: &FallbackInput) -> Vec<Finding> {
+    let worst = input
+        .habits
+        .iter()
+        .filter_map(|h| input.recovery.get(&h.id).map(|r| (h, r.double_misses)))
+        .filter(|(_, n)| *n > 0)
+        .max_by_key(|(_, n)| *n);
+    let Some((h, n)) = worst else { return vec![] };
+    let times = if n == 1 {
+        "once".to_string()
+    } else {
+        format!("{} times", n)
+    };
+    vec![Finding {
+        rule: "double_misses",
+        kind: FindingKind::Improvement,
+        score: (0.3 + 0.1 * n as f64).min(0.7),
+        text: format!(
+            "{} slipped into back-to-back misses {}. After a miss, make the next one tiny so you never miss twice.",
+            h.name, times
+        ),
+    }]
+}
+
+/// The strongest significant mood/metric effect
+fn mood_link(input: &FallbackInput) -> Vec<Finding> {
+    correlations::significant_effects(input.correlations)
+        .first()
+        .map(|(habit, effect)| Finding {
+            rule: "mood_link",
+            kind: FindingKind::MoodLink,
+            score: effect.effect_size.unwrap_or(0.0).abs().min(1.0),
+            text: correlations::describe(habit, effect),
+        })
+        .into_iter()
+        .collect()
+}
+
+pub struct Tip {
+    /// Rule the tip answers; None = general
+    pub rule: Option<&'static str>,
+    pub text: &'static str,
+}
+
+pub const TIPS: &[Tip] = &[
+    Tip { rule: None, text: "The best time to build a habit is right after an existing routine. This is called habit stacking." },
+    Tip { rule: None, text: "Make the cue obvious: put what you need for a habit where you'll see it." },
+    Tip { rule: None, text: "Track at the same time each day to build automaticity." },
+    Tip { rule: None, text: "Pair a habit you find hard with something you enjoy." },
+    Tip { rule: None, text: "Decide in advance: \"After I [current habit], I will [new habit].\"" },
+    Tip { rule: None, text: "Celebrate right after you finish. A small reward helps the habit stick." },
+    Tip { rule: Some("slumping_habit"), text: "When a habit slips, shrink it. Two minutes still counts and keeps the chain alive." },
+    Tip { rule: Some("slumping_habit"), text: "Look at what changed the week a habit slipped: schedule, sleep or setting." },
+    Tip { rule: Some("weekday_dip"), text: "Give your hardest weekday its own plan: a shorter version, or a fixed time." },
+    Tip { rule: Some("weekday_dip"), text: "Prepare the night before your weakest day so starting takes no decisions." },
+    Tip { rule: Some("over_commitment"), text: "Fewer habits done well beat many done sometimes. Add a new one only when the others are automatic." },
+    Tip { rule: Some("over_commitment"), text: "Archive a habit you keep skipping. You can always bring it back later." },
+    Tip { rule: Some("double_misses"), text: "Missing once is an accident; missing twice is the start of a new habit. Protect the day after a miss." },
+];
+
+/// The week's tip: one answering the top improvement's rule if any, else a
+/// general one, rotating by ISO week.
+pub fn tip_for(week: NaiveDate, top_rule: Option<&str>) -> &'static str {
+    let matching: Vec<&Tip> = TIPS
+        .iter()
+        .filter(|t| t.rule.is_some() && t.rule == top_rule)
+        .collect();
+    let pool = if matching.is_empty() {
+        TIPS.iter().filter(|t| t.rule.is_none()).collect()
+    } else {
+        matching
+    };
+    let iso = week.iso_week();
+    let index = (iso.year() as usize * 53 + iso.week() as usize) % pool.len();
+    pool[index].text
+}
+
+/// The fallback insight for the week containing `week`.
+pub fn generate(input: &FallbackInput, week: NaiveDate) -> InsightContent {
+    if input.habits.is_empty() {
+        return InsightContent {
+            summary: "You haven't created any habits yet. Start by adding a habit to track!".into(),
+            wins: vec![],
+            improvements: vec!["Create your first habit to get started".into()],
+            mood_correlation: None,
+            streak_analysis: "No data available yet.".into(),
+            tip_of_the_week:
+                "Start small: one habit, done consistently, beats five habits done sporadically."
+                    .into(),
+            source: "fallback".into(),
+        };
+    }
+
+    let found = findings(input);
+    let of_kind =
+        |kind: FindingKind| -> Vec<&Finding> { found.iter().filter(|f| f.kind == kind).collect() };
+    let wins = of_kind(FindingKind::Win);
+    let mut improvements = of_kind(FindingKind::Improvement);
+    let top_rule = improvements.first().map(|f| f.rule);
+    improvements.truncate(MAX_FINDINGS);
+
+    let mut improvement_texts: Vec<String> = improvements.iter().map(|f| f.text.clone()).collect();
+    if improvement_texts.is_empty() {
+        improvement_texts.push(
+            "Keep your current routine steady, and add a small stretch goal to one habit.".into(),
+        );
+    }
+
+    InsightContent {
+        summary: summary(input, wins.first().copied(), improvements.first().copied()),
+        wins: wins
+            .iter()
+            .take(MAX_FINDINGS)
+            .map(|f| f.text.clone())
+            .collect(),
+        improvements: improvement_texts,
+        mood_correlation: of_kind(FindingKind::MoodLink)
+            .first()
+            .map(|f| f.text.clone()),
+        streak_analysis: streak_analysis(input.habits),
+        tip_of_the_week: tip_for(week, top_rule).into(),
+        source: "fallback".into(),
+    }
+}
+
+fn summary(input: &FallbackInput, win: Option<&Finding>, improvement: Option<&Finding>) -> String {
+    let totals = reviews::period_totals(&input.inputs(), input.start, input.end, input.week_start);
+    let mut parts = vec![format!(
+        "Over the last 30 days you completed {:.0}% of your scheduled habits.",
+        totals.rate * 100.0
+    )];
+    parts.extend(win.map(|f| f.text.clone()));
+    parts.extend(improvement.map(|f| f.text.clone()));
+    parts.join(" ")
+}
+
+fn streak_analysis(habits: &[Habit]) -> String {
+    let Some(top) = habits
+        .iter()
+        .max_by_key(|h| h.current_streak)
+        .filter(|h| h.current_streak > 0)
+    else {
+        return "No active streaks. Complete a habit today to start building momentum.".into();
+    };
+    if top.current_streak > 7 {
+        format!(
+            "Your longest active streak is {} days on {}. Streaks above 7 days indicate strong habit formation.",
+            top.current_streak, top.name
+        )
+    } else {
+        format!(
+            "Your longest active streak is {} days on {}. Focus on not breaking the chain.",
+            top.current_streak, top.name
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::habit::HabitBuilder;
+    use crate::services::correlations::MetricEffect;
+
+    // 2026-01-05 is a Monday; the window is the 28 days to Sunday 2026-02-01
+    fn d(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+    const START: &str = "2026-01-05";
+    const END: &str = "2026-02-01";
+
+    fn habit(id: u128, name: &str, streak: i32, longest: i32) -> Habit {
+        HabitBuilder::new(id)
+            .name(name)
+            .streak(streak, longest)
+            .created("2025-12-01T00:00:00Z")
+            .build()
+    }
+
+    /// Days in the window for which `keep` says the habit was done
+    fn done_where(keep: impl Fn(NaiveDate) -> bool) -> DoneDays {
+        d(START)
+            .iter_days()
+            .take_while(|day| *day <= d(END))
+            .filter(|day| keep(*day))
+            .map(|day| (day, 1))
+            .collect()
+    }
+
+    struct Fixture {
+        habits: Vec<Habit>,
+        done: HashMap<Uuid, DoneDays>,
+        correlations: Vec<HabitCorrelation>,
+        recovery: HashMap<Uuid, Recovery>,
+        total: Recovery,
+    }
+
+    impl Fixture {
+        fn new(habits: Vec<(Habit, DoneDays)>) -> Self {
+            Fixture {
+                done: habits.iter().map(|(h, d)| (h.id, d.clone())).collect(),
+                habits: habits.into_iter().map(|(h, _)| h).collect(),
+                correlations: vec![],
+                recovery: HashMap::new(),
+                total: Recovery::default(),
+            }
+        }
+
+        fn input(&self) -> FallbackInput<'_> {
+            FallbackInput {
+                habits: &self.habits,
+                done: &self.done,
+                correlations: &self.correlations,
+                recovery: &self.recovery,
+                total_recovery: &self.total,
+                start: d(START),
+                end: d(END),
+                week_start: WeekStart::Monday,
+            }
+        }
+
+        fn rules(&self, rule: Rule) -> Vec<Finding> {
+            rule(&self.input())
+        }
+    }
+
+    #[test]
+    fn test_new_personal_best() {
+        let f = Fixture::new(vec![
+            (habit(1, "Read", 12, 12), DoneDays::new()),
+            (habit(2, "Run", 5, 9), DoneDays::new()),
+        ]);
+        let found = f.rules(new_personal_best);
+        assert_eq!(found.len(), 1);
+        assert!(found[0]
+            .text
+            .starts_with("New personal best: 12 days in a row on Read"));
+    }
+
+    #[test]
+    fn test_recovering_streak_needs_a_miss() {
+        let mut f = Fixture::new(vec![(habit(1, "Run", 5, 9), DoneDays::new())]);
+        assert!(f.rules(recovering_streak).is_empty());
+        f.recovery.insert(
+            Uuid::from_u128(1),
+            Recovery::from_outcomes(&[true, false, true]),
+        );
+        let found = f.rules(recovering_streak);
+        assert_eq!(
+            found[0].text,
+            "Run is back on a 5-day streak after a miss, 4 days short of your best of 9."
+        );
+    }
+
+    #[test]
+    fn test_consistent_habit_is_schedule_aware() {
+        // Due Mon/Wed/Fri only and done on each of those days: 100%, even
+        // though that is well under half the days in the window
+        let mut h = habit(1, "Gym", 0, 0);
+        h.frequency = crate::models::habit::HabitFrequency::WeeklyDays;
+        h.frequency_config = serde_json::json!({ "days": [1, 3, 5] });
+        let f = Fixture::new(vec![(
+            h,
+            done_where(|d| [0, 2, 4].contains(&d.weekday().num_days_from_monday())),
+        )]);
+        let found = f.rules(consistent_habit);
+        assert_eq!(
+            found[0].text,
+            "Gym done 100% of the times it was due. That's real consistency."
+        );
+    }
+
+    #[test]
+    fn test_never_miss_twice() {
+        let mut f = Fixture::new(vec![(habit(1, "Run", 0, 0), DoneDays::new())]);
+        f.total = Recovery::from_outcomes(&[true, false, true, false, true, false, true]);
+        assert_eq!(f.rules(never_miss_twice)[0].rule, "never_miss_twice");
+        f.total = Recovery::from_outcomes(&[true, false, false, true, false, true]);
+        assert!(f.rules(never_miss_twice).is_empty());
+    }
+
+    #[test]
+    fn test_slumping_habit() {
+        // Every day for three weeks, then twice in the last week
+        let recent = d("2026-01-26");
+        let f = Fixture::new(vec![(
+            habit(1, "Read", 0, 10),
+            done_where(|d| d <= recent || d == d_plus(recent, 3)),
+        )]);
+        let found = f.rules(slumping_habit);
+        assert_eq!(found.len(), 1);
+        assert!(found[0]
+            .text
+            .starts_with("Read dropped to 29% this week from 100% before."));
+    }
+
+    fn d_plus(day: NaiveDate, n: i64) -> NaiveDate {
+        day + Duration::days(n)
+    }
+
+    #[test]
+    fn test_weekday_dip() {
+        // Done every day except Saturdays
+        let f = Fixture::new(vec![(
+            habit(1, "Walk", 0, 0),
+            done_where(|d| d.weekday() != chrono::Weekday::Sat),
+        )]);
+        let found = f.rules(weekday_dip);
+        assert_eq!(
+            found[0].text,
+            "Saturdays are your weakest day: 0% done vs 100% on other days. Plan a lighter routine for Saturdays."
+        );
+        let steady = Fixture::new(vec![(habit(1, "Walk", 0, 0), done_where(|_| true))]);
+        assert!(steady.rules(weekday_dip).is_empty());
+    }
+
+    #[test]
+    fn test_over_commitment() {
+        let f = Fixture::new(vec![
+            (habit(1, "A", 0, 0), done_where(|_| true)),
+            (habit(2, "B", 0, 0), done_where(|d| d.day() % 7 == 0)),
+            (habit(3, "C", 0, 0), DoneDays::new()),
+            (habit(4, "D", 0, 0), DoneDays::new()),
+        ]);
+        let found = f.rules(over_commitment);
+        assert!(found[0]
+            .text
+            .starts_with("You're tracking 4 habits and completing 29%"));
+        assert!(found[0].text.contains("Pausing the 3 you rarely get to"));
+    }
+
+    #[test]
+    fn test_double_misses_picks_worst_habit() {
+        let mut f = Fixture::new(vec![
+            (habit(1, "A", 0, 0), DoneDays::new()),
+            (habit(2, "B", 0, 0), DoneDays::new()),
+        ]);
+        f.recovery.insert(
+            Uuid::from_u128(1),
+            Recovery::from_outcomes(&[true, false, false, true]),
+        );
+        f.recovery.insert(
+            Uuid::from_u128(2),
+            Recovery::from_outcomes(&[false, false, true, false, false, true]),
+        );
+        assert!(f.rules(double_misses)[0]
+            .text
+            .starts_with("B slipped into back-to-back misses 2 times."));
+    }
+
+    #[test]
+    fn test_mood_link() {
+        let mut f = Fixture::new(vec![(habit(1, "Run", 0, 0), DoneDays::new())]);
+        assert!(f.rules(mood_link).is_empty());
+        f.correlations = vec![HabitCorrelation {
+            habit_id: Uuid::from_u128(1),
+            habit_name: "Run".into(),
+            effects: vec![MetricEffect {
+                metric: "mood".into(),
+                metric_id: None,
+                kind: None,
+                unit: None,
+                done_avg: Some(4.0),
+                not_done_avg: Some(3.0),
+                done_n: 10,
+                not_done_n: 10,
+                difference: Some(1.0),
+                effect_size: Some(0.9),
+                significant: true,
+            }],
+        }];
+        let found = f.rules(mood_link);
+        assert_eq!(found[0].kind, FindingKind::MoodLink);
+        assert!(found[0]
+            .text
+            .starts_with("On days you complete Run, your mood averages 4.0 vs 3.0"));
+    }
+
+    #[test]
+    fn test_tips_rotate_weekly_and_follow_top_rule() {
+        let week = d("2026-02-02");
+        let next = d("2026-02-09");
+        assert_ne!(tip_for(week, None), tip_for(next, None));
+        assert_eq!(tip_for(week, None), tip_for(week + Duration::days(3), None));
+        let tip = tip_for(week, Some("weekday_dip"));
+        assert!(TIPS
+            .iter()
+            .any(|t| t.text == tip && t.rule == Some("weekday_dip")));
+    }
+
+    #[test]
+    fn test_generate_ranks_and_caps() {
+        let f = Fixture::new(vec![
+            (habit(1, "Read", 12, 12), done_where(|_| true)),
+            (
+                habit(2, "Walk", 0, 0),
+                done_where(|d| d.weekday() != chrono::Weekday::Sat),
+            ),
+        ]);
+        let insight = generate(&f.input(), d("2026-02-02"));
+        assert_eq!(insight.source, "fallback");
+        assert!(insight.wins[0].starts_with("New personal best"));
+        assert!(insight.wins.len() <= MAX_FINDINGS);
+        assert!(insight
+            .summary
+            .starts_with("Over the last 30 days you completed 93%"));
+        assert_eq!(insight.streak_analysis, "Your longest active streak is 12 days on Read. Streaks above 7 days indicate strong habit formation.");
+    }
+}
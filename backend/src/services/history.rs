@@ -0,0 +1,142 @@
+//! Tier limits on how far back history reads may go.
+//!
+//! Every endpoint that returns a date range of history clamps it here so the
+//! `analytics_days` and `heatmap_months` entitlements are enforced in one
+//! place. The clamp is reported back as response headers so clients can show
+//! how much was cut off without the body shape changing.
+
+use chrono::{Duration, NaiveDate};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::models::user::{SubscriptionTier, UserEntitlements};
+
+/// Days per heatmap month, as the heatmap endpoints have always counted
+pub const DAYS_PER_MONTH: i64 = 30;
+
+/// Header names; exposed through CORS in main.rs
+pub const LIMIT_DAYS_HEADER: &str = "x-history-limit-days";
+pub const START_HEADER: &str = "x-history-start";
+pub const TRUNCATED_DAYS_HEADER: &str = "x-history-truncated-days";
+
+/// Headers a handler returns alongside its body, see `HistoryWindow::headers`
+pub type HistoryHeaders = [(&'static str, String); 3];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryWindow {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Days of history the tier allows, ending today
+    pub limit_days: i64,
+    /// Requested days that fell outside the limit
+    pub truncated_days: i64,
+}
+
+impl HistoryWindow {
+    /// Clamp `[start, end]` to the last `limit_days` days ending `today`.
+    /// Days after today are dropped too. A range entirely outside the limit
+    /// comes back empty (`start > end`).
+    pub fn clamp(start: NaiveDate, end: NaiveDate, today: NaiveDate, limit_days: i64) -> Self {
+        let end = end.min(today);
+        let earliest = today - Duration::days(limit_days - 1);
+        let truncated_days = if start < earliest {
+            (earliest.min(end + Duration::days(1)) - start).num_days()
+        } else {
+            0
+        };
+        Self {
+            start: start.max(earliest),
+            end,
+            limit_days,
+            truncated_days,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start > self.end
+    }
+
+    pub fn headers(&self) -> HistoryHeaders {
+        [
+            (LIMIT_DAYS_HEADER, self.limit_days.to_string()),
+            (START_HEADER, self.start.to_string()),
+            (TRUNCATED_DAYS_HEADER, self.truncated_days.to_string()),
+        ]
+    }
+}
+
+pub async fn entitlements(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<UserEntitlements, sqlx::Error> {
+    let tier = sqlx::query_scalar::<_, SubscriptionTier>(
+        "SELECT subscription_tier FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_one(conn)
+    .await?;
+    Ok(UserEntitlements::for_tier(&tier))
+}
+
+/// `[start, end]` clamped to the user's `analytics_days`.
+pub async fn analytics_window(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+    today: NaiveDate,
+) -> Result<HistoryWindow, sqlx::Error> {
+    let limit = entitlements(conn, user_id).await?.analytics_days as i64;
+    Ok(HistoryWindow::clamp(start, end, today, limit))
+}
+
+/// The last `months` months up to `today`, clamped to `heatmap_months`.
+pub async fn heatmap_window(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    months: i32,
+    today: NaiveDate,
+) -> Result<HistoryWindow, sqlx::Error> {
+    let limit = entitlements(conn, user_id).await?.heatmap_months as i64 * DAYS_PER_MONTH;
+    let start = today - Duration::days(months.max(1) as i64 * DAYS_PER_MONTH - 1);
+    Ok(HistoryWindow::clamp(start, today, today, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_range_inside_limit_is_untouched() {
+        let w = HistoryWindow::clamp(d(25), d(31), d(31), 7);
+        assert_eq!((w.start, w.end, w.truncated_days), (d(25), d(31), 0));
+        assert_eq!(w.headers()[2].1, "0");
+    }
+
+    #[test]
+    fn test_range_before_limit_is_truncated() {
+        // Limit starts on the 25th; the 1st–24th are cut
+        let w = HistoryWindow::clamp(d(1), d(31), d(31), 7);
+        assert_eq!(w.start, d(25));
+        assert_eq!(w.truncated_days, 24);
+
+        let old = HistoryWindow::clamp(d(1), d(10), d(31), 7);
+        assert!(old.is_empty());
+        assert_eq!(old.truncated_days, 10);
+    }
+
+    #[test]
+    fn test_future_end_is_capped_at_today() {
+        let w = HistoryWindow::clamp(d(25), NaiveDate::MAX, d(31), 7);
+        assert_eq!((w.start, w.end), (d(25), d(31)));
+        assert!(HistoryWindow::clamp(NaiveDate::MAX, NaiveDate::MAX, d(31), 7).is_empty());
+        assert_eq!(
+            HistoryWindow::clamp(d(1), NaiveDate::MAX, d(31), 7).truncated_days,
+            24
+        );
+    }
+}
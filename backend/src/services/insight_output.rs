@@ -0,0 +1,286 @@
+//! Turning a model reply into an insight.
+//!
+//! Models wrap JSON in prose or code fences, drop fields and overrun
+//! lengths. `parse` pulls the first JSON object out of the reply, checks it
+//! against the insight schema and normalises it; `generate` makes one
+//! repair request when that fails. Every failure is logged as an
+//! `insight_parse_failure` metric event with its kind, so the fallback
+//! rate can be broken down by cause.
+
+use serde_json::Value;
+
+use super::llm::InsightProvider;
+use crate::models::insight::InsightContent;
+
+/// Upper bounds for model output; list items past the limit are dropped
+pub const MAX_LIST_ITEMS: usize = 5;
+pub const MAX_ITEM_CHARS: usize = 240;
+pub const MAX_TEXT_CHARS: usize = 600;
+/// Model calls per insight: the first and one repair
+const MAX_ATTEMPTS: u32 = 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum InsightFailure {
+    #[error("model call failed: {0}")]
+    Provider(String),
+
+    #[error("reply contains no JSON object")]
+    NoJson,
+
+    #[error("reply is not valid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("reply does not match the schema: {0}")]
+    Schema(String),
+
+    #[error("reply failed validation: {0}")]
+    Invalid(String),
+}
+
+impl InsightFailure {
+    /// Label for metrics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InsightFailure::Provider(_) => "provider",
+            InsightFailure::NoJson => "no_json",
+            InsightFailure::InvalidJson(_) => "invalid_json",
+            InsightFailure::Schema(_) => "schema",
+            InsightFailure::Invalid(_) => "validation",
+        }
+    }
+}
+
+/// What `generate` got out of the model: the insight or the last failure,
+/// and how many model calls completed (the repair included). Completed
+/// calls are billed by the provider even when their reply is unusable.
+#[derive(Debug)]
+pub struct Generation {
+    pub result: Result<InsightContent, InsightFailure>,
+    pub calls: u32,
+}
+
+/// Ask the model for an insight, with one repair request if the first reply
+/// can't be used. Provider errors are not retried here.
+pub async fn generate(provider: &dyn InsightProvider, prompt: &str) -> Generation {
+    let mut request = prompt.to_string();
+    let mut attempt = 1;
+    loop {
+        let reply = match provider.complete(&request).await {
+            Ok(reply) => reply,
+            Err(e) => {
+                let failure = InsightFailure::Provider(e.to_string());
+                log_failure(provider, &failure, attempt);
+                return Generation {
+                    result: Err(failure),
+                    calls: attempt - 1,
+                };
+            }
+        };
+        match parse(&reply) {
+            Ok(mut insight) => {
+                insight.source = provider.name().to_string();
+                tracing::info!(
+                    metric = "insight_generated",
+                    source = provider.name(),
+                    attempts = attempt,
+                );
+                return Generation {
+                    result: Ok(insight),
+                    calls: attempt,
+                };
+            }
+            Err(failure) => {
+                log_failure(provider, &failure, attempt);
+                if attempt >= MAX_ATTEMPTS {
+                    return Generation {
+                        result: Err(failure),
+                        calls: attempt,
+                    };
+                }
+                request = repair_prompt(prompt, &reply, &failure);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+fn log_failure(provider: &dyn InsightProvider, failure: &InsightFailure, attempt: u32) {
+    tracing::warn!(
+        metric = "insight_parse_failure",
+        kind = failure.kind(),
+        provider = provider.name(),
+        attempt = attempt,
+        error = %failure,
+    );
+}
+
+/// The original prompt plus the rejected reply and why it was rejected.
+fn repair_prompt(prompt: &str, reply: &str, failure: &InsightFailure) -> String {
+    format!(
+        "{prompt}\n\nYour previous reply could not be used ({failure}):\n{reply}\n\n\
+         Reply again with only the JSON object in the schema above: no prose, no code fences. \
+         At most {MAX_LIST_ITEMS} wins and {MAX_LIST_ITEMS} improvements, each under {MAX_ITEM_CHARS} characters."
+    )
+}
+
+/// Parse and validate a model reply.
+pub fn parse(reply: &str) -> Result<InsightContent, InsightFailure> {
+    let json = extract_json(reply).ok_or(InsightFailure::NoJson)?;
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| InsightFailure::InvalidJson(e.to_string()))?;
+    let insight: InsightContent =
+        serde_json::from_value(value).map_err(|e| InsightFailure::Schema(e.to_string()))?;
+    validate(insight)
+}
+
+/// The first balanced `{...}` in `text`, skipping braces inside strings.
+fn extract_json(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    for (i, c) in text[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..start + i + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Trim everything, drop empty list items and cap list lengths; reject
+/// missing text and overlong fields.
+fn validate(insight: InsightContent) -> Result<InsightContent, InsightFailure> {
+    let text = |field: &str, value: String| -> Result<String, InsightFailure> {
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            return Err(InsightFailure::Invalid(format!("{} is empty", field)));
+        }
+        if value.chars().count() > MAX_TEXT_CHARS {
+            return Err(InsightFailure::Invalid(format!(
+                "{} is over {} characters",
+                field, MAX_TEXT_CHARS
+            )));
+        }
+        Ok(value)
+    };
+    let list = |field: &str, items: Vec<String>| -> Result<Vec<String>, InsightFailure> {
+        let items: Vec<String> = items
+            .into_iter()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .take(MAX_LIST_ITEMS)
+            .collect();
+        if items.iter().any(|s| s.chars().count() > MAX_ITEM_CHARS) {
+            return Err(InsightFailure::Invalid(format!(
+                "{} has an item over {} characters",
+                field, MAX_ITEM_CHARS
+            )));
+        }
+        Ok(items)
+    };
+
+    let improvements = list("improvements", insight.improvements)?;
+    if improvements.is_empty() {
+        return Err(InsightFailure::Invalid("improvements is empty".into()));
+    }
+    // Models write the string "null" as often as null
+    let mood_correlation = match insight.mood_correlation.map(|s| s.trim().to_string()) {
+        Some(s) if s.is_empty() || s.eq_ignore_ascii_case("null") => None,
+        Some(s) => Some(text("mood_correlation", s)?),
+        None => None,
+    };
+    Ok(InsightContent {
+        summary: text("summary", insight.summary)?,
+        wins: list("wins", insight.wins)?,
+        improvements,
+        mood_correlation,
+        streak_analysis: text("streak_analysis", insight.streak_analysis)?,
+        tip_of_the_week: text("tip_of_the_week", insight.tip_of_the_week)?,
+        source: insight.source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm::FakeProvider;
+
+    const VALID: &str = r#"{"summary": "Good week {mostly}.", "wins": ["Ran 4 times"],
+        "improvements": ["Sleep earlier"], "mood_correlation": "null",
+        "streak_analysis": "Holding.", "tip_of_the_week": "Stack habits."}"#;
+
+    #[test]
+    fn test_extracts_json_from_prose_and_fences() {
+        let fenced = format!(
+            "Here is your insight:\n```json\n{}\n```\nHope it helps!",
+            VALID
+        );
+        let insight = parse(&fenced).unwrap();
+        assert_eq!(insight.summary, "Good week {mostly}.");
+        assert_eq!(insight.mood_correlation, None);
+    }
+
+    #[test]
+    fn test_classifies_failures() {
+        assert_eq!(parse("Sorry, I can't help").unwrap_err().kind(), "no_json");
+        assert_eq!(
+            parse("{\"summary\": \"x\",}").unwrap_err().kind(),
+            "invalid_json"
+        );
+        assert_eq!(parse("{\"summary\": \"x\"}").unwrap_err().kind(), "schema");
+        let empty_tip = VALID.replace("Stack habits.", "  ");
+        assert_eq!(parse(&empty_tip).unwrap_err().kind(), "validation");
+    }
+
+    #[test]
+    fn test_list_limits() {
+        let many: Vec<String> = (0..8).map(|i| format!("\"item {}\"", i)).collect();
+        let long = VALID.replace("[\"Ran 4 times\"]", &format!("[{}, \"\"]", many.join(",")));
+        assert_eq!(parse(&long).unwrap().wins.len(), MAX_LIST_ITEMS);
+
+        let overlong = VALID.replace("Sleep earlier", &"z".repeat(MAX_ITEM_CHARS + 1));
+        assert_eq!(parse(&overlong).unwrap_err().kind(), "validation");
+    }
+
+    #[tokio::test]
+    async fn test_one_repair_attempt() {
+        let fake = FakeProvider::sequence(vec![Ok("not json".into()), Ok(VALID.into())]);
+        let generation = generate(&fake, "prompt").await;
+        assert_eq!(generation.result.unwrap().source, "fake");
+        assert_eq!((generation.calls, fake.calls()), (2, 2));
+
+        let fake = FakeProvider::replying("still not json");
+        let generation = generate(&fake, "prompt").await;
+        assert_eq!(generation.result.unwrap_err().kind(), "no_json");
+        assert_eq!((generation.calls, fake.calls()), (2, 2));
+
+        let fake = FakeProvider::failing("timeout");
+        let generation = generate(&fake, "prompt").await;
+        assert_eq!(generation.result.unwrap_err().kind(), "provider");
+        assert_eq!(generation.calls, 0);
+        assert_eq!(fake.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_repair_still_counts_the_first_call() {
+        let fake = FakeProvider::sequence(vec![Ok("not json".into()), Err("timeout".into())]);
+        let generation = generate(&fake, "prompt").await;
+        assert_eq!(generation.result.unwrap_err().kind(), "provider");
+        assert_eq!(generation.calls, 1);
+    }
+}
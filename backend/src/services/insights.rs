@@ -0,0 +1,132 @@
+//! Stored weekly insights.
+//!
+//! One insight per user per ISO week (keyed by its Monday, whatever the
+//! user's week-start preference). Reads serve the stored row; generation
+//! writes it, and regeneration overwrites the week's row in place — unless
+//! all it produced was the fallback and the row holds a model's insight.
+
+use chrono::NaiveDate;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use super::schedule::week_start_for;
+use crate::models::insight::{Insight, InsightContent};
+use crate::models::user::WeekStart;
+
+const SELECT_INSIGHT: &str = r#"
+    SELECT id, week_start_date, source::text AS source, summary, wins, improvements,
+           mood_correlation, streak_analysis, tip_of_the_week, created_at
+    FROM insights
+"#;
+
+/// Monday of the ISO week containing `date`.
+pub fn iso_week_start(date: NaiveDate) -> NaiveDate {
+    week_start_for(date, WeekStart::Monday)
+}
+
+pub async fn for_week(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    week_start: NaiveDate,
+) -> Result<Option<Insight>, sqlx::Error> {
+    sqlx::query_as::<_, Insight>(&format!(
+        "{SELECT_INSIGHT} WHERE user_id = $1 AND week_start_date = $2"
+    ))
+    .bind(user_id)
+    .bind(week_start)
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn latest(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<Option<Insight>, sqlx::Error> {
+    sqlx::query_as::<_, Insight>(&format!(
+        "{SELECT_INSIGHT} WHERE user_id = $1 ORDER BY week_start_date DESC LIMIT 1"
+    ))
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await
+}
+
+/// The most recent `limit` weeks' insights, newest first.
+pub async fn history(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    limit: i64,
+) -> Result<Vec<Insight>, sqlx::Error> {
+    sqlx::query_as::<_, Insight>(&format!(
+        "{SELECT_INSIGHT} WHERE user_id = $1 ORDER BY week_start_date DESC LIMIT $2"
+    ))
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+}
+
+/// Store the week's insight, replacing any earlier one for that week. A
+/// fallback never replaces a model's insight; that one is returned instead.
+pub async fn store(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    week_start: NaiveDate,
+    content: &InsightContent,
+) -> Result<Insight, sqlx::Error> {
+    let stored = sqlx::query_as::<_, Insight>(
+        r#"
+        INSERT INTO insights (
+            id, user_id, week_start_date, source, summary, wins, improvements,
+            mood_correlation, streak_analysis, tip_of_the_week
+        )
+        VALUES ($1, $2, $3, $4::insight_source, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (user_id, week_start_date) DO UPDATE SET
+            source = EXCLUDED.source,
+            summary = EXCLUDED.summary,
+            wins = EXCLUDED.wins,
+            improvements = EXCLUDED.improvements,
+            mood_correlation = EXCLUDED.mood_correlation,
+            streak_analysis = EXCLUDED.streak_analysis,
+            tip_of_the_week = EXCLUDED.tip_of_the_week,
+            created_at = NOW()
+        WHERE EXCLUDED.source <> 'fallback' OR insights.source = 'fallback'
+        RETURNING id, week_start_date, source::text AS source, summary, wins, improvements,
+                  mood_correlation, streak_analysis, tip_of_the_week, created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(week_start)
+    .bind(&content.source)
+    .bind(&content.summary)
+    .bind(sqlx::types::Json(&content.wins))
+    .bind(sqlx::types::Json(&content.improvements))
+    .bind(&content.mood_correlation)
+    .bind(&content.streak_analysis)
+    .bind(&content.tip_of_the_week)
+    .fetch_optional(&mut *conn)
+    .await?;
+    match stored {
+        Some(insight) => Ok(insight),
+        None => for_week(conn, user_id, week_start)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iso_week_start_is_monday() {
+        // Sunday 2026-02-08 belongs to the ISO week starting Monday 2026-02-02
+        let sunday = NaiveDate::from_ymd_opt(2026, 2, 8).unwrap();
+        assert_eq!(
+            iso_week_start(sunday),
+            NaiveDate::from_ymd_opt(2026, 2, 2).unwrap()
+        );
+        let monday = NaiveDate::from_ymd_opt(2026, 2, 9).unwrap();
+        assert_eq!(iso_week_start(monday), monday);
+    }
+}
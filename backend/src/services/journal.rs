@@ -0,0 +1,249 @@
+//! Journal entries and reflection prompts.
+//!
+//! Prompts live here rather than in the database so their ids stay stable
+//! across deploys; entries store the id. A few prompts are offered per day,
+//! rotating through the list so consecutive days get different ones.
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::models::journal::JournalEntry;
+
+/// Prompts offered per day
+pub const PROMPTS_PER_DAY: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Prompt {
+    pub id: &'static str,
+    pub text: &'static str,
+}
+
+pub const PROMPTS: &[Prompt] = &[
+    Prompt {
+        id: "win",
+        text: "What went well today, and why?",
+    },
+    Prompt {
+        id: "obstacle",
+        text: "What got in the way of a habit today?",
+    },
+    Prompt {
+        id: "energy",
+        text: "When did you feel most energised today?",
+    },
+    Prompt {
+        id: "tomorrow",
+        text: "What is one thing you'll do differently tomorrow?",
+    },
+    Prompt {
+        id: "gratitude",
+        text: "What are you grateful for today?",
+    },
+    Prompt {
+        id: "trigger",
+        text: "What reminded you to do your habits today?",
+    },
+    Prompt {
+        id: "proud",
+        text: "What are you proud of this week?",
+    },
+    Prompt {
+        id: "easier",
+        text: "Which habit felt easier than expected, and why?",
+    },
+    Prompt {
+        id: "stress",
+        text: "What caused you stress today, and how did you respond?",
+    },
+    Prompt {
+        id: "identity",
+        text: "Who are your habits helping you become?",
+    },
+];
+
+pub fn prompt(id: &str) -> Option<&'static Prompt> {
+    PROMPTS.iter().find(|p| p.id == id)
+}
+
+/// The prompts offered on `date`.
+pub fn prompts_for(date: NaiveDate) -> Vec<&'static Prompt> {
+    let start = (date.num_days_from_ce() as usize * PROMPTS_PER_DAY) % PROMPTS.len();
+    (0..PROMPTS_PER_DAY)
+        .map(|i| &PROMPTS[(start + i) % PROMPTS.len()])
+        .collect()
+}
+
+const SELECT_ENTRY: &str = r#"
+    SELECT e.*,
+           COALESCE(ARRAY_AGG(h.habit_id) FILTER (WHERE h.habit_id IS NOT NULL), '{}') AS habit_ids
+    FROM journal_entries e
+    LEFT JOIN journal_entry_habits h ON h.entry_id = e.id
+"#;
+
+/// Entries in `[start, end]`, newest first, optionally with a tag or linked
+/// to a habit.
+pub async fn list(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+    tag: Option<&str>,
+    habit_id: Option<Uuid>,
+) -> Result<Vec<JournalEntry>, sqlx::Error> {
+    sqlx::query_as::<_, JournalEntry>(&format!(
+        r#"{SELECT_ENTRY}
+        WHERE e.user_id = $1 AND e.local_date_bucket BETWEEN $2 AND $3
+          AND ($4::text IS NULL OR e.tags @> ARRAY[$4::text])
+          AND ($5::uuid IS NULL OR EXISTS (
+              SELECT 1 FROM journal_entry_habits x WHERE x.entry_id = e.id AND x.habit_id = $5
+          ))
+        GROUP BY e.id
+        ORDER BY e.local_date_bucket DESC, e.created_at DESC
+        "#
+    ))
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .bind(tag)
+    .bind(habit_id)
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn find(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    entry_id: Uuid,
+) -> Result<Option<JournalEntry>, sqlx::Error> {
+    sqlx::query_as::<_, JournalEntry>(&format!(
+        "{SELECT_ENTRY} WHERE e.id = $1 AND e.user_id = $2 GROUP BY e.id"
+    ))
+    .bind(entry_id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Whether every id is one of the user's habits.
+pub async fn owns_habits(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    habit_ids: &[Uuid],
+) -> Result<bool, sqlx::Error> {
+    if habit_ids.is_empty() {
+        return Ok(true);
+    }
+    let owned = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM habits WHERE user_id = $1 AND id = ANY($2)",
+    )
+    .bind(user_id)
+    .bind(habit_ids)
+    .fetch_one(conn)
+    .await?;
+    Ok(owned == habit_ids.len() as i64)
+}
+
+/// Replace the entry's habit links.
+pub async fn set_habits(
+    conn: &mut PgConnection,
+    entry_id: Uuid,
+    habit_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM journal_entry_habits WHERE entry_id = $1")
+        .bind(entry_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO journal_entry_habits (entry_id, habit_id)
+        SELECT $1, UNNEST($2::uuid[])
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(entry_id)
+    .bind(habit_ids)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// One line per entry for the insights prompt, oldest first. Bodies are cut
+/// to `max_chars`.
+pub fn summarize(
+    entries: &[JournalEntry],
+    habit_name: impl Fn(Uuid) -> Option<String>,
+    max_chars: usize,
+) -> Vec<String> {
+    let mut lines: Vec<String> = entries
+        .iter()
+        .map(|e| {
+            let mut body: String = e.body.split_whitespace().collect::<Vec<_>>().join(" ");
+            if body.chars().count() > max_chars {
+                body = body.chars().take(max_chars).collect::<String>() + "…";
+            }
+            let mut meta = Vec::new();
+            if let Some(p) = e.prompt_id.as_deref().and_then(prompt) {
+                meta.push(format!("prompt: {}", p.text));
+            }
+            if !e.tags.is_empty() {
+                meta.push(format!("tags: {}", e.tags.join(", ")));
+            }
+            let habits: Vec<String> = e
+                .habit_ids
+                .iter()
+                .filter_map(|id| habit_name(*id))
+                .collect();
+            if !habits.is_empty() {
+                meta.push(format!("habits: {}", habits.join(", ")));
+            }
+            if meta.is_empty() {
+                format!("- {}: {}", e.local_date_bucket, body)
+            } else {
+                format!("- {} ({}): {}", e.local_date_bucket, meta.join("; "), body)
+            }
+        })
+        .collect();
+    lines.reverse();
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompts_rotate_daily_and_ids_are_unique() {
+        let day = NaiveDate::from_ymd_opt(2026, 2, 10).unwrap();
+        let today = prompts_for(day);
+        let tomorrow = prompts_for(day.succ_opt().unwrap());
+        assert_eq!(today.len(), PROMPTS_PER_DAY);
+        assert!(today.iter().all(|p| !tomorrow.contains(p)));
+
+        let mut ids: Vec<&str> = PROMPTS.iter().map(|p| p.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), PROMPTS.len());
+    }
+
+    #[test]
+    fn test_summarize_truncates_and_labels() {
+        let entry = JournalEntry {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            local_date_bucket: NaiveDate::from_ymd_opt(2026, 2, 10).unwrap(),
+            prompt_id: Some("win".into()),
+            body: "Ran   before\nwork and felt great all day".into(),
+            tags: vec!["sleep".into()],
+            habit_ids: vec![Uuid::from_u128(1)],
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let lines = summarize(&[entry], |_| Some("Run".into()), 14);
+        assert_eq!(
+            lines,
+            vec!["- 2026-02-10 (prompt: What went well today, and why?; tags: sleep; habits: Run): Ran before wor…"]
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Model providers for weekly insights.
+//!
+//! Handlers only see `InsightProvider`: a prompt goes in, the model's raw
+//! text comes out. Parsing that text into an insight is the caller's job.
+//! `from_config` picks the implementation once at startup, and `AppState`
+//! carries it, so tests can swap in `FakeProvider` without network access.
+//! Each provider keeps one `reqwest::Client`, so connections are pooled
+//! across calls.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use serde_json::Value;
+
+use crate::config::Config;
+
+/// B-14: no model call may hang a request indefinitely
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_TOKENS: u32 = 1024;
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+pub trait InsightProvider: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    /// Send `prompt` as a single user message and return the reply text.
+    fn complete<'a>(&'a self, prompt: &'a str) -> BoxFuture<'a, anyhow::Result<String>>;
+}
+
+/// The provider selected by `LLM_PROVIDER` (default: anthropic).
+pub fn from_config(config: &Config) -> anyhow::Result<Arc<dyn InsightProvider>> {
+    Ok(match config.llm_provider.as_str() {
+        "anthropic" => Arc::new(AnthropicProvider::new(
+            &config.claude_base_url,
+            &config.claude_api_key,
+            &config.claude_model,
+        )?),
+        "openai" => Arc::new(OpenAiCompatibleProvider::new(
+            &config.llm_base_url,
+            &config.llm_api_key,
+            &config.llm_model,
+        )?),
+        "fake" => Arc::new(FakeProvider::default()),
+        other => anyhow::bail!("Unknown LLM_PROVIDER: {}", other),
+    })
+}
+
+fn client() -> anyhow::Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?)
+}
+
+/// Send `body` as JSON and return the JSON reply, failing on non-2xx.
+async fn post_json(request: reqwest::RequestBuilder, body: Value) -> anyhow::Result<Value> {
+    let response = request.json(&body).send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("Model API error {}: {}", status, body);
+    }
+    Ok(response.json().await?)
+}
+
+/// Anthropic Messages API
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(base_url: &str, api_key: &str, model: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: client()?,
+            url: format!("{}/v1/messages", base_url.trim_end_matches('/')),
+            api_key: api_key.into(),
+            model: model.into(),
+        })
+    }
+}
+
+impl InsightProvider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn complete<'a>(&'a self, prompt: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+        Box::pin(async move {
+            let request = self
+                .client
+                .post(&self.url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION);
+            let reply = post_json(
+                request,
+                serde_json::json!({
+                    "model": self.model,
+                    "max_tokens": MAX_TOKENS,
+                    "messages": [{ "role": "user", "content": prompt }]
+                }),
+            )
+            .await?;
+            anthropic_text(&reply)
+        })
+    }
+}
+
+/// Text of the first content block of a Messages API reply.
+fn anthropic_text(reply: &Value) -> anyhow::Result<String> {
+    reply["content"][0]["text"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Model reply has no text content"))
+}
+
+/// Any server speaking the OpenAI chat completions API (hosted or local)
+pub struct OpenAiCompatibleProvider {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(base_url: &str, api_key: &str, model: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: client()?,
+            url: format!("{}/v1/chat/completions", base_url.trim_end_matches('/')),
+            api_key: api_key.into(),
+            model: model.into(),
+        })
+    }
+}
+
+impl InsightProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn complete<'a>(&'a self, prompt: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+        Box::pin(async move {
+            let mut request = self.client.post(&self.url);
+            // Local servers usually run without a key
+            if !self.api_key.is_empty() {
+                request = request.bearer_auth(&self.api_key);
+            }
+            let reply = post_json(
+                request,
+                serde_json::json!({
+                    "model": self.model,
+                    "max_tokens": MAX_TOKENS,
+                    "messages": [{ "role": "user", "content": prompt }]
+                }),
+            )
+            .await?;
+            openai_text(&reply)
+        })
+    }
+}
+
+/// Message text of the first choice of a chat completions reply.
+fn openai_text(reply: &Value) -> anyhow::Result<String> {
+    reply["choices"][0]["message"]["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Model reply has no message content"))
+}
+
+/// Returns canned replies in order, repeating the last one, and counts
+/// calls; for tests and offline development (`LLM_PROVIDER=fake`).
+pub struct FakeProvider {
+    replies: Vec<Result<String, String>>,
+    calls: AtomicUsize,
+}
+
+impl FakeProvider {
+    pub fn replying(reply: impl Into<String>) -> Self {
+        Self::sequence(vec![Ok(reply.into())])
+    }
+
+    /// A provider whose every call fails with `error`.
+    #[cfg(test)]
+    pub fn failing(error: impl Into<String>) -> Self {
+        Self::sequence(vec![Err(error.into())])
+    }
+
+    /// Successive calls get successive replies (`Err` = call fails).
+    pub fn sequence(replies: Vec<Result<String, String>>) -> Self {
+        assert!(!replies.is_empty(), "FakeProvider needs at least one reply");
+        Self {
+            replies,
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for FakeProvider {
+    /// A well-formed insight reply
+    fn default() -> Self {
+        Self::replying(
+            serde_json::json!({
+                "summary": "A steady week with your habits.",
+                "wins": ["You checked in on most days"],
+                "improvements": ["Pick one habit to do first thing in the morning"],
+                "mood_correlation": null,
+                "streak_analysis": "Your streaks are holding.",
+                "tip_of_the_week": "Pair a new habit with an existing routine."
+            })
+            .to_string(),
+        )
+    }
+}
+
+impl InsightProvider for FakeProvider {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn complete<'a>(&'a self, _prompt: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+        let call = self.calls.fetch_add(1, Ordering::Relaxed);
+        let reply = self.replies[call.min(self.replies.len() - 1)]
+            .clone()
+            .map_err(anyhow::Error::msg);
+        Box::pin(async move { reply })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+
+    /// Serve `reply` at `path` on a local port; returns the base URL.
+    async fn serve(path: &'static str, reply: Value) -> String {
+        let app = Router::new().route(path, post(move || async move { Json(reply) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_uses_configured_base_url() {
+        let base = serve(
+            "/v1/messages",
+            serde_json::json!({ "content": [{ "type": "text", "text": "hello" }] }),
+        )
+        .await;
+        let provider = AnthropicProvider::new(&format!("{}/", base), "key", "model").unwrap();
+        assert_eq!(provider.complete("hi").await.unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_openai_compatible_reads_first_choice() {
+        let base = serve(
+            "/v1/chat/completions",
+            serde_json::json!({ "choices": [{ "message": { "role": "assistant", "content": "hello" } }] }),
+        )
+        .await;
+        let provider = OpenAiCompatibleProvider::new(&base, "", "model").unwrap();
+        assert_eq!(provider.complete("hi").await.unwrap(), "hello");
+        assert!(openai_text(&serde_json::json!({ "choices": [] })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fake_is_deterministic_and_counts_calls() {
+        let fake = FakeProvider::default();
+        let first = fake.complete("a").await.unwrap();
+        assert_eq!(fake.complete("b").await.unwrap(), first);
+        assert_eq!(fake.calls(), 2);
+        assert!(serde_json::from_str::<crate::models::insight::InsightContent>(&first).is_ok());
+
+        assert!(FakeProvider::failing("down").complete("a").await.is_err());
+    }
+}
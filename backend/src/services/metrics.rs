@@ -0,0 +1,182 @@
+//! User-defined daily metrics and their values.
+//!
+//! Values are stored per metric per local day. Readers that analyse them
+//! alongside mood logs (correlations, trends) load a range at once with
+//! `load_series`.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::models::custom_metric::{CustomMetric, MetricValue};
+
+/// A metric with its values by date.
+#[derive(Debug, Clone)]
+pub struct MetricSeries {
+    pub metric: CustomMetric,
+    pub values: BTreeMap<NaiveDate, f64>,
+}
+
+pub async fn list(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    include_archived: bool,
+) -> Result<Vec<CustomMetric>, sqlx::Error> {
+    sqlx::query_as::<_, CustomMetric>(
+        r#"
+        SELECT * FROM custom_metrics
+        WHERE user_id = $1 AND ($2 OR is_archived = false)
+        ORDER BY sort_order, created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(include_archived)
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn find(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    metric_id: Uuid,
+) -> Result<Option<CustomMetric>, sqlx::Error> {
+    sqlx::query_as::<_, CustomMetric>("SELECT * FROM custom_metrics WHERE id = $1 AND user_id = $2")
+        .bind(metric_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await
+}
+
+/// Whether another of the user's metrics already has this name.
+pub async fn name_taken(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    name: &str,
+    except: Option<Uuid>,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM custom_metrics
+            WHERE user_id = $1 AND name = $2 AND ($3::uuid IS NULL OR id <> $3)
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(name)
+    .bind(except)
+    .fetch_one(conn)
+    .await
+}
+
+/// How many stored values of the metric fall outside `[min, max]`.
+pub async fn count_out_of_range(
+    conn: &mut PgConnection,
+    metric_id: Uuid,
+    min: Option<f64>,
+    max: Option<f64>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) FROM custom_metric_values
+        WHERE metric_id = $1 AND (value < $2 OR value > $3)
+        "#,
+    )
+    .bind(metric_id)
+    .bind(min)
+    .bind(max)
+    .fetch_one(conn)
+    .await
+}
+
+/// Values in `[start, end]` for the user's metrics, ordered by date.
+pub async fn values(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<MetricValue>, sqlx::Error> {
+    sqlx::query_as::<_, MetricValue>(
+        r#"
+        SELECT metric_id, local_date_bucket, value FROM custom_metric_values
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        ORDER BY local_date_bucket, metric_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn set_value(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    metric_id: Uuid,
+    date: NaiveDate,
+    value: f64,
+) -> Result<MetricValue, sqlx::Error> {
+    sqlx::query_as::<_, MetricValue>(
+        r#"
+        INSERT INTO custom_metric_values (metric_id, user_id, local_date_bucket, value)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (metric_id, local_date_bucket) DO UPDATE SET value = EXCLUDED.value
+        RETURNING metric_id, local_date_bucket, value
+        "#,
+    )
+    .bind(metric_id)
+    .bind(user_id)
+    .bind(date)
+    .bind(value)
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn delete_value(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    metric_id: Uuid,
+    date: NaiveDate,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM custom_metric_values WHERE metric_id = $1 AND user_id = $2 AND local_date_bucket = $3",
+    )
+    .bind(metric_id)
+    .bind(user_id)
+    .bind(date)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Active metrics with their values in `[start, end]`.
+pub async fn load_series(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<MetricSeries>, sqlx::Error> {
+    let metrics = list(&mut *conn, user_id, false).await?;
+    let rows = values(conn, user_id, start, end).await?;
+    Ok(group_series(metrics, rows))
+}
+
+/// Attach values to their metrics; values of metrics not listed are dropped.
+pub fn group_series(metrics: Vec<CustomMetric>, rows: Vec<MetricValue>) -> Vec<MetricSeries> {
+    let mut series: Vec<MetricSeries> = metrics
+        .into_iter()
+        .map(|metric| MetricSeries {
+            metric,
+            values: BTreeMap::new(),
+        })
+        .collect();
+    for row in rows {
+        if let Some(s) = series.iter_mut().find(|s| s.metric.id == row.metric_id) {
+            s.values.insert(row.local_date_bucket, row.value);
+        }
+    }
+    series
+}
@@ -1,3 +1,23 @@
-// Service layer placeholder for future domain logic extraction
-// Currently, business logic lives in handlers for simplicity.
-// As the codebase grows, extract complex domain operations here.
+// Service layer for domain logic shared across handlers.
+// Simple request/response logic still lives in handlers; operations that
+// several handlers (or background workers) need are extracted here.
+pub mod ai_quota;
+pub mod calendar;
+pub mod checkins;
+pub mod correlations;
+pub mod fallback_insight;
+pub mod history;
+pub mod insight_output;
+pub mod insights;
+pub mod journal;
+pub mod llm;
+pub mod metrics;
+pub mod mood;
+pub mod mood_trend;
+pub mod notifications;
+pub mod recovery;
+pub mod reviews;
+pub mod risk;
+pub mod rollups;
+pub mod schedule;
+pub mod stats;
@@ -0,0 +1,114 @@
+//! The `mood_logs` store.
+//!
+//! `/api/mood` and the older `/api/daily-logs` routes both read and write
+//! through here, so there is a single table and a single set of rules for
+//! partial updates and review-cache invalidation.
+
+use chrono::NaiveDate;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::models::mood_log::{MoodLog, MoodValues};
+
+/// Today's date in the user's timezone, the default day for a new log.
+pub async fn local_today(conn: &mut PgConnection, user_id: Uuid) -> Result<NaiveDate, sqlx::Error> {
+    sqlx::query_scalar::<_, NaiveDate>(
+        "SELECT to_user_local(NOW(), timezone)::date FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_one(conn)
+    .await
+}
+
+/// The day's log, locked for a read-modify-write. A row lock alone can't
+/// cover a day with no log yet, so concurrent writers first queue on the
+/// user's row; call inside the transaction that saves.
+pub async fn find(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    date: NaiveDate,
+) -> Result<Option<MoodLog>, sqlx::Error> {
+    sqlx::query("SELECT 1 FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query_as::<_, MoodLog>(
+        "SELECT * FROM mood_logs WHERE user_id = $1 AND local_date_bucket = $2 FOR UPDATE",
+    )
+    .bind(user_id)
+    .bind(date)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Write the day's full values (already merged, see `MoodValues::merge`).
+pub async fn save(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    date: NaiveDate,
+    values: &MoodValues,
+) -> Result<MoodLog, sqlx::Error> {
+    let log = sqlx::query_as::<_, MoodLog>(
+        r#"
+        INSERT INTO mood_logs (id, user_id, local_date_bucket, mood, energy, stress, note)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (user_id, local_date_bucket) DO UPDATE SET
+            mood = EXCLUDED.mood,
+            energy = EXCLUDED.energy,
+            stress = EXCLUDED.stress,
+            note = EXCLUDED.note
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(date)
+    .bind(values.mood)
+    .bind(values.energy)
+    .bind(values.stress)
+    .bind(&values.note)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    // Closed periods' cached reviews include mood averages
+    super::reviews::invalidate_cached(conn, user_id, date).await?;
+    Ok(log)
+}
+
+/// Logs in `[start, end]`, most recent first.
+pub async fn list(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<MoodLog>, sqlx::Error> {
+    sqlx::query_as::<_, MoodLog>(
+        r#"
+        SELECT * FROM mood_logs
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        ORDER BY local_date_bucket DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(conn)
+    .await
+}
+
+/// Remove the day's log. Returns whether one existed.
+pub async fn delete(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    date: NaiveDate,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM mood_logs WHERE user_id = $1 AND local_date_bucket = $2")
+        .bind(user_id)
+        .bind(date)
+        .execute(&mut *conn)
+        .await?;
+    if result.rows_affected() > 0 {
+        super::reviews::invalidate_cached(conn, user_id, date).await?;
+    }
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,255 @@
+//! Mood analytics over a date range.
+//!
+//! Every tracked series (mood, energy, stress and each custom metric) gets
+//! the same treatment: 7-day rolling averages, an average per weekday,
+//! volatility (sample standard deviation of the logged days) and how the
+//! current week compares with the rest of the range. Days without a value
+//! are skipped, never counted as zero.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::stats::WEEKDAY_NAMES;
+use crate::models::custom_metric::MetricKind;
+
+pub const ROLLING_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SeriesTrend {
+    /// "mood", "energy", "stress", or a custom metric's name
+    pub metric: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metric_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<MetricKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    pub days_logged: usize,
+    pub average: Option<f64>,
+    /// Sample standard deviation; None below two logged days
+    pub volatility: Option<f64>,
+    /// Average from the start of the current week through the end
+    pub current_week: Option<f64>,
+    /// Average before the current week
+    pub baseline: Option<f64>,
+    /// current_week − baseline
+    pub vs_baseline: Option<f64>,
+    /// Monday first
+    pub weekday: Vec<WeekdayAverage>,
+    pub points: Vec<TrendPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct WeekdayAverage {
+    pub weekday: String,
+    pub average: Option<f64>,
+    pub days: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TrendPoint {
+    pub date: NaiveDate,
+    pub value: Option<f64>,
+    /// Mean of the logged values in the 7 days ending here (within the range)
+    pub rolling_7d: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+pub struct LoggingStreaks {
+    /// Consecutive logged days ending today, or yesterday while today is open
+    pub current: u32,
+    pub longest: u32,
+}
+
+/// How a trend is labelled; custom metrics also carry their id, kind and unit.
+pub struct SeriesLabel {
+    pub metric: String,
+    pub metric_id: Option<Uuid>,
+    pub kind: Option<MetricKind>,
+    pub unit: Option<String>,
+}
+
+impl SeriesLabel {
+    pub fn named(metric: &str) -> Self {
+        SeriesLabel {
+            metric: metric.to_string(),
+            metric_id: None,
+            kind: None,
+            unit: None,
+        }
+    }
+}
+
+/// Trend for one series over `[start, end]`; `week_start` is the first day
+/// of the current week.
+pub fn series_trend(
+    label: SeriesLabel,
+    values: &BTreeMap<NaiveDate, f64>,
+    start: NaiveDate,
+    end: NaiveDate,
+    week_start: NaiveDate,
+) -> SeriesTrend {
+    let in_range: Vec<f64> = values.range(start..=end).map(|(_, v)| *v).collect();
+
+    let points = start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .map(|date| {
+            let from = (date - Duration::days(ROLLING_DAYS - 1)).max(start);
+            TrendPoint {
+                date,
+                value: values.get(&date).copied(),
+                rolling_7d: mean(values.range(from..=date).map(|(_, v)| *v)),
+            }
+        })
+        .collect();
+
+    let mut by_weekday: [Vec<f64>; 7] = Default::default();
+    for (date, value) in values.range(start..=end) {
+        by_weekday[date.weekday().num_days_from_monday() as usize].push(*value);
+    }
+    let weekday = by_weekday
+        .iter()
+        .enumerate()
+        .map(|(i, vals)| WeekdayAverage {
+            weekday: WEEKDAY_NAMES[i].to_string(),
+            average: mean(vals.iter().copied()),
+            days: vals.len(),
+        })
+        .collect();
+
+    let week_from = week_start.max(start);
+    let current_week = mean(values.range(week_from..=end).map(|(_, v)| *v));
+    let baseline = if week_from > start {
+        mean(values.range(start..week_from).map(|(_, v)| *v))
+    } else {
+        None
+    };
+
+    SeriesTrend {
+        metric: label.metric,
+        metric_id: label.metric_id,
+        kind: label.kind,
+        unit: label.unit,
+        days_logged: in_range.len(),
+        average: mean(in_range.iter().copied()),
+        volatility: std_dev(&in_range),
+        current_week,
+        baseline,
+        vs_baseline: current_week.zip(baseline).map(|(c, b)| c - b),
+        weekday,
+        points,
+    }
+}
+
+/// Logging streaks from the logged dates, judged as of `today`.
+pub fn logging_streaks(logged: &BTreeSet<NaiveDate>, today: NaiveDate) -> LoggingStreaks {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev: Option<NaiveDate> = None;
+    for date in logged.range(..=today) {
+        run = match prev {
+            Some(p) if *date - p == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        prev = Some(*date);
+    }
+
+    let mut day = if logged.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut current = 0;
+    while logged.contains(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+    LoggingStreaks { current, longest }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, n) = values.fold((0.0, 0usize), |(s, n), v| (s + v, n + 1));
+    (n > 0).then(|| sum / n as f64)
+}
+
+fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let m = mean(values.iter().copied())?;
+    let var = values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(var.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        // 2026-02-02 is a Monday
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    #[test]
+    fn test_rolling_weekday_and_baseline() {
+        // Mood 2 every day of the first week, 4 on Mon/Tue of the second
+        let mut values: BTreeMap<NaiveDate, f64> = (2..=8).map(|day| (d(day), 2.0)).collect();
+        values.insert(d(9), 4.0);
+        values.insert(d(10), 4.0);
+
+        let t = series_trend(SeriesLabel::named("mood"), &values, d(2), d(11), d(9));
+        assert_eq!(t.days_logged, 9);
+        assert_eq!(t.points.len(), 10);
+        assert_eq!(t.points[0].rolling_7d, Some(2.0));
+        // 4th–10th: five 2s and two 4s
+        assert_eq!(t.points[8].rolling_7d, Some(18.0 / 7.0));
+        assert_eq!(t.points[9].value, None);
+        assert_eq!(
+            (t.current_week, t.baseline, t.vs_baseline),
+            (Some(4.0), Some(2.0), Some(2.0))
+        );
+        assert_eq!(t.weekday[0].average, Some(3.0));
+        assert_eq!(t.weekday[0].days, 2);
+        assert_eq!(t.weekday[2].average, Some(2.0));
+        assert!(t.volatility.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_empty_series_has_no_stats() {
+        let t = series_trend(
+            SeriesLabel::named("stress"),
+            &BTreeMap::new(),
+            d(2),
+            d(8),
+            d(2),
+        );
+        assert_eq!((t.average, t.volatility, t.baseline), (None, None, None));
+        assert!(t.points.iter().all(|p| p.rolling_7d.is_none()));
+    }
+
+    #[test]
+    fn test_logging_streaks() {
+        let logged: BTreeSet<NaiveDate> = [2, 3, 4, 6, 7, 8, 9].into_iter().map(d).collect();
+        // Today (10th) not logged yet: the run through yesterday still counts
+        assert_eq!(
+            logging_streaks(&logged, d(10)),
+            LoggingStreaks {
+                current: 4,
+                longest: 4
+            }
+        );
+        assert_eq!(logging_streaks(&logged, d(11)).current, 0);
+        assert_eq!(
+            logging_streaks(&logged, d(4)),
+            LoggingStreaks {
+                current: 3,
+                longest: 3
+            }
+        );
+    }
+}
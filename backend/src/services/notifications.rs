@@ -0,0 +1,137 @@
+//! Notification scheduling.
+//!
+//! Producers here only enqueue rows in `notification_jobs`; delivery (push,
+//! email) is the job worker's concern. Each producer is idempotent per user
+//! and day so a restart or a slow tick never double-notifies.
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::risk;
+
+/// Local hour at which "streak at risk" nudges go out
+pub const STREAK_NUDGE_HOUR: i32 = 18;
+
+/// Hourly: for users who just reached the nudge hour locally, enqueue a
+/// `streak_at_risk` job for each streak the predictor flags.
+pub fn spawn_streak_risk_worker(db: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            match schedule_streak_nudges(&db).await {
+                Ok(count) => {
+                    if count > 0 {
+                        tracing::info!(enqueued = count, "Streak nudges: enqueued notifications");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Streak nudge worker error");
+                }
+            }
+        }
+    });
+}
+
+async fn schedule_streak_nudges(db: &PgPool) -> Result<u64, sqlx::Error> {
+    // Each user's own local date, so nudges go out for their today
+    let users = sqlx::query_as::<_, (Uuid, NaiveDate)>(
+        r#"
+        SELECT DISTINCT u.id, to_user_local(NOW(), u.timezone)::date FROM users u
+        JOIN habits h ON h.user_id = u.id
+        WHERE h.is_archived = false
+          AND h.current_streak >= $1
+          AND EXTRACT(HOUR FROM to_user_local(NOW(), u.timezone)) = $2
+        "#,
+    )
+    .bind(risk::MIN_STREAK_FOR_NUDGE)
+    .bind(STREAK_NUDGE_HOUR)
+    .fetch_all(db)
+    .await?;
+
+    let mut enqueued = 0;
+    for (user_id, today) in users {
+        let mut conn = db.acquire().await?;
+        for (habit, prediction) in risk::assess_user(&mut conn, user_id, today).await? {
+            if !prediction.streak_at_risk {
+                continue;
+            }
+            let payload = serde_json::json!({
+                "habit_id": habit.id,
+                "habit_name": habit.name,
+                "current_streak": habit.current_streak,
+                "risk_score": prediction.risk_score,
+                "date": today,
+            });
+            enqueued +=
+                enqueue_once(db, user_id, "streak_at_risk", habit.id, today, &payload).await?;
+        }
+    }
+    Ok(enqueued)
+}
+
+/// Insert a pending job unless one of the same type already exists for
+/// this habit and day.
+async fn enqueue_once(
+    db: &PgPool,
+    user_id: Uuid,
+    job_type: &str,
+    habit_id: Uuid,
+    date: NaiveDate,
+    payload: &serde_json::Value,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO notification_jobs (user_id, job_type, payload)
+        SELECT $1, $2, $3
+        WHERE NOT EXISTS (
+            SELECT 1 FROM notification_jobs
+            WHERE user_id = $1 AND job_type = $2
+              AND payload->>'habit_id' = $4::text
+              AND payload->>'date' = $5::text
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(job_type)
+    .bind(payload)
+    .bind(habit_id)
+    .bind(date)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Enqueue an `insight_ready` job for the week's insight, once per week and
+/// only if the user hasn't turned insight notifications off.
+pub async fn enqueue_insight_ready(
+    db: &PgPool,
+    user_id: Uuid,
+    insight_id: Uuid,
+    week_start: NaiveDate,
+) -> Result<u64, sqlx::Error> {
+    let payload = serde_json::json!({
+        "insight_id": insight_id,
+        "week_start_date": week_start,
+    });
+    let result = sqlx::query(
+        r#"
+        INSERT INTO notification_jobs (user_id, job_type, payload)
+        SELECT u.id, 'insight_ready', $2
+        FROM users u
+        WHERE u.id = $1 AND u.insight_notifications
+          AND NOT EXISTS (
+              SELECT 1 FROM notification_jobs
+              WHERE user_id = $1 AND job_type = 'insight_ready'
+                AND payload->>'week_start_date' = $3::text
+          )
+        "#,
+    )
+    .bind(user_id)
+    .bind(&payload)
+    .bind(week_start)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,198 @@
+//! "Never miss twice" recovery tracking.
+//!
+//! Walks a habit's due occasions in order and groups consecutive misses into
+//! runs. A run of one that ends in a completion is a recovery; a run of two
+//! or more is a double miss. A single miss at the end of the range is still
+//! open and counts as neither. Weekly-target habits are judged per week (a
+//! week that missed its quota is one miss), other schedules per due day.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::schedule::{week_start_for, Schedule};
+use super::stats::{DoneDays, HabitDays};
+use crate::models::habit::Habit;
+use crate::models::user::WeekStart;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Recovery {
+    /// Settled miss runs: recovered + double_misses
+    pub misses: u32,
+    /// Single misses followed by a completion
+    pub recovered: u32,
+    /// Runs of two or more misses
+    pub double_misses: u32,
+    /// recovered / misses; None without settled misses
+    pub rate: Option<f64>,
+}
+
+impl Recovery {
+    /// Summarise a sequence of due occasions (true = done).
+    pub fn from_outcomes(outcomes: &[bool]) -> Self {
+        let mut r = Recovery::default();
+        let mut run = 0u32;
+        for done in outcomes {
+            if !done {
+                run += 1;
+                continue;
+            }
+            match run {
+                0 => {}
+                1 => r.recovered += 1,
+                _ => r.double_misses += 1,
+            }
+            run = 0;
+        }
+        if run >= 2 {
+            r.double_misses += 1;
+        }
+        r.settle()
+    }
+
+    fn settle(mut self) -> Self {
+        self.misses = self.recovered + self.double_misses;
+        self.rate = (self.misses > 0).then(|| self.recovered as f64 / self.misses as f64);
+        self
+    }
+
+    /// Add another habit's counts.
+    pub fn merge(self, other: Recovery) -> Self {
+        Recovery {
+            recovered: self.recovered + other.recovered,
+            double_misses: self.double_misses + other.double_misses,
+            ..Recovery::default()
+        }
+        .settle()
+    }
+}
+
+/// Due occasions for a habit in `[start, end]` while it was active.
+pub fn outcomes(
+    habit: &HabitDays,
+    start: NaiveDate,
+    end: NaiveDate,
+    first: WeekStart,
+) -> Vec<bool> {
+    let start = start.max(habit.from);
+    let end = match habit.until {
+        Some(until) => end.min(until - Duration::days(1)),
+        None => end,
+    };
+    if end < start {
+        return Vec::new();
+    }
+
+    match habit.schedule {
+        // Whole weeks only, so a week in progress or cut by the range isn't a miss
+        Schedule::WeeklyTarget(times) => {
+            let mut week = week_start_for(start, first);
+            if week < start {
+                week += Duration::days(7);
+            }
+            let mut weeks = Vec::new();
+            while week + Duration::days(6) <= end {
+                let done = habit.done.range(week..=week + Duration::days(6)).count() as u32;
+                weeks.push(done >= times);
+                week += Duration::days(7);
+            }
+            weeks
+        }
+        _ => start
+            .iter_days()
+            .take_while(|d| *d <= end)
+            .filter(|d| habit.schedule.is_due(*d))
+            .map(|d| habit.done.contains_key(&d))
+            .collect(),
+    }
+}
+
+/// Recovery for a habit in `[start, end]`.
+pub fn recovery(habit: &HabitDays, start: NaiveDate, end: NaiveDate, first: WeekStart) -> Recovery {
+    Recovery::from_outcomes(&outcomes(habit, start, end, first))
+}
+
+/// Recovery per habit and combined, for habits with their completions.
+pub fn for_habits(
+    habits: &[Habit],
+    done: &HashMap<Uuid, DoneDays>,
+    start: NaiveDate,
+    end: NaiveDate,
+    first: WeekStart,
+) -> (HashMap<Uuid, Recovery>, Recovery) {
+    let empty = DoneDays::new();
+    let per_habit: HashMap<Uuid, Recovery> = habits
+        .iter()
+        .map(|h| {
+            let days = HabitDays::new(h, done.get(&h.id).unwrap_or(&empty));
+            (h.id, recovery(&days, start, end, first))
+        })
+        .collect();
+    let total = per_habit
+        .values()
+        .fold(Recovery::default(), |acc, r| acc.merge(*r));
+    (per_habit, total)
+}
+
+/// "You recovered after 4 of 5 misses", or None without settled misses.
+pub fn describe(r: &Recovery) -> Option<String> {
+    (r.misses > 0).then(|| {
+        let noun = if r.misses == 1 { "miss" } else { "misses" };
+        format!(
+            "You recovered after {} of {} {}",
+            r.recovered, r.misses, noun
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        // 2026-02-02 is a Monday
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    #[test]
+    fn test_single_misses_recover_and_runs_count_once() {
+        let r =
+            Recovery::from_outcomes(&[true, false, true, false, false, false, true, false, true]);
+        assert_eq!((r.recovered, r.double_misses, r.misses), (2, 1, 3));
+        assert_eq!(describe(&r).unwrap(), "You recovered after 2 of 3 misses");
+    }
+
+    #[test]
+    fn test_trailing_single_miss_is_open() {
+        let r = Recovery::from_outcomes(&[true, false]);
+        assert_eq!(r.misses, 0);
+        assert_eq!(r.rate, None);
+        assert_eq!(Recovery::from_outcomes(&[false, false]).double_misses, 1);
+    }
+
+    #[test]
+    fn test_weekly_target_judged_per_whole_week() {
+        // 2x/week: week 1 met, week 2 missed, week 3 met, week 4 in progress
+        let done: DoneDays = [d(2), d(4), d(9), d(16), d(17), d(23)]
+            .into_iter()
+            .map(|d| (d, 1))
+            .collect();
+        let habit = HabitDays {
+            schedule: Schedule::WeeklyTarget(2),
+            from: d(1),
+            until: None,
+            weight: 1,
+            done: &done,
+        };
+        assert_eq!(
+            outcomes(&habit, d(1), d(25), WeekStart::Monday),
+            vec![true, false, true]
+        );
+        assert_eq!(
+            recovery(&habit, d(1), d(25), WeekStart::Monday).recovered,
+            1
+        );
+    }
+}
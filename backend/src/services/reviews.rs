@@ -0,0 +1,489 @@
+//! Period reviews: week/month/year addressing, schedule-aware totals and
+//! the cache for closed monthly and yearly reviews.
+//!
+//! Weeks are addressed as ISO "YYYY-WNN". With a Sunday start the week
+//! begins the day before the ISO Monday, so the same label names the same
+//! week shifted by one day. Months are "YYYY-MM" and years "YYYY".
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use super::schedule::{effective_start, week_start_for, Schedule};
+use super::stats::{self, DoneDays};
+use crate::models::habit::Habit;
+use crate::models::user::WeekStart;
+
+/// Parse "YYYY-WNN" into the first day of that week.
+pub fn parse_week(label: &str, first: WeekStart) -> Option<NaiveDate> {
+    let (year, week) = label.split_once("-W")?;
+    let year: i32 = year.parse().ok()?;
+    let week: u32 = week.parse().ok()?;
+    let monday = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+    Some(match first {
+        WeekStart::Monday => monday,
+        WeekStart::Sunday => monday - Duration::days(1),
+    })
+}
+
+/// "YYYY-WNN" label for the week starting on `start`.
+pub fn week_label(start: NaiveDate, first: WeekStart) -> String {
+    let monday = match first {
+        WeekStart::Monday => start,
+        WeekStart::Sunday => start + Duration::days(1),
+    };
+    let iso = monday.iso_week();
+    format!("{}-W{:02}", iso.year(), iso.week())
+}
+
+/// Start of the most recent fully elapsed week before `today`.
+pub fn last_complete_week(today: NaiveDate, first: WeekStart) -> NaiveDate {
+    week_start_for(today, first) - Duration::days(7)
+}
+
+/// Parse "YYYY-MM" into the month's first and last day.
+pub fn parse_month(label: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let (year, month) = label.split_once('-')?;
+    let start = NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)?;
+    Some((start, month_end(start)))
+}
+
+/// Parse "YYYY" into the year's first and last day.
+pub fn parse_year(label: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let year: i32 = label.parse().ok()?;
+    Some((
+        NaiveDate::from_ymd_opt(year, 1, 1)?,
+        NaiveDate::from_ymd_opt(year, 12, 31)?,
+    ))
+}
+
+/// Last day of the month containing `date`.
+pub fn month_end(date: NaiveDate) -> NaiveDate {
+    let first = date.with_day(1).unwrap_or(date);
+    let next = first
+        .checked_add_months(chrono::Months::new(1))
+        .unwrap_or(first);
+    next.pred_opt().unwrap_or(date)
+}
+
+/// Month starts covering `[start, end]`.
+pub fn months_in(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let mut months = Vec::new();
+    let mut month = start.with_day(1).unwrap_or(start);
+    while month <= end {
+        months.push(month);
+        match month.checked_add_months(chrono::Months::new(1)) {
+            Some(next) => month = next,
+            None => break,
+        }
+    }
+    months
+}
+
+/// One habit's schedule and completed days, as fed to a review.
+pub struct HabitInput<'a> {
+    pub habit: &'a Habit,
+    pub schedule: Schedule,
+    pub done: &'a DoneDays,
+}
+
+impl<'a> HabitInput<'a> {
+    pub fn new(habit: &'a Habit, done: &'a DoneDays) -> Self {
+        Self {
+            habit,
+            schedule: Schedule::from_habit(habit),
+            done,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HabitTotals {
+    pub habit_id: Uuid,
+    pub completed: i64,
+    pub possible: f64,
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodTotals {
+    pub completed: i64,
+    pub possible: f64,
+    pub rate: f64,
+    pub habits: Vec<HabitTotals>,
+}
+
+/// Schedule-aware totals over `[start, end]`. Habits only count from the day
+/// they were created; habits created after `end` are left out.
+pub fn period_totals(
+    inputs: &[HabitInput],
+    start: NaiveDate,
+    end: NaiveDate,
+    first: WeekStart,
+) -> PeriodTotals {
+    let mut habits = Vec::with_capacity(inputs.len());
+    let mut completed = 0;
+    let mut possible = 0.0;
+
+    for input in inputs {
+        let from = effective_start(input.habit, start);
+        if from > end {
+            continue;
+        }
+        let done = stats::counted_completions_in(&input.schedule, from, end, input.done, first);
+        let expected = input.schedule.expected_in_range(from, end);
+        completed += done;
+        possible += expected;
+        habits.push(HabitTotals {
+            habit_id: input.habit.id,
+            completed: done,
+            possible: expected,
+            rate: ratio(done, expected),
+        });
+    }
+
+    PeriodTotals {
+        completed,
+        possible,
+        rate: ratio(completed, possible),
+        habits,
+    }
+}
+
+/// Completion rate per ISO weekday (index 0 = Monday) across `[start, end]`.
+/// Only habits with fixed days count; a weekday where no habit was scheduled
+/// is `None` so it can't be picked as a best or worst day.
+pub fn weekday_rates(inputs: &[HabitInput], start: NaiveDate, end: NaiveDate) -> [Option<f64>; 7] {
+    let mut due = [0u32; 7];
+    let mut hit = [0u32; 7];
+    for date in start.iter_days().take_while(|d| *d <= end) {
+        let i = date.weekday().num_days_from_monday() as usize;
+        let (d, h) = scheduled_on(inputs, date);
+        due[i] += d;
+        hit[i] += h;
+    }
+    let mut rates = [None; 7];
+    for i in 0..7 {
+        if due[i] > 0 {
+            rates[i] = Some(hit[i] as f64 / due[i] as f64);
+        }
+    }
+    rates
+}
+
+/// Habits with `date` as a fixed day, and how many of them were done.
+fn scheduled_on(inputs: &[HabitInput], date: NaiveDate) -> (u32, u32) {
+    let mut due = 0;
+    let mut hit = 0;
+    for input in inputs {
+        if effective_start(input.habit, date) > date || !input.schedule.is_scheduled_day(date) {
+            continue;
+        }
+        due += 1;
+        if input.done.contains_key(&date) {
+            hit += 1;
+        }
+    }
+    (due, hit)
+}
+
+/// Longest streak that falls entirely inside `[start, end]`.
+pub fn longest_streak_in(
+    input: &HabitInput,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Option<stats::StreakRange> {
+    let within: DoneDays = input
+        .done
+        .range(start..=end)
+        .map(|(d, v)| (*d, *v))
+        .collect();
+    stats::longest_streak_range(&input.schedule, &within)
+}
+
+/// Cached review payload for a closed period, if one is stored.
+pub async fn cached_review(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    kind: &str,
+    start: NaiveDate,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    sqlx::query_scalar::<_, serde_json::Value>(
+        "SELECT payload FROM period_reviews WHERE user_id = $1 AND period_kind = $2 AND period_start = $3",
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(start)
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn store_review(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    kind: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    payload: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO period_reviews (user_id, period_kind, period_start, period_end, payload)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (user_id, period_kind, period_start) DO UPDATE
+            SET period_end = EXCLUDED.period_end,
+                payload = EXCLUDED.payload,
+                generated_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(start)
+    .bind(end)
+    .bind(payload)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Drop cached reviews covering `date` after a backfill changed its data.
+pub async fn invalidate_cached(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    date: NaiveDate,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM period_reviews WHERE user_id = $1 AND period_start <= $2 AND period_end >= $2",
+    )
+    .bind(user_id)
+    .bind(date)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Drop cached reviews that end on or after `from` (all of them when None)
+/// after a change that rewrites history, such as a schedule edit.
+pub async fn invalidate_cached_from(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    from: Option<NaiveDate>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM period_reviews WHERE user_id = $1 AND ($2::date IS NULL OR period_end >= $2)",
+    )
+    .bind(user_id)
+    .bind(from)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Group completed days by habit.
+pub fn done_by_habit<I>(rows: I) -> HashMap<Uuid, DoneDays>
+where
+    I: IntoIterator<Item = (Uuid, NaiveDate, i32)>,
+{
+    let mut map: HashMap<Uuid, DoneDays> = HashMap::new();
+    for (habit_id, date, value) in rows {
+        map.entry(habit_id).or_default().insert(date, value);
+    }
+    map
+}
+
+/// The user's completions in `[start, end]`, keyed by habit.
+pub async fn load_done(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<HashMap<Uuid, DoneDays>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, NaiveDate, i32)>(
+        r#"
+        SELECT habit_id, local_date_bucket, value FROM habit_completions
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(conn)
+    .await?;
+    Ok(done_by_habit(rows))
+}
+
+fn ratio(completed: i64, possible: f64) -> f64 {
+    if possible > 0.0 {
+        (completed as f64 / possible).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::habit::{HabitBuilder, HabitFrequency};
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_week_monday_and_sunday_start() {
+        // ISO 2026-W06 runs Mon 2 Feb – Sun 8 Feb
+        assert_eq!(
+            parse_week("2026-W06", WeekStart::Monday),
+            Some(d(2026, 2, 2))
+        );
+        assert_eq!(
+            parse_week("2026-W06", WeekStart::Sunday),
+            Some(d(2026, 2, 1))
+        );
+        assert_eq!(parse_week("2026-W54", WeekStart::Monday), None);
+        assert_eq!(parse_week("2026-06", WeekStart::Monday), None);
+    }
+
+    #[test]
+    fn test_week_label_round_trips() {
+        for first in [WeekStart::Monday, WeekStart::Sunday] {
+            let start = parse_week("2026-W01", first).unwrap();
+            assert_eq!(week_label(start, first), "2026-W01");
+        }
+        // ISO week 1 of 2026 starts in 2025
+        assert_eq!(week_label(d(2025, 12, 29), WeekStart::Monday), "2026-W01");
+    }
+
+    #[test]
+    fn test_last_complete_week() {
+        // Wednesday 11 Feb 2026
+        let today = d(2026, 2, 11);
+        assert_eq!(last_complete_week(today, WeekStart::Monday), d(2026, 2, 2));
+        assert_eq!(last_complete_week(today, WeekStart::Sunday), d(2026, 2, 1));
+        // On a Sunday with Sunday start, the week that just began is excluded
+        assert_eq!(
+            last_complete_week(d(2026, 2, 8), WeekStart::Sunday),
+            d(2026, 2, 1)
+        );
+    }
+
+    fn habit(
+        id: u128,
+        frequency: HabitFrequency,
+        config: serde_json::Value,
+        created: &str,
+    ) -> Habit {
+        HabitBuilder::new(id)
+            .frequency(frequency, config)
+            .created(created)
+            .build()
+    }
+
+    fn done(dates: &[NaiveDate]) -> DoneDays {
+        dates.iter().map(|d| (*d, 1)).collect()
+    }
+
+    #[test]
+    fn test_weekday_rates_skip_days_without_scheduled_habits() {
+        let start = d(2026, 2, 2);
+        // Mon/Wed only, done on Monday
+        let h = habit(
+            1,
+            HabitFrequency::WeeklyDays,
+            serde_json::json!({"days": [1, 3]}),
+            "2026-01-01T00:00:00Z",
+        );
+        let days = done(&[start]);
+        let end = start + Duration::days(6);
+        let rates = weekday_rates(&[HabitInput::new(&h, &days)], start, end);
+        assert_eq!(rates[0], Some(1.0));
+        assert_eq!(rates[1], None);
+        assert_eq!(rates[2], Some(0.0));
+        assert_eq!(stats::best_and_worst(&rates), (Some(0), Some(2)));
+    }
+
+    #[test]
+    fn test_period_totals_prorate_new_habits() {
+        let start = d(2026, 2, 2);
+        let end = d(2026, 2, 8);
+        // Created Friday: only Fri–Sun are expected
+        let h = habit(
+            1,
+            HabitFrequency::Daily,
+            serde_json::json!({}),
+            "2026-02-06T09:00:00Z",
+        );
+        // Created after the week: excluded
+        let later = habit(
+            2,
+            HabitFrequency::Daily,
+            serde_json::json!({}),
+            "2026-02-10T09:00:00Z",
+        );
+        let days = done(&[d(2026, 2, 6), d(2026, 2, 7)]);
+        let empty = DoneDays::new();
+        let totals = period_totals(
+            &[HabitInput::new(&h, &days), HabitInput::new(&later, &empty)],
+            start,
+            end,
+            WeekStart::Monday,
+        );
+        assert_eq!(totals.habits.len(), 1);
+        assert_eq!(totals.possible, 3.0);
+        assert_eq!(totals.completed, 2);
+    }
+
+    #[test]
+    fn test_parse_month_and_year() {
+        assert_eq!(
+            parse_month("2026-02"),
+            Some((d(2026, 2, 1), d(2026, 2, 28)))
+        );
+        assert_eq!(
+            parse_month("2024-02"),
+            Some((d(2024, 2, 1), d(2024, 2, 29)))
+        );
+        assert_eq!(parse_month("2026-13"), None);
+        assert_eq!(parse_year("2026"), Some((d(2026, 1, 1), d(2026, 12, 31))));
+        assert_eq!(parse_year("twenty"), None);
+    }
+
+    #[test]
+    fn test_months_in_range() {
+        let months = months_in(d(2026, 1, 1), d(2026, 12, 31));
+        assert_eq!(months.len(), 12);
+        assert_eq!(months[11], d(2026, 12, 1));
+        assert_eq!(month_end(d(2026, 12, 15)), d(2026, 12, 31));
+    }
+
+    #[test]
+    fn test_longest_streak_in_ignores_days_outside_period() {
+        let h = habit(
+            1,
+            HabitFrequency::Daily,
+            serde_json::json!({}),
+            "2026-01-01T00:00:00Z",
+        );
+        // Jan 30 – Feb 2 is four days, but only Feb 1–2 are in February
+        let days = done(&[d(2026, 1, 30), d(2026, 1, 31), d(2026, 2, 1), d(2026, 2, 2)]);
+        let input = HabitInput::new(&h, &days);
+        let run = longest_streak_in(&input, d(2026, 2, 1), d(2026, 2, 28)).unwrap();
+        assert_eq!(run.length, 2);
+        assert_eq!(run.start, d(2026, 2, 1));
+    }
+
+    #[test]
+    fn test_done_by_habit_groups_rows() {
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let map = done_by_habit(vec![
+            (a, d(2026, 2, 2), 1),
+            (a, d(2026, 2, 3), 2),
+            (b, d(2026, 2, 2), 1),
+        ]);
+        assert_eq!(map[&a].len(), 2);
+        assert_eq!(map[&b].len(), 1);
+    }
+}
@@ -0,0 +1,250 @@
+//! "Streak at risk" prediction.
+//!
+//! A deterministic estimate of how likely each due habit is to be completed
+//! today, built from three signals in the habit's own history:
+//!
+//! - weekday pattern: how often it was done on this weekday recently
+//! - recent trend: schedule-aware rate over the last 7 days
+//! - recency: days since the last completion against the schedule's usual gap
+//!
+//! The blend is intentionally simple so the score is explainable and stable
+//! between requests; the today view and the notification scheduler share it.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use super::schedule::{effective_start, Schedule};
+use super::stats::{self, DoneDays};
+use crate::models::habit::Habit;
+
+/// Days of history the predictor looks at
+pub const HISTORY_DAYS: i64 = 56;
+/// Streaks shorter than this aren't worth a nudge
+pub const MIN_STREAK_FOR_NUDGE: i32 = 3;
+/// Risk score (0–100) at which a streak counts as at risk
+pub const AT_RISK_SCORE: u8 = 50;
+
+const WEEKDAY_WEIGHT: f64 = 0.5;
+const TREND_WEIGHT: f64 = 0.3;
+const RECENCY_WEIGHT: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HabitRisk {
+    /// Estimated chance of completing today, 0.0–1.0
+    pub probability: f64,
+    /// 100 − probability as a percentage
+    pub risk_score: u8,
+    pub level: RiskLevel,
+    /// Active streak of at least MIN_STREAK_FOR_NUDGE with a high enough risk
+    pub streak_at_risk: bool,
+    pub weekday_rate: f64,
+    pub recent_rate: f64,
+    pub days_since_last: Option<i64>,
+}
+
+/// Predict today's outcome for a habit that is due and not yet complete.
+pub fn predict(habit: &Habit, today: NaiveDate, done: &DoneDays) -> HabitRisk {
+    let schedule = Schedule::from_habit(habit);
+    let history_start = effective_start(habit, today - Duration::days(HISTORY_DAYS));
+    let yesterday = today - Duration::days(1);
+
+    // Same weekday over the window, Laplace-smoothed so new habits start at 50%
+    let mut due = 0u32;
+    let mut hit = 0u32;
+    let mut date = today - Duration::days(7);
+    while date >= history_start {
+        if schedule.is_due(date) {
+            due += 1;
+            if done.contains_key(&date) {
+                hit += 1;
+            }
+        }
+        date -= Duration::days(7);
+    }
+    let weekday_rate = (hit as f64 + 1.0) / (due as f64 + 2.0);
+
+    let trend_start = effective_start(habit, today - Duration::days(7));
+    let recent_rate = if trend_start <= yesterday {
+        stats::period_rate(&schedule, trend_start, yesterday, done).rate
+    } else {
+        weekday_rate
+    };
+
+    let last = done.range(..today).next_back().map(|(d, _)| *d);
+    let days_since_last = last.map(|d| (today - d).num_days());
+    let recency = match days_since_last {
+        // Within the usual gap counts fully; beyond it decays by the gap
+        Some(days) => {
+            let gap = usual_gap(&schedule);
+            if days as f64 <= gap {
+                1.0
+            } else {
+                (-(days as f64 - gap) / gap).exp()
+            }
+        }
+        None => 0.5,
+    };
+
+    let probability =
+        (WEEKDAY_WEIGHT * weekday_rate + TREND_WEIGHT * recent_rate + RECENCY_WEIGHT * recency)
+            .clamp(0.0, 1.0);
+    let risk_score = ((1.0 - probability) * 100.0).round() as u8;
+    let level = match risk_score {
+        0..=33 => RiskLevel::Low,
+        34..=66 => RiskLevel::Medium,
+        _ => RiskLevel::High,
+    };
+
+    HabitRisk {
+        probability,
+        risk_score,
+        level,
+        streak_at_risk: habit.current_streak >= MIN_STREAK_FOR_NUDGE && risk_score >= AT_RISK_SCORE,
+        weekday_rate,
+        recent_rate,
+        days_since_last,
+    }
+}
+
+/// Typical days between completions the schedule asks for.
+fn usual_gap(schedule: &Schedule) -> f64 {
+    match schedule {
+        Schedule::Daily => 1.0,
+        Schedule::WeeklyDays(_) | Schedule::WeeklyTarget(_) => {
+            7.0 / schedule.weekly_target().max(1) as f64
+        }
+    }
+}
+
+/// The user's completions over the predictor's window, keyed by habit.
+pub async fn load_history(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    today: NaiveDate,
+) -> Result<HashMap<Uuid, DoneDays>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, NaiveDate, i32)>(
+        r#"
+        SELECT habit_id, local_date_bucket, value FROM habit_completions
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(today - Duration::days(HISTORY_DAYS))
+    .bind(today)
+    .fetch_all(conn)
+    .await?;
+    Ok(super::reviews::done_by_habit(rows))
+}
+
+/// Risk for each of the user's due, unfinished habits today.
+pub async fn assess_user(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    today: NaiveDate,
+) -> Result<Vec<(Habit, HabitRisk)>, sqlx::Error> {
+    let habits = sqlx::query_as::<_, Habit>(
+        "SELECT * FROM habits WHERE user_id = $1 AND is_archived = false",
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let history = load_history(conn, user_id, today).await?;
+    let empty = DoneDays::new();
+
+    Ok(habits
+        .into_iter()
+        .filter_map(|habit| {
+            let done = history.get(&habit.id).unwrap_or(&empty);
+            if !Schedule::from_habit(&habit).is_due(today)
+                || done.get(&today).is_some_and(|v| *v >= habit.target_per_day)
+            {
+                return None;
+            }
+            let risk = predict(&habit, today, done);
+            Some((habit, risk))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::habit::HabitBuilder;
+    use chrono::Datelike;
+
+    fn habit(streak: i32) -> Habit {
+        habit_created(streak, "2025-01-01T00:00:00Z")
+    }
+
+    fn habit_created(streak: i32, created: &str) -> Habit {
+        HabitBuilder::new(0)
+            .streak(streak, streak)
+            .created(created)
+            .build()
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, 11).unwrap()
+    }
+
+    #[test]
+    fn test_consistent_habit_is_low_risk() {
+        let done: DoneDays = (1..=HISTORY_DAYS)
+            .map(|i| (today() - Duration::days(i), 1))
+            .collect();
+        let risk = predict(&habit(56), today(), &done);
+        assert_eq!(risk.level, RiskLevel::Low);
+        assert!(!risk.streak_at_risk);
+        assert_eq!(risk.days_since_last, Some(1));
+    }
+
+    #[test]
+    fn test_habit_skipped_on_this_weekday_is_at_risk() {
+        // Done every day except this weekday, and not for the last two days
+        let done: DoneDays = (3..=HISTORY_DAYS)
+            .map(|i| today() - Duration::days(i))
+            .filter(|d| d.weekday() != today().weekday())
+            .map(|d| (d, 1))
+            .collect();
+        let risk = predict(&habit(4), today(), &done);
+        assert!(
+            risk.risk_score >= AT_RISK_SCORE,
+            "score {}",
+            risk.risk_score
+        );
+        assert!(risk.streak_at_risk);
+    }
+
+    #[test]
+    fn test_new_habit_without_history_is_medium() {
+        let risk = predict(
+            &habit_created(0, "2026-02-11T07:00:00Z"),
+            today(),
+            &DoneDays::new(),
+        );
+        assert_eq!(risk.days_since_last, None);
+        assert_eq!(risk.level, RiskLevel::Medium);
+        assert!(!risk.streak_at_risk);
+    }
+
+    #[test]
+    fn test_prediction_is_deterministic() {
+        let done: DoneDays = [(today() - Duration::days(2), 1)].into_iter().collect();
+        assert_eq!(
+            predict(&habit(1), today(), &done),
+            predict(&habit(1), today(), &done)
+        );
+    }
+}
@@ -0,0 +1,390 @@
+//! Per-user daily rollups (`user_daily_rollups`).
+//!
+//! Rows hold `stats::daily_tallies` output plus raw completion totals. They
+//! are refreshed inside completion write transactions, invalidated by habit
+//! edits that rewrite history, filled on demand by readers, and rebuilt for
+//! recent days by a background job.
+
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::reviews::done_by_habit;
+use super::schedule::week_start_for;
+use super::stats::{self, DoneDays, HabitDays};
+use crate::models::habit::Habit;
+use crate::models::user::WeekStart;
+
+/// Days the background job recomputes for recently active users
+pub const REBUILD_DAYS: i64 = 35;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, PartialEq)]
+pub struct DailyRollup {
+    #[sqlx(rename = "local_date_bucket")]
+    pub date: NaiveDate,
+    pub due_count: i32,
+    pub completed_count: i32,
+    pub completion_count: i32,
+    pub total_value: i64,
+    /// Weighted due/completed sums behind the daily score
+    pub due_weight: i32,
+    pub completed_weight: i32,
+}
+
+impl DailyRollup {
+    /// Share of due habits completed; None when nothing was due
+    pub fn rate(&self) -> Option<f64> {
+        (self.due_count > 0).then(|| self.completed_count as f64 / self.due_count as f64)
+    }
+
+    /// Weighted score, 0–100; None when nothing was due
+    pub fn score(&self) -> Option<f64> {
+        stats::score(self.completed_weight as i64, self.due_weight as i64)
+    }
+}
+
+/// Weighted score across several days' rollups
+pub fn total_score<'a>(rollups: impl IntoIterator<Item = &'a DailyRollup>) -> Option<f64> {
+    let (done, due) = rollups.into_iter().fold((0, 0), |(c, d), r| {
+        (c + r.completed_weight as i64, d + r.due_weight as i64)
+    });
+    stats::score(done, due)
+}
+
+/// Due and completed habit-days summed over several days' rollups
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Totals {
+    pub completed: i64,
+    pub due: i64,
+    /// completed / due; 0 when nothing was due
+    pub rate: f64,
+}
+
+pub fn totals<'a>(rollups: impl IntoIterator<Item = &'a DailyRollup>) -> Totals {
+    let (completed, due) = rollups.into_iter().fold((0, 0), |(c, d), r| {
+        (c + r.completed_count as i64, d + r.due_count as i64)
+    });
+    let rate = if due > 0 {
+        completed as f64 / due as f64
+    } else {
+        0.0
+    };
+    Totals {
+        completed,
+        due,
+        rate,
+    }
+}
+
+/// Compute rollups for `[start, end]` from habits and completions.
+pub async fn compute(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<DailyRollup>, sqlx::Error> {
+    let week_start_day =
+        sqlx::query_scalar::<_, WeekStart>("SELECT week_start FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+    // Archived habits still count for the dates they were active
+    let habits = sqlx::query_as::<_, Habit>("SELECT * FROM habits WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    // Weekly targets need the earlier days of the first week too
+    let rows = sqlx::query_as::<_, (Uuid, NaiveDate, i32)>(
+        r#"
+        SELECT habit_id, local_date_bucket, value FROM habit_completions
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(week_start_for(start, week_start_day))
+    .bind(end)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(build(
+        &habits,
+        &done_by_habit(rows),
+        start,
+        end,
+        week_start_day,
+    ))
+}
+
+fn build(
+    habits: &[Habit],
+    done: &std::collections::HashMap<Uuid, DoneDays>,
+    start: NaiveDate,
+    end: NaiveDate,
+    first: WeekStart,
+) -> Vec<DailyRollup> {
+    let empty = DoneDays::new();
+    let spans: Vec<HabitDays> = habits
+        .iter()
+        .map(|h| HabitDays::new(h, done.get(&h.id).unwrap_or(&empty)))
+        .collect();
+
+    stats::daily_tallies(&spans, start, end, first)
+        .into_iter()
+        .map(|t| {
+            let values: Vec<i32> = done
+                .values()
+                .filter_map(|d| d.get(&t.date).copied())
+                .collect();
+            DailyRollup {
+                date: t.date,
+                due_count: t.due as i32,
+                completed_count: t.completed as i32,
+                completion_count: values.len() as i32,
+                total_value: values.iter().map(|v| *v as i64).sum(),
+                due_weight: t.due_weight as i32,
+                completed_weight: t.completed_weight as i32,
+            }
+        })
+        .collect()
+}
+
+/// Recompute and store rollups for `[start, end]`.
+pub async fn refresh(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<DailyRollup>, sqlx::Error> {
+    let rollups = compute(conn, user_id, start, end).await?;
+    if rollups.is_empty() {
+        return Ok(rollups);
+    }
+
+    let dates: Vec<NaiveDate> = rollups.iter().map(|r| r.date).collect();
+    let due: Vec<i32> = rollups.iter().map(|r| r.due_count).collect();
+    let completed: Vec<i32> = rollups.iter().map(|r| r.completed_count).collect();
+    let count: Vec<i32> = rollups.iter().map(|r| r.completion_count).collect();
+    let value: Vec<i64> = rollups.iter().map(|r| r.total_value).collect();
+    let due_weight: Vec<i32> = rollups.iter().map(|r| r.due_weight).collect();
+    let completed_weight: Vec<i32> = rollups.iter().map(|r| r.completed_weight).collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_daily_rollups
+            (user_id, local_date_bucket, due_count, completed_count, completion_count, total_value,
+             due_weight, completed_weight)
+        SELECT $1::uuid, * FROM UNNEST(
+            $2::date[], $3::int[], $4::int[], $5::int[], $6::bigint[], $7::int[], $8::int[]
+        )
+        ON CONFLICT (user_id, local_date_bucket) DO UPDATE
+            SET due_count = EXCLUDED.due_count,
+                completed_count = EXCLUDED.completed_count,
+                completion_count = EXCLUDED.completion_count,
+                total_value = EXCLUDED.total_value,
+                due_weight = EXCLUDED.due_weight,
+                completed_weight = EXCLUDED.completed_weight,
+                updated_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(&dates)
+    .bind(&due)
+    .bind(&completed)
+    .bind(&count)
+    .bind(&value)
+    .bind(&due_weight)
+    .bind(&completed_weight)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(rollups)
+}
+
+/// Refresh after a completion on `date` changed. The following six days are
+/// included because weekly-target misses depend on earlier days of the week.
+pub async fn refresh_after_write(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    date: NaiveDate,
+) -> Result<(), sqlx::Error> {
+    let today = super::mood::local_today(&mut *conn, user_id).await?;
+    let end = (date + Duration::days(6)).min(today).max(date);
+    refresh(conn, user_id, date, end).await?;
+    Ok(())
+}
+
+/// Drop rollups from `from` onwards (all of them for `None`) after a change
+/// that rewrites history, such as a schedule edit or a deleted habit.
+pub async fn invalidate_from(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    from: Option<NaiveDate>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM user_daily_rollups WHERE user_id = $1 AND ($2::date IS NULL OR local_date_bucket >= $2)",
+    )
+    .bind(user_id)
+    .bind(from)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Rollups for `[start, end]`, computing and storing any missing days.
+/// Days the user hasn't reached yet are never built: `end` is capped at
+/// their local today.
+pub async fn read_range(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<DailyRollup>, sqlx::Error> {
+    let end = end.min(super::mood::local_today(&mut *conn, user_id).await?);
+    if start > end {
+        return Ok(Vec::new());
+    }
+    let rows = select_range(conn, user_id, start, end).await?;
+    let expected = (end - start).num_days() + 1;
+    if rows.len() as i64 == expected {
+        return Ok(rows);
+    }
+
+    // Fill the span between the first and last missing day in one pass
+    let mut missing = start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .filter(|d| rows.binary_search_by_key(d, |r| r.date).is_err());
+    let Some(first) = missing.next() else {
+        return Ok(rows);
+    };
+    let last = missing.last().unwrap_or(first);
+    refresh(conn, user_id, first, last).await?;
+
+    select_range(conn, user_id, start, end).await
+}
+
+async fn select_range(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<DailyRollup>, sqlx::Error> {
+    sqlx::query_as::<_, DailyRollup>(
+        r#"
+        SELECT local_date_bucket, due_count, completed_count, completion_count, total_value,
+               due_weight, completed_weight
+        FROM user_daily_rollups
+        WHERE user_id = $1 AND local_date_bucket BETWEEN $2 AND $3
+        ORDER BY local_date_bucket
+        "#,
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(conn)
+    .await
+}
+
+/// Daily: rebuild the last REBUILD_DAYS of rollups for recently active users.
+pub fn spawn_rollup_worker(db: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(24 * 3600));
+        loop {
+            interval.tick().await;
+            match rebuild_recent(&db).await {
+                Ok(users) => {
+                    if users > 0 {
+                        tracing::info!(users, "Rollup rebuild: refreshed recent days");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Rollup rebuild worker error");
+                }
+            }
+        }
+    });
+}
+
+async fn rebuild_recent(db: &PgPool) -> Result<usize, sqlx::Error> {
+    // Any user's local today is at most a day from the server's
+    let start = Utc::now().date_naive() - Duration::days(REBUILD_DAYS);
+    let users = sqlx::query_scalar::<_, Uuid>(
+        "SELECT DISTINCT user_id FROM habit_completions WHERE local_date_bucket >= $1",
+    )
+    .bind(start)
+    .fetch_all(db)
+    .await?;
+
+    for user_id in &users {
+        let mut tx = db.begin().await?;
+        let today = super::mood::local_today(&mut tx, *user_id).await?;
+        refresh(
+            &mut tx,
+            *user_id,
+            today - Duration::days(REBUILD_DAYS - 1),
+            today,
+        )
+        .await?;
+        tx.commit().await?;
+    }
+    Ok(users.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::habit::HabitBuilder;
+
+    fn habit(id: u128, created: &str) -> Habit {
+        HabitBuilder::new(id).created(created).build()
+    }
+
+    #[test]
+    fn test_build_counts_values_and_due_habits() {
+        let day = NaiveDate::from_ymd_opt(2026, 2, 10).unwrap();
+        let a = habit(1, "2026-01-01T00:00:00Z");
+        let b = habit(2, "2026-01-01T00:00:00Z");
+        let done = done_by_habit(vec![(a.id, day, 3)]);
+        let rollups = build(&[a, b], &done, day, day, WeekStart::Monday);
+        assert_eq!(
+            rollups,
+            vec![DailyRollup {
+                date: day,
+                due_count: 2,
+                completed_count: 1,
+                completion_count: 1,
+                total_value: 3,
+                due_weight: 8,
+                completed_weight: 4,
+            }]
+        );
+        assert_eq!(rollups[0].rate(), Some(0.5));
+        assert_eq!(rollups[0].score(), Some(50.0));
+    }
+
+    #[test]
+    fn test_totals_sum_days() {
+        let day = |due, completed| DailyRollup {
+            date: NaiveDate::from_ymd_opt(2026, 2, 10).unwrap(),
+            due_count: due,
+            completed_count: completed,
+            completion_count: completed,
+            total_value: completed as i64,
+            due_weight: due * 4,
+            completed_weight: completed * 4,
+        };
+        let rollups = [day(2, 1), day(3, 3), day(0, 0)];
+        assert_eq!(
+            totals(&rollups),
+            Totals {
+                completed: 4,
+                due: 5,
+                rate: 0.8
+            }
+        );
+        assert_eq!(totals(&[]), Totals::default());
+    }
+}
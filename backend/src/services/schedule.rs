@@ -0,0 +1,115 @@
+//! Habit schedules: which dates a habit is due on and how many completions
+//! a date range can be expected to hold.
+//!
+//! `frequency_config` is JSONB, so it is parsed once here instead of in every
+//! handler. Malformed configs degrade to a daily schedule, matching the
+//! behaviour of the today view.
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::models::habit::{Habit, HabitFrequency};
+use crate::models::user::WeekStart;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Daily,
+    /// Due on the flagged ISO weekdays (index 0 = Monday)
+    WeeklyDays([bool; 7]),
+    /// N completions per ISO week, on any days
+    WeeklyTarget(u32),
+}
+
+impl Schedule {
+    pub fn from_habit(habit: &Habit) -> Self {
+        match habit.frequency {
+            HabitFrequency::Daily => Schedule::Daily,
+            HabitFrequency::WeeklyDays => {
+                // frequency_config: { "days": [1, 3, 5] } where 1=Mon, 7=Sun (ISO 8601)
+                let mut days = [false; 7];
+                if let Some(list) = habit
+                    .frequency_config
+                    .get("days")
+                    .and_then(|d| d.as_array())
+                {
+                    for d in list.iter().filter_map(|d| d.as_i64()) {
+                        if (1..=7).contains(&d) {
+                            days[(d - 1) as usize] = true;
+                        }
+                    }
+                }
+                if days.iter().any(|d| *d) {
+                    Schedule::WeeklyDays(days)
+                } else {
+                    Schedule::Daily // malformed config → treat as daily
+                }
+            }
+            HabitFrequency::WeeklyTarget => {
+                let times = habit
+                    .frequency_config
+                    .get("times_per_week")
+                    .and_then(|t| t.as_i64())
+                    .unwrap_or(1)
+                    .clamp(1, 7);
+                Schedule::WeeklyTarget(times as u32)
+            }
+        }
+    }
+
+    /// Whether the habit is due on `date`. Weekly-target habits are always
+    /// "due" — the user picks which days.
+    pub fn is_due(&self, date: NaiveDate) -> bool {
+        match self {
+            Schedule::Daily | Schedule::WeeklyTarget(_) => true,
+            Schedule::WeeklyDays(days) => days[date.weekday().num_days_from_monday() as usize],
+        }
+    }
+
+    /// Whether `date` is one of the habit's fixed days. Weekly-target habits
+    /// have no fixed days, only a weekly quota.
+    pub fn is_scheduled_day(&self, date: NaiveDate) -> bool {
+        !matches!(self, Schedule::WeeklyTarget(_)) && self.is_due(date)
+    }
+
+    /// Completions the schedule expects across `[start, end]`. Weekly targets
+    /// are prorated for partial weeks.
+    pub fn expected_in_range(&self, start: NaiveDate, end: NaiveDate) -> f64 {
+        if end < start {
+            return 0.0;
+        }
+        match self {
+            Schedule::WeeklyTarget(times) => {
+                let days = (end - start).num_days() + 1;
+                *times as f64 * days as f64 / 7.0
+            }
+            _ => start
+                .iter_days()
+                .take_while(|d| *d <= end)
+                .filter(|d| self.is_due(*d))
+                .count() as f64,
+        }
+    }
+
+    /// Completions expected across a whole ISO week (not prorated).
+    pub fn weekly_target(&self) -> i32 {
+        match self {
+            Schedule::Daily => 7,
+            Schedule::WeeklyDays(days) => days.iter().filter(|d| **d).count() as i32,
+            Schedule::WeeklyTarget(times) => *times as i32,
+        }
+    }
+}
+
+/// First day of the week containing `date` under the user's week start.
+pub fn week_start_for(date: NaiveDate, first: WeekStart) -> NaiveDate {
+    let offset = match first {
+        WeekStart::Monday => date.weekday().num_days_from_monday(),
+        WeekStart::Sunday => date.weekday().num_days_from_sunday(),
+    };
+    date - chrono::Duration::days(offset as i64)
+}
+
+/// First date a habit can count toward stats: its creation day or `start`,
+/// whichever is later.
+pub fn effective_start(habit: &Habit, start: NaiveDate) -> NaiveDate {
+    start.max(habit.created_at.date_naive())
+}
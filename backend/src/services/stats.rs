@@ -0,0 +1,574 @@
+//! Schedule-aware per-habit statistics.
+//!
+//! Pure functions over a habit's `Schedule` and its completed days, so the
+//! numbers can be unit-tested without a database. A completion only counts
+//! toward a rate on days the schedule expects one; weekly targets count at
+//! most `times_per_week` completions per ISO week.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::schedule::{week_start_for, Schedule};
+use crate::models::habit::Habit;
+use crate::models::user::WeekStart;
+
+pub const WEEKDAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// Completed days keyed by date, valued by the day's completion value.
+pub type DoneDays = BTreeMap<NaiveDate, i32>;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PeriodRate {
+    pub days: i64,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub completed: i64,
+    pub expected: f64,
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StreakRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub length: i32,
+}
+
+/// Completions that count toward the schedule within `[start, end]`.
+pub fn counted_completions(
+    schedule: &Schedule,
+    start: NaiveDate,
+    end: NaiveDate,
+    done: &DoneDays,
+) -> i64 {
+    counted_completions_in(schedule, start, end, done, WeekStart::Monday)
+}
+
+/// As `counted_completions`, capping weekly targets per week as the user
+/// defines it.
+pub fn counted_completions_in(
+    schedule: &Schedule,
+    start: NaiveDate,
+    end: NaiveDate,
+    done: &DoneDays,
+    first: WeekStart,
+) -> i64 {
+    if end < start {
+        return 0;
+    }
+    match schedule {
+        Schedule::WeeklyTarget(times) => {
+            let mut per_week: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+            for date in done.range(start..=end).map(|(d, _)| *d) {
+                *per_week.entry(week_start_for(date, first)).or_default() += 1;
+            }
+            per_week.values().map(|n| (*n).min(*times as i64)).sum()
+        }
+        _ => done
+            .range(start..=end)
+            .filter(|(d, _)| schedule.is_due(**d))
+            .count() as i64,
+    }
+}
+
+/// Schedule-aware completion rate over `[start, end]`, capped at 1.0.
+pub fn period_rate(
+    schedule: &Schedule,
+    start: NaiveDate,
+    end: NaiveDate,
+    done: &DoneDays,
+) -> PeriodRate {
+    let expected = schedule.expected_in_range(start, end);
+    let completed = counted_completions(schedule, start, end, done);
+    let rate = if expected > 0.0 {
+        (completed as f64 / expected).min(1.0)
+    } else {
+        0.0
+    };
+    PeriodRate {
+        days: (end - start).num_days() + 1,
+        start,
+        end,
+        completed,
+        expected,
+        rate,
+    }
+}
+
+/// Completion rate per ISO weekday (index 0 = Monday). `None` for weekdays
+/// the schedule never expects in the range.
+pub fn weekday_rates(
+    schedule: &Schedule,
+    start: NaiveDate,
+    end: NaiveDate,
+    done: &DoneDays,
+) -> [Option<f64>; 7] {
+    let mut due = [0u32; 7];
+    let mut hit = [0u32; 7];
+    if end >= start {
+        for date in start.iter_days().take_while(|d| *d <= end) {
+            if !schedule.is_due(date) {
+                continue;
+            }
+            let i = date.weekday().num_days_from_monday() as usize;
+            due[i] += 1;
+            if done.contains_key(&date) {
+                hit[i] += 1;
+            }
+        }
+    }
+    let mut rates = [None; 7];
+    for i in 0..7 {
+        if due[i] > 0 {
+            rates[i] = Some(hit[i] as f64 / due[i] as f64);
+        }
+    }
+    rates
+}
+
+/// Best and worst weekday indices. Weekdays without data are skipped, ties
+/// go to the earlier weekday, and there is no worst day when every weekday
+/// scored the same.
+pub fn best_and_worst(rates: &[Option<f64>; 7]) -> (Option<usize>, Option<usize>) {
+    let mut best: Option<(usize, f64)> = None;
+    let mut worst: Option<(usize, f64)> = None;
+    for (i, rate) in rates.iter().enumerate() {
+        let Some(rate) = *rate else { continue };
+        if best.map_or(true, |(_, b)| rate > b) {
+            best = Some((i, rate));
+        }
+        if worst.map_or(true, |(_, w)| rate < w) {
+            worst = Some((i, rate));
+        }
+    }
+    match (best, worst) {
+        (Some((b, br)), Some((w, wr))) if wr < br => (Some(b), Some(w)),
+        (Some((b, _)), _) => (Some(b), None),
+        _ => (None, None),
+    }
+}
+
+/// Longest run of completed days. For specific-weekday schedules the run
+/// only has to cover scheduled days; otherwise days must be consecutive.
+pub fn longest_streak_range(schedule: &Schedule, done: &DoneDays) -> Option<StreakRange> {
+    let mut best: Option<StreakRange> = None;
+    let mut current: Option<StreakRange> = None;
+
+    let dates: Vec<NaiveDate> = done
+        .keys()
+        .copied()
+        .filter(|d| schedule.is_due(*d))
+        .collect();
+
+    for date in dates {
+        let extends = match &current {
+            Some(run) => previous_due_day(schedule, date) == Some(run.end),
+            None => false,
+        };
+        current = match current {
+            Some(mut run) if extends => {
+                run.end = date;
+                run.length += 1;
+                Some(run)
+            }
+            _ => Some(StreakRange {
+                start: date,
+                end: date,
+                length: 1,
+            }),
+        };
+        if let Some(run) = &current {
+            if best.as_ref().map_or(true, |b| run.length > b.length) {
+                best = Some(run.clone());
+            }
+        }
+    }
+    best
+}
+
+fn previous_due_day(schedule: &Schedule, date: NaiveDate) -> Option<NaiveDate> {
+    match schedule {
+        Schedule::WeeklyDays(_) => (1..=7)
+            .map(|n| date - chrono::Duration::days(n))
+            .find(|d| schedule.is_due(*d)),
+        _ => date.pred_opt(),
+    }
+}
+
+/// A habit's active span and history, for day-by-day tallies.
+pub struct HabitDays<'a> {
+    pub schedule: Schedule,
+    /// Creation day
+    pub from: NaiveDate,
+    /// Archive day; the habit stops counting from this date on
+    pub until: Option<NaiveDate>,
+    /// Weight in the daily score (`Habit::weight`)
+    pub weight: i64,
+    pub done: &'a DoneDays,
+}
+
+impl<'a> HabitDays<'a> {
+    pub fn new(habit: &Habit, done: &'a DoneDays) -> Self {
+        Self {
+            schedule: Schedule::from_habit(habit),
+            from: habit.created_at.date_naive(),
+            until: habit.archived_at.map(|t| t.date_naive()),
+            weight: habit.weight(),
+            done,
+        }
+    }
+
+    pub fn is_active(&self, date: NaiveDate) -> bool {
+        date >= self.from && self.until.map_or(true, |until| date < until)
+    }
+
+    /// Whether the habit counts on `date` by the rules of `daily_tallies`.
+    pub fn counts_on(&self, date: NaiveDate, first: WeekStart) -> bool {
+        match self.schedule {
+            Schedule::WeeklyTarget(_) => {
+                let needed = self.still_needed(date, first);
+                let days_left =
+                    (week_start_for(date, first) + chrono::Duration::days(6) - date).num_days() + 1;
+                needed > 0 && (self.done.contains_key(&date) || needed >= days_left)
+            }
+            _ => self.schedule.is_due(date),
+        }
+    }
+
+    /// Weekly-target sessions still open on `date` (done earlier that week
+    /// don't count towards it); 0 for other schedules.
+    pub fn still_needed(&self, date: NaiveDate, first: WeekStart) -> i64 {
+        match self.schedule {
+            Schedule::WeeklyTarget(times) => {
+                let week = week_start_for(date, first);
+                times as i64 - self.done.range(week..date).count() as i64
+            }
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DayTally {
+    pub date: NaiveDate,
+    /// Habits that counted on this date
+    pub due: i64,
+    pub completed: i64,
+    /// The same, summed by habit weight
+    pub due_weight: i64,
+    pub completed_weight: i64,
+}
+
+/// Due and completed habit counts for each date in `[start, end]`.
+///
+/// A habit counts only while it existed and wasn't archived, and only on
+/// its scheduled days. A weekly-target habit counts on days it was done
+/// while the week's quota was still open, and as missed only once the quota
+/// can no longer be met without that day — so resting early in the week
+/// isn't a miss, but leaving three sessions for the last two days is.
+pub fn daily_tallies(
+    habits: &[HabitDays],
+    start: NaiveDate,
+    end: NaiveDate,
+    first: WeekStart,
+) -> Vec<DayTally> {
+    if end < start {
+        return Vec::new();
+    }
+    start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .map(|date| {
+            let mut tally = DayTally {
+                date,
+                due: 0,
+                completed: 0,
+                due_weight: 0,
+                completed_weight: 0,
+            };
+            for habit in habits.iter().filter(|h| h.is_active(date)) {
+                let done_today = habit.done.contains_key(&date);
+                if habit.counts_on(date, first) {
+                    tally.due += 1;
+                    tally.due_weight += habit.weight;
+                    if done_today {
+                        tally.completed += 1;
+                        tally.completed_weight += habit.weight;
+                    }
+                }
+            }
+            tally
+        })
+        .collect()
+}
+
+/// Weighted daily score, 0–100 to one decimal; None when nothing was due.
+pub fn score(completed_weight: i64, due_weight: i64) -> Option<f64> {
+    (due_weight > 0).then(|| (completed_weight as f64 * 1000.0 / due_weight as f64).round() / 10.0)
+}
+
+/// Score over the trailing `window` days ending at each day of `days`
+/// (`(completed_weight, due_weight)` per consecutive day). Weights are
+/// summed across the window rather than averaging daily scores, so a day
+/// with one small habit due doesn't swing the trend.
+pub fn rolling_scores(days: &[(i64, i64)], window: usize) -> Vec<Option<f64>> {
+    (0..days.len())
+        .map(|i| {
+            let from = (i + 1).saturating_sub(window);
+            let (done, due) = days[from..=i]
+                .iter()
+                .fold((0, 0), |(c, d), (dc, dd)| (c + dc, d + dd));
+            score(done, due)
+        })
+        .collect()
+}
+
+/// Mean completion value over completed days in `[start, end]`.
+pub fn average_value(start: NaiveDate, end: NaiveDate, done: &DoneDays) -> Option<f64> {
+    if end < start {
+        return None;
+    }
+    let values: Vec<i32> = done.range(start..=end).map(|(_, v)| *v).collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().map(|v| *v as f64).sum::<f64>() / values.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    fn done(dates: &[NaiveDate]) -> DoneDays {
+        dates.iter().map(|d| (*d, 1)).collect()
+    }
+
+    // 2026-02-09 is a Monday
+    const MON: (i32, u32, u32) = (2026, 2, 9);
+
+    fn mon() -> NaiveDate {
+        d(MON.0, MON.1, MON.2)
+    }
+
+    #[test]
+    fn test_daily_rate_counts_every_day() {
+        let start = mon();
+        let end = start + chrono::Duration::days(9);
+        let days: Vec<_> = (0..5).map(|i| start + chrono::Duration::days(i)).collect();
+        let r = period_rate(&Schedule::Daily, start, end, &done(&days));
+        assert_eq!(r.days, 10);
+        assert_eq!(r.completed, 5);
+        assert_eq!(r.expected, 10.0);
+        assert!((r.rate - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weekly_days_ignores_unscheduled_completions() {
+        // Mon/Wed/Fri; completions on Mon, Tue, Fri
+        let schedule = Schedule::WeeklyDays([true, false, true, false, true, false, false]);
+        let start = mon();
+        let end = start + chrono::Duration::days(6);
+        let days = [
+            start,
+            start + chrono::Duration::days(1),
+            start + chrono::Duration::days(4),
+        ];
+        let r = period_rate(&schedule, start, end, &done(&days));
+        assert_eq!(r.expected, 3.0);
+        assert_eq!(r.completed, 2);
+    }
+
+    #[test]
+    fn test_weekly_target_caps_per_week() {
+        let schedule = Schedule::WeeklyTarget(3);
+        let start = mon();
+        let end = start + chrono::Duration::days(13);
+        // Five completions in week one, one in week two
+        let mut days: Vec<_> = (0..5).map(|i| start + chrono::Duration::days(i)).collect();
+        days.push(start + chrono::Duration::days(8));
+        let r = period_rate(&schedule, start, end, &done(&days));
+        assert_eq!(r.expected, 6.0);
+        assert_eq!(r.completed, 4);
+    }
+
+    #[test]
+    fn test_rate_is_capped_at_one() {
+        // Partial week: 3 days of a 3x/week target, all done
+        let schedule = Schedule::WeeklyTarget(3);
+        let start = mon();
+        let end = start + chrono::Duration::days(2);
+        let days: Vec<_> = (0..3).map(|i| start + chrono::Duration::days(i)).collect();
+        let r = period_rate(&schedule, start, end, &done(&days));
+        assert_eq!(r.rate, 1.0);
+    }
+
+    #[test]
+    fn test_best_and_worst_tie_breaks_to_earlier_weekday() {
+        let rates = [Some(0.5), Some(1.0), None, Some(1.0), Some(0.5), None, None];
+        assert_eq!(best_and_worst(&rates), (Some(1), Some(0)));
+    }
+
+    #[test]
+    fn test_best_and_worst_all_equal_has_no_worst() {
+        let rates = [Some(1.0), Some(1.0), None, None, None, None, None];
+        assert_eq!(best_and_worst(&rates), (Some(0), None));
+        assert_eq!(best_and_worst(&[None; 7]), (None, None));
+    }
+
+    #[test]
+    fn test_weekday_rates_skip_unscheduled_days() {
+        let schedule = Schedule::WeeklyDays([true, false, false, false, false, false, false]);
+        let start = mon();
+        let end = start + chrono::Duration::days(13);
+        let rates = weekday_rates(&schedule, start, end, &done(&[start]));
+        assert_eq!(rates[0], Some(0.5));
+        assert!(rates[1..].iter().all(|r| r.is_none()));
+    }
+
+    #[test]
+    fn test_longest_streak_range_daily() {
+        let start = mon();
+        let days = [
+            start,
+            start + chrono::Duration::days(1),
+            start + chrono::Duration::days(3),
+            start + chrono::Duration::days(4),
+            start + chrono::Duration::days(5),
+        ];
+        let run = longest_streak_range(&Schedule::Daily, &done(&days)).unwrap();
+        assert_eq!(run.start, start + chrono::Duration::days(3));
+        assert_eq!(run.length, 3);
+    }
+
+    #[test]
+    fn test_longest_streak_range_spans_unscheduled_days() {
+        // Mon/Wed/Fri done for two weeks
+        let schedule = Schedule::WeeklyDays([true, false, true, false, true, false, false]);
+        let start = mon();
+        let days: Vec<_> = [0, 2, 4, 7, 9, 11]
+            .iter()
+            .map(|i| start + chrono::Duration::days(*i))
+            .collect();
+        let run = longest_streak_range(&schedule, &done(&days)).unwrap();
+        assert_eq!(run.length, 6);
+        assert_eq!(run.end, start + chrono::Duration::days(11));
+    }
+
+    fn span(
+        schedule: Schedule,
+        from: NaiveDate,
+        until: Option<NaiveDate>,
+        done: &DoneDays,
+    ) -> HabitDays<'_> {
+        HabitDays {
+            schedule,
+            from,
+            until,
+            weight: 1,
+            done,
+        }
+    }
+
+    #[test]
+    fn test_daily_tallies_respect_lifecycle() {
+        let start = mon();
+        let end = start + chrono::Duration::days(6);
+        let none = DoneDays::new();
+        // Created Wednesday, archived Saturday
+        let habits = [span(
+            Schedule::Daily,
+            start + chrono::Duration::days(2),
+            Some(start + chrono::Duration::days(5)),
+            &none,
+        )];
+        let due: Vec<i64> = daily_tallies(&habits, start, end, WeekStart::Monday)
+            .iter()
+            .map(|t| t.due)
+            .collect();
+        assert_eq!(due, vec![0, 0, 1, 1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_daily_tallies_weekly_target_only_misses_when_forced() {
+        let start = mon();
+        let end = start + chrono::Duration::days(6);
+        // 3x/week, done Monday only: the last two sessions must land Sat/Sun
+        let days = done(&[start]);
+        let habits = [span(Schedule::WeeklyTarget(3), start, None, &days)];
+        let tallies = daily_tallies(&habits, start, end, WeekStart::Monday);
+        let due: Vec<i64> = tallies.iter().map(|t| t.due).collect();
+        assert_eq!(due, vec![1, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(tallies[0].completed, 1);
+
+        // Quota met by Wednesday: nothing counts afterwards
+        let days = done(&[
+            start,
+            start + chrono::Duration::days(1),
+            start + chrono::Duration::days(2),
+        ]);
+        let habits = [span(Schedule::WeeklyTarget(3), start, None, &days)];
+        let due: Vec<i64> = daily_tallies(&habits, start, end, WeekStart::Monday)
+            .iter()
+            .map(|t| t.due)
+            .collect();
+        assert_eq!(due, vec![1, 1, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_daily_tallies_sum_weights() {
+        let start = mon();
+        let days = done(&[start]);
+        let none = DoneDays::new();
+        let mut heavy = span(Schedule::Daily, start, None, &days);
+        heavy.weight = 9;
+        let habits = [heavy, span(Schedule::Daily, start, None, &none)];
+        let tally = &daily_tallies(&habits, start, start, WeekStart::Monday)[0];
+        assert_eq!((tally.due_weight, tally.completed_weight), (10, 9));
+        assert_eq!(score(tally.completed_weight, tally.due_weight), Some(90.0));
+        assert_eq!(score(0, 0), None);
+    }
+
+    #[test]
+    fn test_rolling_scores_sum_weights_over_window() {
+        let days = [(4, 4), (0, 0), (0, 4), (1, 1)];
+        assert_eq!(
+            rolling_scores(&days, 2),
+            vec![Some(100.0), Some(100.0), Some(0.0), Some(20.0)]
+        );
+    }
+
+    #[test]
+    fn test_average_value() {
+        let start = mon();
+        let mut days = DoneDays::new();
+        days.insert(start, 2);
+        days.insert(start + chrono::Duration::days(1), 4);
+        assert_eq!(
+            average_value(start, start + chrono::Duration::days(6), &days),
+            Some(3.0)
+        );
+        assert_eq!(
+            average_value(
+                start + chrono::Duration::days(2),
+                start + chrono::Duration::days(6),
+                &days
+            ),
+            None
+        );
+    }
+}
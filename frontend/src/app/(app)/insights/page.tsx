@@ -17,7 +17,10 @@ export default function InsightsPage() {
     setLoading(true);
     setError("");
     try {
-      const data = await api.get<InsightResponse>("/api/insights");
+      // This week's insight is cached; refreshing replaces it
+      const data = insights
+        ? await api.post<InsightResponse>("/api/insights/regenerate")
+        : await api.get<InsightResponse>("/api/insights");
       setInsights(data);
     } catch (err) {
       setError(
@@ -63,6 +63,12 @@ export interface HabitWithStatus extends Habit {
   is_due_today: boolean;
 }
 
+export interface TodayView {
+  date: string;
+  score: number | null;
+  habits: HabitWithStatus[];
+}
+
 export interface Completion {
   id: string;
   habit_id: string;
@@ -126,13 +132,26 @@ export interface DailyLog {
 }
 
 export interface InsightResponse {
+  id: string;
+  week_start_date: string;
+  generated_at: string;
   summary: string;
   wins: string[];
   improvements: string[];
-  mood_correlation: string | null;
+  mood_correlation?: string | null;
   streak_analysis: string;
   tip_of_the_week: string;
-  source: "claude" | "fallback";
+  source: "anthropic" | "openai" | "fake" | "claude" | "fallback";
+  quota?: InsightQuota;
+}
+
+export interface InsightQuota {
+  limit_per_week: number | null;
+  used_this_week: number;
+  remaining_this_week: number | null;
+  available: boolean;
+  next_available_at: string | null;
+  resets_at: string;
 }
 
 export interface SubscriptionInfo {